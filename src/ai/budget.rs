@@ -0,0 +1,86 @@
+//! Limits on how long a search is allowed to run, see [SearchBudget].
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A flag that can be used to stop a running search from another thread.
+///
+/// Clones share the same underlying flag. The flag is not reset automatically when a new search starts,
+/// call [StopFlag::reset] for that.
+#[derive(Clone, Default)]
+pub struct StopFlag(Arc<AtomicBool>);
+
+impl StopFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the searches using this flag to stop as soon as possible.
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Debug for StopFlag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StopFlag({})", self.is_stopped())
+    }
+}
+
+/// The budget for a search, the search stops as soon as any of the limits is reached.
+/// The default value has no limits at all.
+///
+/// Searches may always finish a minimal amount of work before they check the budget,
+/// so they can still return a useful result.
+#[derive(Debug, Clone, Default)]
+pub struct SearchBudget {
+    /// The maximum wall-clock time.
+    pub time: Option<Duration>,
+    /// The maximum number of visited nodes, the exact meaning depends on the search algorithm.
+    pub nodes: Option<u64>,
+    /// Stop once this flag is set.
+    pub stop: Option<StopFlag>,
+}
+
+impl SearchBudget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn time(time: Duration) -> Self {
+        SearchBudget {
+            time: Some(time),
+            ..Self::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        SearchBudget {
+            nodes: Some(nodes),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_stop(self, stop: StopFlag) -> Self {
+        SearchBudget {
+            stop: Some(stop),
+            ..self
+        }
+    }
+
+    /// Whether a search that started at `start` and has visited `nodes` nodes so far should stop.
+    pub fn is_exhausted(&self, start: Instant, nodes: u64) -> bool {
+        self.nodes.is_some_and(|max| nodes >= max)
+            || self.stop.as_ref().is_some_and(|stop| stop.is_stopped())
+            || self.time.is_some_and(|max| start.elapsed() >= max)
+    }
+}
@@ -1,16 +1,25 @@
-use std::fmt::{Debug, Formatter};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter, Write};
 use std::num::NonZeroUsize;
 use std::ops::{Index, IndexMut};
+use std::thread::JoinHandle;
+use std::time::Instant;
 
 use decorum::N32;
 use internal_iterator::{InternalIterator, IteratorExt};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::SmallRng;
 use rand::seq::IteratorRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_distr::Dirichlet;
 
+use crate::ai::budget::{SearchBudget, StopFlag};
+use crate::ai::rollout::{rollout, RandomRollout, RolloutPolicy};
 use crate::ai::Bot;
-use crate::board::{AltBoard, BoardDone, Outcome};
+use crate::board::{Board, BoardDone, Outcome, Player};
 use crate::pov::{NonPov, Pov};
-use crate::wdl::{OutcomeWDL, WDL};
+use crate::wdl::{OutcomeWDL, WDLAbs, WDL};
 
 #[derive(Debug, Copy, Clone)]
 pub struct IdxRange {
@@ -41,25 +50,36 @@ impl IntoIterator for IdxRange {
 
 /// Represents a node in the MCTS search tree.
 ///
-/// The outcome or wdl in this node are always from the POV of the player that just played `self.last_move`.
+/// The outcome or wdl in this node are always from the POV of the player that just played `self.last_move`,
+/// or more precisely the `next_player` of the parent board. For chance nodes this is the player that will play after
+/// the chance event.
 #[derive(Debug)]
 pub struct Node<M> {
     pub last_move: Option<M>,
     pub children: Option<IdxRange>,
     pub visits: i64,
+    /// The prior probability of `last_move` according to the policy of the [Evaluator], uniform by default.
+    /// Only used by [Selection::Puct].
+    pub prior: f32,
+    /// All-moves-as-first statistics, only collected with [MCTSConfig::rave].
+    /// The sum of the values of all simulations through the parent in which the same player played `last_move`
+    /// at any later point, from the POV of that player.
+    pub amaf: WDL<f64>,
     pub kind: SNodeKind,
 }
 
 #[derive(Debug)]
 pub enum SNodeKind {
-    Estimate(WDL<i64>),
+    /// The sum of all values backed up through this node.
+    /// These are whole game outcomes for rollouts but can be any WDL for other [Evaluator]s.
+    Estimate(WDL<f64>),
     Solved(OutcomeWDL),
 }
 
 impl<M> Node<M> {
     /// Create the correct type of `Node` for the given `outcome`.
     /// ´outcome` should be from the POV of the player that just played `last_move`.
-    fn new(last_move: Option<M>, outcome: Option<OutcomeWDL>) -> Self {
+    fn new(last_move: Option<M>, outcome: Option<OutcomeWDL>, prior: f32) -> Self {
         let kind = match outcome {
             None => SNodeKind::Estimate(WDL::default()),
             Some(outcome) => SNodeKind::Solved(outcome),
@@ -69,13 +89,15 @@ impl<M> Node<M> {
             last_move,
             visits: 0,
             children: None,
+            prior,
+            amaf: WDL::default(),
             kind,
         }
     }
 
     pub fn is_unvisited(&self) -> bool {
         match self.kind {
-            SNodeKind::Estimate(wdl) => wdl.sum() == 0,
+            SNodeKind::Estimate(wdl) => wdl.sum() == 0.0,
             SNodeKind::Solved(_) => false,
         }
     }
@@ -98,10 +120,15 @@ impl<M> Node<M> {
     }
 
     pub fn increment(&mut self, outcome: OutcomeWDL) {
+        self.increment_wdl(outcome.to_wdl());
+    }
+
+    /// Add a (possibly fractional) value to this node, from the POV of the player that just played `self.last_move`.
+    pub fn increment_wdl(&mut self, value: WDL<f32>) {
         self.visits += 1;
         match &mut self.kind {
             SNodeKind::Estimate(wdl) => {
-                *wdl += outcome.to_wdl();
+                *wdl += value.cast::<f64>();
             }
             SNodeKind::Solved(_) => {
                 panic!("Cannot increment solved node")
@@ -109,55 +136,109 @@ impl<M> Node<M> {
         }
     }
 
+    /// Add the statistics of `other`, a node for the same position in a different tree.
+    /// If `other` is solved this node becomes solved too.
+    fn merge(&mut self, other: &Node<M>) {
+        self.visits += other.visits;
+        self.amaf += other.amaf;
+        match (&mut self.kind, &other.kind) {
+            (_, &SNodeKind::Solved(outcome)) => self.kind = SNodeKind::Solved(outcome),
+            (SNodeKind::Estimate(wdl), &SNodeKind::Estimate(other_wdl)) => *wdl += other_wdl,
+            (SNodeKind::Solved(_), SNodeKind::Estimate(_)) => {}
+        }
+    }
+
+    fn flip_pov(&mut self) {
+        self.amaf = self.amaf.flip();
+        self.kind = match self.kind {
+            SNodeKind::Estimate(wdl) => SNodeKind::Estimate(wdl.flip()),
+            SNodeKind::Solved(outcome) => SNodeKind::Solved(outcome.flip()),
+        };
+    }
+
     /// The value of this node from the POV of the player that just played `self.last_move`.
     pub fn wdl(&self) -> WDL<f32> {
+        match self.kind {
+            SNodeKind::Estimate(wdl) => (wdl / wdl.sum()).cast::<f32>(),
+            SNodeKind::Solved(outcome) => outcome.to_wdl(),
+        }
+    }
+
+    /// The mean and variance of the value of this node, scaled to `0..1`.
+    /// Returns NaN for unvisited nodes.
+    fn unit_stats(&self) -> (f32, f32) {
         match self.kind {
             SNodeKind::Estimate(wdl) => {
                 let visits = wdl.sum();
-                wdl.cast::<f32>() / visits as f32
+                let mean = (wdl.win + 0.5 * wdl.draw) / visits;
+                let mean_square = (wdl.win + 0.25 * wdl.draw) / visits;
+                (mean as f32, (mean_square - mean * mean).max(0.0) as f32)
             }
-            SNodeKind::Solved(outcome) => outcome.to_wdl(),
+            SNodeKind::Solved(outcome) => ((outcome.sign::<f32>() + 1.0) / 2.0, 0.0),
+        }
+    }
+
+    /// The unit mean value of this node, blended with the AMAF statistics if `rave` is set.
+    /// Returns `None` if there is no data at all yet.
+    fn rave_mean(&self, rave: Option<Rave>) -> Option<f32> {
+        let (mean, _) = self.unit_stats();
+        let amaf_visits = self.amaf.sum();
+
+        match rave {
+            Some(rave) if amaf_visits > 0.0 => {
+                let amaf_mean = ((self.amaf.win + 0.5 * self.amaf.draw) / amaf_visits) as f32;
+                if mean.is_nan() {
+                    return Some(amaf_mean);
+                }
+
+                let visits = self.visits as f32;
+                let beta = (rave.equivalence / (3.0 * visits + rave.equivalence)).sqrt();
+                Some((1.0 - beta) * mean + beta * amaf_mean)
+            }
+            _ => (!mean.is_nan()).then_some(mean),
         }
     }
 
-    /// Return the uct value of this code.
+    /// Return the value used to select the child to explore, according to `config.selection`.
+    /// `fpu` is the unit value for unvisited nodes, see [Fpu].
     ///
     /// For solved nodes this is just the unit value, with no exploration bonus. This is equivalent to
     /// a child node that's visited an infinite amount of times (together with the parent node).
-    fn uct(&self, parent_visits: i64, exploration_weight: f32) -> f32 {
-        //TODO continue investigating this, what uct value to use for solved (in practice lost and drawn) nodes?
-        // if exploration_weight < 0.0 {
-        //     let value_unit = (self.wdl().value() + 1.0) / 2.0;
-        //     let explore = ((parent_visits as f32).ln() / self.visits as f32).sqrt();
-        //
-        //     return value_unit - exploration_weight * explore;
-        // }
-
-        match self.kind {
-            SNodeKind::Estimate(wdl) => {
-                let visits = wdl.sum() as f32;
-                let value = wdl.cast::<f32>().value() / visits;
-                let value_unit = (value + 1.0) / 2.0;
-
-                let explore = ((parent_visits as f32).ln() / visits).sqrt();
+    /// As a result lost children get the lowest possible value and are in practice only selected once all siblings
+    /// are lost too, and drawn children compete with their unsolved siblings on value alone.
+    fn selection_value(&self, config: &MCTSConfig, parent_visits: i64, fpu: f32) -> f32 {
+        let visits = match self.kind {
+            SNodeKind::Estimate(wdl) => wdl.sum() as f32,
+            SNodeKind::Solved(_) => return self.unit_stats().0,
+        };
 
-                value_unit + exploration_weight * explore
+        let c = config.exploration_weight;
+        let parent_visits = parent_visits as f32;
+        let bias = config.progressive_bias * self.prior / (1.0 + visits);
+        let mean = self.rave_mean(config.rave).unwrap_or(fpu);
+
+        match config.selection {
+            Selection::Puct => mean + c * self.prior * parent_visits.sqrt() / (1.0 + visits),
+            Selection::Uct | Selection::Ucb1Tuned if visits == 0.0 => mean,
+            Selection::Uct => mean + c * (parent_visits.ln() / visits).sqrt() + bias,
+            Selection::Ucb1Tuned => {
+                let (_, variance) = self.unit_stats();
+                let log_ratio = parent_visits.ln() / visits;
+                let variance_bound = f32::min(0.25, variance + (2.0 * log_ratio).sqrt());
+                mean + c * (log_ratio * variance_bound).sqrt() + bias
             }
-            SNodeKind::Solved(outcome) => (outcome.sign::<f32>() + 1.0) / 2.0,
         }
     }
 }
 
-// TODO extend mcts to non-alternating games
-
 /// A small wrapper type for `Vec<SNode>` that uses u64 for indexing instead.
 #[derive(Debug)]
-pub struct Tree<B: AltBoard> {
+pub struct Tree<B: Board> {
     pub root_board: B,
     pub nodes: Vec<Node<B::Move>>,
 }
 
-impl<B: AltBoard> Tree<B> {
+impl<B: Board> Tree<B> {
     pub fn new(root_board: B) -> Self {
         Tree {
             root_board,
@@ -185,6 +266,182 @@ impl<B: AltBoard> Tree<B> {
         self[best_child].last_move.unwrap()
     }
 
+    /// Pick the best child of the root according to `final_move`.
+    /// A proven winning child is always picked first, and unvisited children are never picked by value.
+    pub fn best_child_by(&self, final_move: FinalMove) -> usize {
+        let children = self[0].children.expect("Root node must have children");
+
+        let won_child = children.iter().find(|&c| self[c].solution() == Some(OutcomeWDL::Win));
+        if let Some(win_child) = won_child {
+            return win_child;
+        }
+
+        let key = |c: usize| -> f32 {
+            let node = &self[c];
+            let (mean, variance) = node.unit_stats();
+            match final_move {
+                FinalMove::Visits => node.visits as f32,
+                _ if node.is_unvisited() => f32::NEG_INFINITY,
+                FinalMove::Value => mean,
+                FinalMove::Lcb(z) => mean - z * (variance / node.visits as f32).sqrt(),
+            }
+        };
+
+        children.iter().max_by_key(|&c| N32::from(key(c))).unwrap()
+    }
+
+    /// Pick the move to play after a search with `config`. If the temperature is positive the move is sampled
+    /// with probability proportional to `visits^(1 / temperature)`, otherwise it's picked with [Tree::best_child_by].
+    pub fn select_move(&self, config: &MCTSConfig, rng: &mut impl Rng) -> B::Move {
+        let children = self[0].children.expect("Root node must have children");
+
+        let picked = if config.temperature > 0.0 && self[0].solution().is_none() {
+            let weights: Vec<f64> = children
+                .iter()
+                .map(|c| (self[c].visits as f64).powf(1.0 / config.temperature as f64))
+                .collect();
+            match WeightedIndex::new(&weights) {
+                Ok(distr) => children.get(distr.sample(rng)),
+                Err(_) => self.best_child_by(config.final_move),
+            }
+        } else {
+            self.best_child_by(config.final_move)
+        };
+
+        self[picked].last_move.unwrap()
+    }
+
+    /// Mix Dirichlet noise into the priors of the children of the root, see [MCTSConfig::root_noise].
+    pub fn add_root_noise(&mut self, noise: DirichletNoise, rng: &mut impl Rng) {
+        let children = self[0].children.expect("Root node must have children");
+        if children.length < 2 || self.root_board.is_chance_node() {
+            return;
+        }
+
+        let sample = Dirichlet::new_with_size(noise.alpha, children.length)
+            .expect("Invalid Dirichlet noise alpha")
+            .sample(rng);
+        for (c, eta) in children.iter().zip(sample) {
+            let prior = &mut self.nodes[c].prior;
+            *prior = (1.0 - noise.fraction) * *prior + noise.fraction * eta;
+        }
+    }
+
+    /// Find the node for `board` by playing moves from the root, at most `max_depth` of them.
+    /// Only children that have already been expanded are considered.
+    /// Returns the moves that lead to `board`, or `None` if it is not in the tree.
+    pub fn find_moves_to(&self, board: &B, max_depth: u32) -> Option<Vec<B::Move>> {
+        let mut moves = vec![];
+        self.find_moves_to_impl(0, &self.root_board, board, max_depth, &mut moves)
+            .then_some(moves)
+    }
+
+    fn find_moves_to_impl(&self, node: usize, curr: &B, target: &B, depth_left: u32, moves: &mut Vec<B::Move>) -> bool {
+        if curr == target {
+            return true;
+        }
+        if depth_left == 0 {
+            return false;
+        }
+
+        let children = match self[node].children {
+            Some(children) => children,
+            None => return false,
+        };
+
+        for child in children {
+            let mv = self[child].last_move.unwrap();
+            let next = curr.clone_and_play(mv).unwrap();
+
+            moves.push(mv);
+            if self.find_moves_to_impl(child, &next, target, depth_left - 1, moves) {
+                return true;
+            }
+            moves.pop();
+        }
+
+        false
+    }
+
+    /// The node reached by playing `moves` from the root,
+    /// or `None` if any node along the way was not expanded yet.
+    pub fn find_node(&self, moves: &[B::Move]) -> Option<usize> {
+        moves.iter().try_fold(0, |node, &mv| {
+            let children = self[node].children?;
+            children.iter().find(|&c| self[c].last_move == Some(mv))
+        })
+    }
+
+    /// Make the node reached by playing `moves` from the root the new root, keeping only its subtree.
+    /// The nodes are compacted into a new arena, so all existing node indices are invalidated.
+    ///
+    /// Returns `false` and leaves the tree unchanged if any node along the way was not expanded yet.
+    pub fn reroot(&mut self, moves: &[B::Move]) -> bool {
+        let node = match self.find_node(moves) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        let mut board = self.root_board.clone();
+        let mut prev_player = board.next_player().other();
+        for &mv in moves {
+            prev_player = board.next_player();
+            board.play(mv).unwrap();
+        }
+
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+        let mut nodes = vec![std::mem::replace(&mut old_nodes[node], Node::new(None, None, 0.0))];
+        nodes[0].last_move = None;
+
+        // the root node has to be from the POV of the player before `next_player`, see [mcts_build_tree]
+        if prev_player != board.next_player().other() {
+            nodes[0].flip_pov();
+        }
+
+        // breadth-first so the children of each node stay next to each other
+        let mut todo = VecDeque::from([0]);
+        while let Some(new) = todo.pop_front() {
+            // the moved node still refers to its children in the old arena
+            if let Some(children) = nodes[new].children {
+                let start = NonZeroUsize::new(nodes.len()).unwrap();
+                for (i, child) in children.iter().enumerate() {
+                    nodes.push(std::mem::replace(&mut old_nodes[child], Node::new(None, None, 0.0)));
+                    todo.push_back(start.get() + i);
+                }
+                nodes[new].children = Some(IdxRange {
+                    start,
+                    length: children.length,
+                });
+            }
+        }
+
+        self.root_board = board;
+        self.nodes = nodes;
+        true
+    }
+
+    /// Add the statistics of the root and its children in `other`, a tree for the same root board, to this tree.
+    /// Deeper nodes are not merged, they keep the statistics of this tree only.
+    /// This is used to combine the results of [root parallelism](mcts_expand_tree_parallel).
+    pub fn merge_root(&mut self, other: &Tree<B>) {
+        assert_eq!(
+            self.root_board, other.root_board,
+            "Can only merge trees with the same root"
+        );
+
+        self.nodes[0].merge(&other[0]);
+
+        if let Some(other_children) = other[0].children {
+            let children = self[0].children.expect("Root node must have children");
+            assert_eq!(children.length, other_children.length);
+
+            for (c, other_c) in children.iter().zip(other_children) {
+                assert_eq!(self[c].last_move, other[other_c].last_move);
+                self.nodes[c].merge(&other[other_c]);
+            }
+        }
+    }
+
     /// The wdl of `root_board` from the POV of `root_board.next_player`.
     pub fn wdl(&self) -> WDL<f32> {
         // the evaluation of the starting board is the opposite of the the evaluation of the root node,
@@ -230,9 +487,153 @@ impl<B: AltBoard> Tree<B> {
             }
         }
     }
+
+    /// Export the part of the tree selected by `filter` as a [Graphviz](https://graphviz.org/) DOT graph.
+    ///
+    /// Each node is labeled with its move, visit count and either its WDL or its solution,
+    /// from the POV of the player that played the move. Solved nodes are colored.
+    pub fn to_dot(&self, filter: ExportFilter) -> String {
+        let mut result = String::new();
+        writeln!(&mut result, "digraph mcts {{").unwrap();
+        writeln!(&mut result, "    node [shape=box];").unwrap();
+        self.to_dot_impl(&mut result, 0, 0, filter);
+        writeln!(&mut result, "}}").unwrap();
+        result
+    }
+
+    fn to_dot_impl(&self, result: &mut String, node: usize, depth: u32, filter: ExportFilter) {
+        let n = &self[node];
+
+        let mv = n.last_move.map_or("root".to_owned(), |mv| mv.to_string());
+        let (value, color) = match n.solution() {
+            None => {
+                let wdl = n.wdl();
+                (format!("{:.3}, {:.3}, {:.3}", wdl.win, wdl.draw, wdl.loss), None)
+            }
+            Some(outcome) => {
+                let color = match outcome {
+                    OutcomeWDL::Win => "palegreen",
+                    OutcomeWDL::Draw => "lightgray",
+                    OutcomeWDL::Loss => "lightpink",
+                };
+                (format!("{:?}", outcome), Some(color))
+            }
+        };
+
+        let label = format!("{}\\nvisits: {}\\n{}", dot_escape(&mv), n.visits, value);
+        write!(result, "    n{} [label=\"{}\"", node, label).unwrap();
+        if let Some(color) = color {
+            write!(result, ", style=filled, fillcolor={}", color).unwrap();
+        }
+        writeln!(result, "];").unwrap();
+
+        for child in self.exported_children(node, depth, filter) {
+            writeln!(result, "    n{} -> n{};", node, child).unwrap();
+            self.to_dot_impl(result, child, depth + 1, filter);
+        }
+    }
+
+    /// Export the part of the tree selected by `filter` as JSON.
+    ///
+    /// The result is an object with the `root_board` as a string and the nested `root` node.
+    /// Each node has the fields `move` (`null` for the root), `visits`, `wdl` (`null` if unvisited),
+    /// `solution` (`null` if not solved) and `children`.
+    /// The values are from the POV of the player that played the move, like [Node::wdl] and [Node::solution].
+    pub fn to_json(&self, filter: ExportFilter) -> String {
+        let mut result = String::new();
+        write!(
+            &mut result,
+            "{{\"root_board\":{},\"root\":",
+            json_string(&self.root_board.to_string())
+        )
+        .unwrap();
+        self.to_json_impl(&mut result, 0, 0, filter);
+        write!(&mut result, "}}").unwrap();
+        result
+    }
+
+    fn to_json_impl(&self, result: &mut String, node: usize, depth: u32, filter: ExportFilter) {
+        let n = &self[node];
+
+        let mv = n.last_move.map_or("null".to_owned(), |mv| json_string(&mv.to_string()));
+        let wdl = if n.is_unvisited() {
+            "null".to_owned()
+        } else {
+            let wdl = n.wdl();
+            format!("{{\"win\":{},\"draw\":{},\"loss\":{}}}", wdl.win, wdl.draw, wdl.loss)
+        };
+        let solution = n
+            .solution()
+            .map_or("null".to_owned(), |s| json_string(&format!("{:?}", s)));
+
+        write!(
+            result,
+            "{{\"move\":{},\"visits\":{},\"wdl\":{},\"solution\":{},\"children\":[",
+            mv, n.visits, wdl, solution
+        )
+        .unwrap();
+        for (i, child) in self.exported_children(node, depth, filter).enumerate() {
+            if i != 0 {
+                write!(result, ",").unwrap();
+            }
+            self.to_json_impl(result, child, depth + 1, filter);
+        }
+        write!(result, "]}}").unwrap();
+    }
+
+    fn exported_children(&self, node: usize, depth: u32, filter: ExportFilter) -> impl Iterator<Item = usize> + '_ {
+        let children = self[node].children.filter(|_| depth < filter.max_depth);
+        children
+            .into_iter()
+            .flat_map(|c| c.iter())
+            .filter(move |&c| self[c].visits >= filter.min_visits)
+    }
+}
+
+/// Selects the part of a [Tree] that is exported by [Tree::to_dot] and [Tree::to_json].
+/// The root is always included.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ExportFilter {
+    /// The maximum depth of the exported nodes, the children of the root have depth 1.
+    pub max_depth: u32,
+    /// Nodes with fewer visits are skipped, together with their subtree.
+    pub min_visits: i64,
+}
+
+impl ExportFilter {
+    pub fn new(max_depth: u32, min_visits: i64) -> Self {
+        ExportFilter { max_depth, min_visits }
+    }
 }
 
-impl<B: AltBoard> Index<usize> for Tree<B> {
+/// Export all visited nodes.
+impl Default for ExportFilter {
+    fn default() -> Self {
+        ExportFilter::new(u32::MAX, 1)
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if c.is_control() => write!(&mut result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+impl<B: Board> Index<usize> for Tree<B> {
     type Output = Node<B::Move>;
 
     fn index(&self, index: usize) -> &Self::Output {
@@ -240,20 +641,195 @@ impl<B: AltBoard> Index<usize> for Tree<B> {
     }
 }
 
-impl<B: AltBoard> IndexMut<usize> for Tree<B> {
+impl<B: Board> IndexMut<usize> for Tree<B> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.nodes[index]
     }
 }
 
-fn random_playout<B: AltBoard>(mut board: B, rng: &mut impl Rng) -> Outcome {
-    assert!(!board.is_done(), "should never start random playout on a done board");
+/// The settings for an MCTS search, see [mcts_build_tree_with].
+///
+/// Values are scaled to `0..1` in all formulas, `Q` is the mean value of a child, `N` and `n` are the visit counts
+/// of the parent and the child and `P` is the prior of the child.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MCTSConfig {
+    /// The weight `c` of the exploration term of the selection formula.
+    pub exploration_weight: f32,
+    pub selection: Selection,
+    pub fpu: Fpu,
+    /// The weight of the progressive bias term `P / (1 + n)` added to [Selection::Uct] and [Selection::Ucb1Tuned].
+    /// If this is not zero nodes are expanded as soon as they are evaluated, like for [Selection::Puct].
+    pub progressive_bias: f32,
+    pub final_move: FinalMove,
+    /// Dirichlet noise mixed into the root priors at the start of each search, for self-play data generation.
+    /// Only has an effect if the priors are used.
+    pub root_noise: Option<DirichletNoise>,
+    /// The temperature for sampling the final move, see [Tree::select_move]. Zero means no sampling.
+    pub temperature: f32,
+    /// Collect all-moves-as-first statistics and blend them into the `Q` term of the selection formula.
+    pub rave: Option<Rave>,
+}
+
+/// Settings for [RAVE](https://www.cs.utexas.edu/~pstone/Courses/394Rspring13/resources/mcrave.pdf),
+/// which blends the AMAF value of a child into its value as `(1 - beta) * Q + beta * Q_amaf`,
+/// with `beta = sqrt(equivalence / (3 * n + equivalence))`.
+///
+/// The AMAF statistics are collected from the moves played in the tree and the moves reported by the
+/// [Evaluator], see [Evaluation::played_moves]. Children without visits but with AMAF statistics
+/// use their AMAF value instead of the first-play urgency.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rave {
+    /// The number of visits at which the AMAF value and the real value are weighted equally.
+    pub equivalence: f32,
+}
+
+/// The formula used to pick the child to explore next.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Selection {
+    /// UCT, `Q + c * sqrt(ln(N) / n)`.
+    Uct,
+    /// UCB1-tuned, `Q + c * sqrt(ln(N) / n * min(1/4, V + sqrt(2 * ln(N) / n)))`, where `V` is the variance of
+    /// the value of the child. The original formula uses `c = 1`.
+    Ucb1Tuned,
+    /// PUCT as used by AlphaZero, `Q + c * P * sqrt(N) / (1 + n)`. Nodes are expanded as soon as they are evaluated,
+    /// with the priors from the policy of the [Evaluator].
+    Puct,
+}
+
+/// First-play urgency, the value of children that have not been visited yet.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Fpu {
+    /// Explore all unvisited children first, in random order.
+    Infinite,
+    /// A fixed unit value. For UCT and UCB1-tuned this replaces the entire selection value,
+    /// for PUCT only the `Q` term.
+    Fixed(f32),
+    /// The unit value of the parent minus this reduction, as used by Leela Zero.
+    Parent(f32),
+}
+
+/// How the move is picked from the root after the search is done.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FinalMove {
+    /// The most visited child.
+    Visits,
+    /// The child with the highest mean value.
+    Value,
+    /// The child with the highest lower confidence bound `Q - z * sqrt(V / n)`.
+    Lcb(f32),
+}
+
+/// Dirichlet noise added to the root priors, `P = (1 - fraction) * P + fraction * Dir(alpha)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DirichletNoise {
+    pub alpha: f32,
+    pub fraction: f32,
+}
+
+impl MCTSConfig {
+    /// Classic UCT with random rollouts, exploring every child once before revisiting any of them.
+    pub fn uct(exploration_weight: f32) -> Self {
+        MCTSConfig {
+            exploration_weight,
+            selection: Selection::Uct,
+            fpu: Fpu::Infinite,
+            progressive_bias: 0.0,
+            final_move: FinalMove::Visits,
+            root_noise: None,
+            temperature: 0.0,
+            rave: None,
+        }
+    }
+
+    /// PUCT as used by AlphaZero, unvisited children are assumed to be a draw.
+    pub fn puct(exploration_weight: f32) -> Self {
+        MCTSConfig {
+            selection: Selection::Puct,
+            fpu: Fpu::Fixed(0.5),
+            ..Self::uct(exploration_weight)
+        }
+    }
+
+    /// Whether the selection formula uses the priors of the evaluator.
+    fn uses_priors(&self) -> bool {
+        self.selection == Selection::Puct || self.progressive_bias != 0.0
+    }
+}
+
+impl Default for MCTSConfig {
+    fn default() -> Self {
+        Self::uct(2.0)
+    }
+}
 
-    loop {
-        board.play_random_available_move(rng).unwrap();
+/// The result of an [Evaluator].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation<M> {
+    /// The expected outcome from the POV of `board.next_player()`, should sum to one.
+    pub wdl: WDL<f32>,
+    /// Prior probabilities for `board.available_moves()`, in the same order. `None` means uniform priors.
+    /// Ignored for chance nodes, those use the probabilities of the chance event.
+    pub policy: Option<Vec<f32>>,
+    /// The moves played during the evaluation together with the player that played them, used for [Rave].
+    pub played_moves: Vec<(Player, M)>,
+}
 
-        if let Some(outcome) = board.outcome() {
-            return outcome;
+impl<M> Evaluation<M> {
+    pub fn new(wdl: WDL<f32>, policy: Option<Vec<f32>>) -> Self {
+        Evaluation {
+            wdl,
+            policy,
+            played_moves: vec![],
+        }
+    }
+}
+
+/// Evaluates new leaf nodes of the MCTS tree, for example using a neural network.
+/// [RolloutEvaluator] implements the classic random rollouts.
+///
+/// The search still proves nodes as won, drawn or lost when it reaches terminal boards,
+/// independent of the evaluator.
+pub trait Evaluator<B: Board> {
+    /// Evaluate `board`, which is never done but can be a chance node.
+    fn evaluate(&mut self, board: &B, rng: &mut impl Rng) -> Evaluation<B::Move>;
+}
+
+/// The default [Evaluator], plays random moves until the game is done and returns that outcome.
+/// The moves are reported in [Evaluation::played_moves], except for chance events.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RolloutEvaluator;
+
+impl<B: Board> Evaluator<B> for RolloutEvaluator {
+    fn evaluate(&mut self, board: &B, rng: &mut impl Rng) -> Evaluation<B::Move> {
+        PolicyRolloutEvaluator::new(RandomRollout).evaluate(board, rng)
+    }
+}
+
+/// An [Evaluator] that plays out games using a [RolloutPolicy] instead of random moves,
+/// for example to cut off long rollouts with a heuristic evaluation.
+/// The moves are reported in [Evaluation::played_moves], except for chance events.
+#[derive(Debug, Clone)]
+pub struct PolicyRolloutEvaluator<P> {
+    pub policy: P,
+}
+
+impl<P> PolicyRolloutEvaluator<P> {
+    pub fn new(policy: P) -> Self {
+        PolicyRolloutEvaluator { policy }
+    }
+}
+
+impl<B: Board, P: RolloutPolicy<B>> Evaluator<B> for PolicyRolloutEvaluator<P> {
+    fn evaluate(&mut self, board: &B, rng: &mut impl Rng) -> Evaluation<B::Move> {
+        assert!(!board.is_done(), "should never start rollout on a done board");
+
+        let mut played_moves = vec![];
+        let wdl = rollout(board, &mut self.policy, &mut played_moves, rng);
+
+        Evaluation {
+            wdl: wdl.pov(board.next_player()),
+            policy: None,
+            played_moves,
         }
     }
 }
@@ -261,112 +837,318 @@ fn random_playout<B: AltBoard>(mut board: B, rng: &mut impl Rng) -> Outcome {
 /// Run a single MCTS step.
 ///
 /// Returns `(result, proven)`, where
-/// * `result` is from the pov of the player that just played on `curr_board`.
+/// * `result` is the value of the simulated game.
 /// * `proven` is whether this result is fully proven
 ///
+/// `curr_pov` is the POV of the values in `curr_node`, see [Node].
 /// This function has already increments `curr_node` before it returns.
-fn mcts_solver_step<B: AltBoard>(
+///
+/// Moves are played on `curr_board` using [Board::play_scoped], so it is restored to its original state afterwards.
+///
+/// With [MCTSConfig::rave] the moves played below `curr_node` are appended to `played`.
+fn mcts_solver_step<B: Board>(
     tree: &mut Tree<B>,
     curr_node: usize,
-    curr_board: &B,
-    exploration_weight: f32,
+    curr_pov: Player,
+    curr_board: &mut B,
+    config: &MCTSConfig,
+    evaluator: &mut impl Evaluator<B>,
+    played: &mut Vec<(Player, B::Move)>,
     rng: &mut impl Rng,
-) -> (OutcomeWDL, bool) {
+) -> (WDLAbs<f32>, bool) {
     //TODO should we decrement visit count? -> meh, then we're pulling search time towards partially solved branches
     //TODO should we backprop all previous backpropped losses and draws as wins now? -> meh, then we're overestimating this entire branch
 
     if let Some(outcome) = tree[curr_node].solution() {
-        return (outcome, true);
+        return (outcome.un_pov(curr_pov).to_wdl_abs(), true);
     }
 
+    // the values of the children are from the POV of this player
+    let next_player = curr_board.next_player();
+
     // initialize children
     let children = match tree[curr_node].children {
         Some(children) => children,
+        // with priors nodes are expanded when they are evaluated, so this is the first visit of the root
+        None if config.uses_priors() => {
+            return evaluate_node(tree, curr_node, curr_pov, curr_board, config, evaluator, played, rng);
+        }
         None => {
-            let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
-
-            curr_board.children().unwrap().for_each(|(mv, next_board)| {
-                let outcome = next_board.outcome().pov(curr_board.next_player());
-                let node = Node::new(Some(mv), outcome);
-                tree.nodes.push(node);
-            });
-
-            let length = tree.nodes.len() - start.get();
-            let children = IdxRange { start, length };
-            tree[curr_node].children = Some(children);
-
             //TODO maybe do this even earlier, and immediately stop pushing nodes -> but then children are inconsistent :(
             //  so what? who care about children somewhere deep in the tree!
-            let outcome = OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal());
-            if let Some(outcome) = outcome.flip() {
-                tree[curr_node].mark_solved(outcome);
-                return (outcome, true);
-            } else {
-                children
+            let (children, solution) = expand_node(tree, curr_node, curr_board, None);
+            if let Some(outcome) = solution {
+                tree[curr_node].mark_solved(outcome.pov(curr_pov));
+                return (outcome.to_wdl_abs(), true);
             }
+            children
         }
     };
 
-    // check if there are unvisited children
-    let unvisited = children.iter().filter(|&c| tree[c].is_unvisited());
-    let picked_unvisited = unvisited.choose(rng);
-
-    // result is from the POV of curr_board.next_player
-    let (result, proven) = if let Some(picked_child) = picked_unvisited {
-        let picked_mv = tree[picked_child].last_move.unwrap();
-        let next_board = curr_board.clone_and_play(picked_mv).unwrap();
-
-        let outcome = random_playout(next_board, rng).pov(curr_board.next_player().other());
-        tree[picked_child].increment(outcome);
-
-        (outcome.flip(), false)
+    let picked = if curr_board.is_chance_node() {
+        // sample the chance outcome
+        pick_chance_child(tree, curr_board, children, rng)
     } else {
-        //pick the max-uct child
-        //TODO we're including lost and drawn nodes here, is there nothing better we can do?
-        // at least this is what the paper seems to suggest
-        let parent_visits = tree[curr_node].visits;
-
-        let picked = children
-            .iter()
-            .max_by_key(|&c| N32::from(tree[c].uct(parent_visits, exploration_weight)))
-            .unwrap();
+        // pick an unvisited child if there are any and they should be explored first
+        let unvisited = match config.fpu {
+            Fpu::Infinite => children.iter().filter(|&c| tree[c].is_unvisited()).choose(rng),
+            Fpu::Fixed(_) | Fpu::Parent(_) => None,
+        };
 
-        //continue recursing
-        let picked_mv = tree[picked].last_move.unwrap();
-        let next_board = curr_board.clone_and_play(picked_mv).unwrap();
+        unvisited.unwrap_or_else(|| {
+            //pick the max-uct child, solved children are included without exploration bonus
+            let parent_visits = tree[curr_node].visits;
+            let fpu = fpu_value(config.fpu, &tree[curr_node], curr_pov, next_player);
 
-        mcts_solver_step(tree, picked, &next_board, exploration_weight, rng)
+            children
+                .iter()
+                .max_by_key(|&c| N32::from(tree[c].selection_value(config, parent_visits, fpu)))
+                .unwrap()
+        })
     };
 
-    let result = result.flip();
+    let picked_mv = tree[picked].last_move.unwrap();
+
+    let (result, proven) = curr_board
+        .play_scoped(picked_mv, |next_board| {
+            if tree[picked].is_unvisited() {
+                // evaluate new nodes
+                evaluate_node(tree, picked, next_player, next_board, config, evaluator, played, rng)
+            } else {
+                // continue recursing
+                mcts_solver_step(tree, picked, next_player, next_board, config, evaluator, played, rng)
+            }
+        })
+        .unwrap();
+
+    if config.rave.is_some() && !curr_board.is_chance_node() {
+        played.push((next_player, picked_mv));
+        update_amaf(tree, children, next_player, played, result);
+    }
 
     if proven {
         //check if we can prove the current node as well
-        let outcome = OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal());
-        if let Some(outcome) = outcome.flip() {
-            tree[curr_node].mark_solved(outcome);
-            return (outcome, true);
+        if let Some(outcome) = solution_from_children(tree, curr_board, children) {
+            tree[curr_node].mark_solved(outcome.pov(curr_pov));
+            return (outcome.to_wdl_abs(), true);
+        }
+    }
+
+    tree[curr_node].increment_wdl(result.pov(curr_pov));
+    (result, false)
+}
+
+/// The unit value for unvisited children of `parent`, from the POV of `next_player`.
+fn fpu_value<M>(fpu: Fpu, parent: &Node<M>, parent_pov: Player, next_player: Player) -> f32 {
+    match fpu {
+        Fpu::Infinite => f32::INFINITY,
+        Fpu::Fixed(value) => value,
+        Fpu::Parent(reduction) => {
+            let (mean, _) = parent.unit_stats();
+            let mean = if mean.is_nan() { 0.5 } else { mean };
+            let mean = if parent_pov == next_player { mean } else { 1.0 - mean };
+            mean - reduction
+        }
+    }
+}
+
+/// Evaluate the new node `node` for `board` and increment it with the result.
+/// If the config uses priors the node is also expanded using the policy of the evaluation,
+/// which can immediately prove it.
+fn evaluate_node<B: Board>(
+    tree: &mut Tree<B>,
+    node: usize,
+    pov: Player,
+    board: &mut B,
+    config: &MCTSConfig,
+    evaluator: &mut impl Evaluator<B>,
+    played: &mut Vec<(Player, B::Move)>,
+    rng: &mut impl Rng,
+) -> (WDLAbs<f32>, bool) {
+    let evaluation = evaluator.evaluate(board, rng);
+    if config.rave.is_some() {
+        played.extend(evaluation.played_moves);
+    }
+
+    if config.uses_priors() {
+        let (_, solution) = expand_node(tree, node, board, evaluation.policy.as_deref());
+        if let Some(outcome) = solution {
+            tree[node].mark_solved(outcome.pov(pov));
+            return (outcome.to_wdl_abs(), true);
         }
     }
 
-    tree[curr_node].increment(result);
+    let result = evaluation.wdl.un_pov(board.next_player());
+    tree[node].increment_wdl(result.pov(pov));
     (result, false)
 }
 
-pub fn mcts_build_tree<B: AltBoard>(
+/// Add `result` to the AMAF statistics of the children whose move was played by `player` in `played`.
+fn update_amaf<B: Board>(
+    tree: &mut Tree<B>,
+    children: IdxRange,
+    player: Player,
+    played: &[(Player, B::Move)],
+    result: WDLAbs<f32>,
+) {
+    // a linear scan is fine here, playouts are short compared to the work of simulating them
+    let moves: Vec<B::Move> = played
+        .iter()
+        .filter(|&&(p, _)| p == player)
+        .map(|&(_, mv)| mv)
+        .collect();
+    let value = result.pov(player).cast::<f64>();
+
+    for c in children {
+        if tree[c].last_move.is_some_and(|mv| moves.contains(&mv)) {
+            tree.nodes[c].amaf += value;
+        }
+    }
+}
+
+/// Create the children of `node`, using `policy` as the priors if given or uniform priors otherwise.
+/// Returns the children and the outcome of `board` if it can already be proven from them.
+fn expand_node<B: Board>(
+    tree: &mut Tree<B>,
+    node: usize,
+    board: &mut B,
+    policy: Option<&[f32]>,
+) -> (IdxRange, Option<Outcome>) {
+    let next_player = board.next_player();
+    let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
+
+    let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+    let policy = policy.filter(|_| !board.is_chance_node());
+    if let Some(policy) = policy {
+        assert_eq!(
+            moves.len(),
+            policy.len(),
+            "Policy length must match the number of available moves"
+        );
+    }
+
+    let uniform = 1.0 / moves.len() as f32;
+    for (i, mv) in moves.into_iter().enumerate() {
+        let outcome = board.play_scoped(mv, |next_board| next_board.outcome()).unwrap();
+        let prior = policy.map_or(uniform, |policy| policy[i]);
+        tree.nodes.push(Node::new(Some(mv), outcome.pov(next_player), prior));
+    }
+
+    let length = tree.nodes.len() - start.get();
+    let children = IdxRange { start, length };
+    tree[node].children = Some(children);
+
+    (children, solution_from_children(tree, board, children))
+}
+
+/// Try to prove the outcome of `board` based on the solutions of its children.
+fn solution_from_children<B: Board>(tree: &Tree<B>, board: &B, children: IdxRange) -> Option<Outcome> {
+    let next_player = board.next_player();
+
+    if board.is_chance_node() {
+        // chance nodes are only solved if every possible outcome leads to the same result
+        let first = tree[children.get(0)].solution()?;
+        let all_same = children.iter().all(|c| tree[c].solution() == Some(first));
+        all_same.then(|| first.un_pov(next_player))
+    } else {
+        OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal()).un_pov(next_player)
+    }
+}
+
+/// Sample a child of a chance node according to the probabilities of the chance event.
+fn pick_chance_child<B: Board>(tree: &Tree<B>, board: &B, children: IdxRange, rng: &mut impl Rng) -> usize {
+    let mut left = rng.gen::<f32>();
+
+    for child in children {
+        left -= board.chance_probability(tree[child].last_move.unwrap());
+        if left < 0.0 {
+            return child;
+        }
+    }
+
+    // fallback for rounding errors
+    children.get(children.length - 1)
+}
+
+pub fn mcts_build_tree<B: Board>(
     root_board: &B,
     iterations: u64,
     exploration_weight: f32,
     rng: &mut impl Rng,
 ) -> Tree<B> {
     assert!(iterations > 0);
+
+    let mut tree = new_tree(root_board);
+    mcts_expand_tree(&mut tree, iterations, exploration_weight, rng);
+    tree
+}
+
+/// Variant of [mcts_build_tree] with a custom [MCTSConfig] and leaf [Evaluator],
+/// for example [MCTSConfig::puct] together with a neural network.
+pub fn mcts_build_tree_with<B: Board>(
+    root_board: &B,
+    iterations: u64,
+    config: &MCTSConfig,
+    evaluator: &mut impl Evaluator<B>,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    assert!(iterations > 0);
+
+    let mut tree = new_tree(root_board);
+    mcts_expand_tree_with(&mut tree, iterations, config, evaluator, rng);
+    tree
+}
+
+/// Variant of [mcts_build_tree] that keeps running iterations until `budget` is exhausted,
+/// see [mcts_expand_tree_budget].
+pub fn mcts_build_tree_budget<B: Board>(
+    root_board: &B,
+    budget: &SearchBudget,
+    exploration_weight: f32,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    let mut tree = new_tree(root_board);
+    mcts_expand_tree_budget(&mut tree, budget, exploration_weight, rng);
+    tree
+}
+
+/// A tree that only contains the root node.
+fn new_tree<B: Board>(root_board: &B) -> Tree<B> {
     assert!(!root_board.is_done());
 
     let mut tree = Tree::new(root_board.clone());
 
-    let root_outcome = root_board.outcome().map(|o| o.pov(root_board.next_player().other()));
+    // the root node has no parent, so pick the POV such that [Tree::wdl] works
+    let root_pov = root_board.next_player().other();
+    let root_outcome = root_board.outcome().pov(root_pov);
+
+    tree.nodes.push(Node::new(None, root_outcome, 1.0));
+    tree
+}
+
+/// Run `iterations` more MCTS steps on an existing tree, for example one that was reused with [Tree::reroot].
+pub fn mcts_expand_tree<B: Board>(tree: &mut Tree<B>, iterations: u64, exploration_weight: f32, rng: &mut impl Rng) {
+    let config = MCTSConfig::uct(exploration_weight);
+    mcts_expand_tree_with(tree, iterations, &config, &mut RolloutEvaluator, rng)
+}
+
+/// Variant of [mcts_expand_tree] with a custom [MCTSConfig] and leaf [Evaluator].
+/// The tree should always be expanded with the same selection settings.
+pub fn mcts_expand_tree_with<B: Board>(
+    tree: &mut Tree<B>,
+    iterations: u64,
+    config: &MCTSConfig,
+    evaluator: &mut impl Evaluator<B>,
+    rng: &mut impl Rng,
+) {
+    assert!(!tree.root_board.is_done());
+
+    let root_pov = tree.root_board.next_player().other();
 
-    tree.nodes.push(Node::new(None, root_outcome));
+    // a single scratch board is enough, every step restores it to the root state
+    let mut board = tree.root_board.clone();
+    let mut played = vec![];
+    let mut noise_added = false;
 
     for _ in 0..iterations {
         //we've solved the root node, so we're done
@@ -374,24 +1156,173 @@ pub fn mcts_build_tree<B: AltBoard>(
             break;
         }
 
-        mcts_solver_step(&mut tree, 0, root_board, exploration_weight, rng);
+        // the root might only be expanded during the first step
+        if let (Some(noise), false, Some(_)) = (config.root_noise, noise_added, tree[0].children) {
+            tree.add_root_noise(noise, rng);
+            noise_added = true;
+        }
+
+        played.clear();
+        mcts_solver_step(tree, 0, root_pov, &mut board, config, evaluator, &mut played, rng);
     }
+}
+
+/// Variant of [mcts_build_tree] that spreads the iterations over `threads` threads, see [mcts_expand_tree_parallel].
+pub fn mcts_build_tree_parallel<B: Board>(
+    root_board: &B,
+    iterations: u64,
+    exploration_weight: f32,
+    threads: usize,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    assert!(iterations > 0);
 
+    let mut tree = new_tree(root_board);
+    mcts_expand_tree_parallel(&mut tree, iterations, exploration_weight, threads, rng);
     tree
 }
 
-pub struct MCTSBot<R: Rng> {
+/// Run `iterations` more MCTS steps on an existing tree, spread over `threads` threads using root parallelism.
+///
+/// `tree` itself is expanded on the current thread, while every other thread builds an independent tree for the same
+/// root board. Afterwards those trees are merged into `tree` with [Tree::merge_root],
+/// so the root and its children contain the statistics of all threads.
+pub fn mcts_expand_tree_parallel<B: Board>(
+    tree: &mut Tree<B>,
     iterations: u64,
     exploration_weight: f32,
+    threads: usize,
+    rng: &mut impl Rng,
+) {
+    let config = MCTSConfig::uct(exploration_weight);
+    expand_tree_parallel(tree, iterations, &config, threads, rng)
+}
+
+/// Implementation of [mcts_expand_tree_parallel] for any [MCTSConfig], using rollouts on every thread.
+fn expand_tree_parallel<B: Board>(
+    tree: &mut Tree<B>,
+    iterations: u64,
+    config: &MCTSConfig,
+    threads: usize,
+    rng: &mut impl Rng,
+) {
+    assert!(threads > 0);
+    if iterations == 0 {
+        return;
+    }
+
+    // don't start threads that would not get any iterations
+    let threads = (threads as u64).min(iterations);
+    let thread_iterations = |thread: u64| iterations / threads + (thread < iterations % threads) as u64;
+
+    let root_board = tree.root_board.clone();
+    let seeds: Vec<u64> = (1..threads).map(|_| rng.gen()).collect();
+
+    let helper_trees: Vec<Tree<B>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (1..threads)
+            .zip(seeds)
+            .map(|(thread, seed)| {
+                let root_board = &root_board;
+                s.spawn(move || {
+                    let rng = &mut SmallRng::seed_from_u64(seed);
+                    mcts_build_tree_with(
+                        root_board,
+                        thread_iterations(thread),
+                        config,
+                        &mut RolloutEvaluator,
+                        rng,
+                    )
+                })
+            })
+            .collect();
+
+        mcts_expand_tree_with(tree, thread_iterations(0), config, &mut RolloutEvaluator, rng);
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("MCTS thread panicked"))
+            .collect()
+    });
+
+    for helper_tree in &helper_trees {
+        tree.merge_root(helper_tree);
+    }
+}
+
+/// Run MCTS steps on an existing tree until `budget` is exhausted or the root is solved.
+/// The node limit of the budget is the number of iterations.
+///
+/// At least one iteration is always run, so the root has children and [Tree::best_move] works.
+/// Returns the number of iterations that were run.
+pub fn mcts_expand_tree_budget<B: Board>(
+    tree: &mut Tree<B>,
+    budget: &SearchBudget,
+    exploration_weight: f32,
+    rng: &mut impl Rng,
+) -> u64 {
+    assert!(!tree.root_board.is_done());
+
+    let start = Instant::now();
+    let config = MCTSConfig::uct(exploration_weight);
+    let root_pov = tree.root_board.next_player().other();
+    let mut board = tree.root_board.clone();
+
+    let mut iterations = 0;
+    while iterations == 0 || !budget.is_exhausted(start, iterations) {
+        if tree[0].solution().is_some() {
+            break;
+        }
+
+        mcts_solver_step(
+            tree,
+            0,
+            root_pov,
+            &mut board,
+            &config,
+            &mut RolloutEvaluator,
+            &mut vec![],
+            rng,
+        );
+        iterations += 1;
+    }
+
+    iterations
+}
+
+/// The maximum number of moves between the previous root and the next board for a tree to be reused,
+/// typically the bot's own move and the opponent's reply.
+pub const MAX_REUSE_DEPTH: u32 = 2;
+
+/// Try to reuse `tree` for a search on `board`, see [Tree::reroot].
+fn reuse_tree<B: Board>(tree: Option<Tree<B>>, board: &B) -> Option<Tree<B>> {
+    let mut tree = tree?;
+    let moves = tree.find_moves_to(board, MAX_REUSE_DEPTH)?;
+    tree.reroot(&moves).then_some(tree)
+}
+
+/// A bot that runs MCTS for a fixed number of iterations per move.
+///
+/// With [MCTSBot::with_tree_reuse] the tree is kept between moves: if the next board can be reached from the
+/// previous root in at most [MAX_REUSE_DEPTH] moves the search continues from that subtree instead of starting over.
+///
+/// The bot can use multiple threads with [MCTSBot::with_threads], see [mcts_expand_tree_parallel],
+/// and other search settings with [MCTSBot::with_config].
+pub struct MCTSBot<R: Rng> {
+    iterations: u64,
+    config: MCTSConfig,
+    threads: usize,
+    reuse_tree: bool,
     rng: R,
+    /// The tree kept from the previous move, a `Tree<B>` for the board type the bot was last used with.
+    tree: Option<Box<dyn Any + Send>>,
 }
 
 impl<R: Rng> Debug for MCTSBot<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "MCTSBot {{ iterations: {}, exploration_weight: {} }}",
-            self.iterations, self.exploration_weight
+            "MCTSBot {{ iterations: {}, config: {:?}, threads: {}, reuse_tree: {} }}",
+            self.iterations, self.config, self.threads, self.reuse_tree
         )
     }
 }
@@ -401,19 +1332,209 @@ impl<R: Rng> MCTSBot<R> {
         assert!(iterations > 0);
         MCTSBot {
             iterations,
-            exploration_weight,
+            config: MCTSConfig::uct(exploration_weight),
+            threads: 1,
+            reuse_tree: false,
             rng,
+            tree: None,
         }
     }
 
-    pub fn build_tree<B: AltBoard>(&mut self, board: &B) -> Tree<B> {
-        mcts_build_tree(board, self.iterations, self.exploration_weight, &mut self.rng)
+    /// Enable or disable reusing the tree between moves.
+    pub fn with_tree_reuse(self, reuse_tree: bool) -> Self {
+        MCTSBot {
+            reuse_tree,
+            tree: None,
+            ..self
+        }
+    }
+
+    /// Use `config` instead of plain UCT. The evaluator is always [RolloutEvaluator].
+    pub fn with_config(self, config: MCTSConfig) -> Self {
+        MCTSBot {
+            config,
+            tree: None,
+            ..self
+        }
+    }
+
+    /// Spread the iterations for each move over `threads` threads,
+    /// for example `num_cpus::get()` to use all cores.
+    pub fn with_threads(self, threads: usize) -> Self {
+        assert!(threads > 0);
+        MCTSBot { threads, ..self }
+    }
+
+    /// Build a new tree for `board`, independent of the tree kept between moves.
+    pub fn build_tree<B: Board>(&mut self, board: &B) -> Tree<B> {
+        let mut tree = new_tree(board);
+        expand_tree_parallel(&mut tree, self.iterations, &self.config, self.threads, &mut self.rng);
+        tree
+    }
+
+    /// The tree kept from the previous move, if any and if it was built for the same board type.
+    pub fn tree<B: Board>(&self) -> Option<&Tree<B>> {
+        self.tree.as_ref()?.downcast_ref()
     }
 }
 
-impl<R: Rng, B: AltBoard> Bot<B> for MCTSBot<R> {
+impl<B: Board, R: Rng> Bot<B> for MCTSBot<R> {
     fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
         board.check_done()?;
-        Ok(self.build_tree(board).best_move())
+
+        if !self.reuse_tree {
+            let tree = self.build_tree(board);
+            return Ok(tree.select_move(&self.config, &mut self.rng));
+        }
+
+        let prev = self.tree.take().and_then(|tree| tree.downcast::<Tree<B>>().ok());
+        let tree = match reuse_tree(prev.map(|tree| *tree), board) {
+            Some(mut tree) => {
+                expand_tree_parallel(&mut tree, self.iterations, &self.config, self.threads, &mut self.rng);
+                tree
+            }
+            None => self.build_tree(board),
+        };
+
+        let mv = tree.select_move(&self.config, &mut self.rng);
+        self.tree = Some(Box::new(tree));
+        Ok(mv)
+    }
+}
+
+/// Runs MCTS searches within a [SearchBudget], for example to respect the time controls of the GTP and UAI interfaces.
+///
+/// The tree is reused between searches, like [MCTSBot] does with [MCTSBot::with_tree_reuse].
+/// If a ponder budget is set, the searcher keeps searching on a background thread after each move selected by
+/// [MCTSSearcher::select_move_within], using the opponent's time.
+/// The next search then continues from the subtree of the reply the opponent actually played.
+pub struct MCTSSearcher<B: Board, R: Rng> {
+    exploration_weight: f32,
+    ponder_budget: Option<SearchBudget>,
+    rng: R,
+    tree: Option<Tree<B>>,
+    ponder: Option<Ponder<B>>,
+}
+
+/// A background search that is running on another thread.
+struct Ponder<B: Board> {
+    stop: StopFlag,
+    handle: JoinHandle<Tree<B>>,
+}
+
+impl<B: Board, R: Rng> Debug for MCTSSearcher<B, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MCTSSearcher {{ exploration_weight: {}, ponder_budget: {:?}, pondering: {} }}",
+            self.exploration_weight,
+            self.ponder_budget,
+            self.is_pondering()
+        )
+    }
+}
+
+impl<B: Board, R: Rng> MCTSSearcher<B, R> {
+    pub fn new(exploration_weight: f32, rng: R) -> Self {
+        MCTSSearcher {
+            exploration_weight,
+            ponder_budget: None,
+            rng,
+            tree: None,
+            ponder: None,
+        }
+    }
+
+    /// Ponder within `budget` after every move selected by [MCTSSearcher::select_move_within].
+    /// The budget should include a node or time limit, the tree keeps growing while pondering.
+    pub fn with_pondering(mut self, budget: SearchBudget) -> Self {
+        self.ponder_budget = Some(budget);
+        self
+    }
+
+    /// Search `board` until `budget` is exhausted, continuing from the previous tree if possible.
+    /// Any running background search is stopped first.
+    pub fn search(&mut self, board: &B, budget: &SearchBudget) -> &Tree<B> {
+        self.stop_pondering();
+
+        let mut tree = reuse_tree(self.tree.take(), board).unwrap_or_else(|| new_tree(board));
+        mcts_expand_tree_budget(&mut tree, budget, self.exploration_weight, &mut self.rng);
+        self.tree.insert(tree)
+    }
+
+    /// Search `board` within `budget` and return the best move.
+    /// Starts pondering afterwards if a ponder budget is set, see [MCTSSearcher::with_pondering].
+    pub fn select_move_within(&mut self, board: &B, budget: &SearchBudget) -> Result<B::Move, BoardDone> {
+        board.check_done()?;
+
+        let mv = self.search(board, budget).best_move();
+        if let Some(ponder_budget) = self.ponder_budget.clone() {
+            self.start_pondering(mv, ponder_budget);
+        }
+        Ok(mv)
+    }
+
+    /// Start searching the board reached by playing `mv` on the current root on a background thread,
+    /// until `budget` is exhausted or [MCTSSearcher::stop_pondering] is called.
+    /// The stop flag of `budget` is replaced by an internal one.
+    ///
+    /// Does nothing if there is no tree yet or if the game is over after `mv`.
+    pub fn start_pondering(&mut self, mv: B::Move, budget: SearchBudget) {
+        self.stop_pondering();
+
+        let mut tree = match self.tree.take() {
+            Some(tree) => tree,
+            None => return,
+        };
+        if !tree.reroot(&[mv]) {
+            let board = tree.root_board.clone_and_play(mv).unwrap();
+            if board.is_done() {
+                return;
+            }
+            tree = new_tree(&board);
+        }
+        if tree.root_board.is_done() {
+            return;
+        }
+
+        let stop = StopFlag::new();
+        let budget = budget.with_stop(stop.clone());
+        let exploration_weight = self.exploration_weight;
+        let mut rng = SmallRng::seed_from_u64(self.rng.gen());
+
+        let handle = std::thread::spawn(move || {
+            mcts_expand_tree_budget(&mut tree, &budget, exploration_weight, &mut rng);
+            tree
+        });
+        self.ponder = Some(Ponder { stop, handle });
+    }
+
+    /// Stop the background search, if any, and keep its tree for the next search.
+    pub fn stop_pondering(&mut self) {
+        if let Some(ponder) = self.ponder.take() {
+            ponder.stop.stop();
+            self.tree = Some(ponder.handle.join().expect("Pondering thread panicked"));
+        }
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.ponder.is_some()
+    }
+
+    /// The tree of the last search, `None` while pondering.
+    pub fn tree(&self) -> Option<&Tree<B>> {
+        self.tree.as_ref()
+    }
+
+    /// Stop pondering and throw away the tree, for example when a new game starts.
+    pub fn clear(&mut self) {
+        self.stop_pondering();
+        self.tree = None;
+    }
+}
+
+impl<B: Board, R: Rng> Drop for MCTSSearcher<B, R> {
+    fn drop(&mut self) {
+        self.stop_pondering();
     }
 }
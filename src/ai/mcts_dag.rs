@@ -0,0 +1,322 @@
+//! Transposition-aware MCTS, where identical positions share a single node and the search tree becomes a DAG.
+//!
+//! Positions are identified by their [canonical](crate::board::BoardSymmetry::canonicalize) board,
+//! so symmetric positions are merged too.
+//! Visits are counted separately for each edge, and the value of a node is recomputed from the values of its
+//! children on every backup, as described in [Monte-Carlo Graph Search for AlphaZero](https://arxiv.org/abs/2012.11045).
+//! This keeps the values consistent when a node is reached through multiple paths.
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::ops::Index;
+
+use decorum::N32;
+use internal_iterator::InternalIterator;
+use rand::Rng;
+
+use crate::ai::mcts::{Evaluator, RolloutEvaluator};
+use crate::ai::Bot;
+use crate::board::{Board, BoardDone, Outcome, Player};
+use crate::pov::{NonPov, Pov};
+use crate::symmetry::Symmetry;
+use crate::wdl::{WDLAbs, WDL};
+
+/// An edge in a [DagTree], the move is relative to the canonical board of the parent node.
+#[derive(Debug, Copy, Clone)]
+pub struct DagEdge<M> {
+    pub mv: M,
+    /// The node this edge leads to, `None` until the edge is visited for the first time.
+    pub child: Option<usize>,
+    pub visits: u64,
+}
+
+/// A position in a [DagTree], possibly reachable through multiple edges.
+#[derive(Debug, Clone)]
+pub struct DagNode<M> {
+    /// The number of times this node was visited, through any parent.
+    pub visits: u64,
+    pub outcome: Option<Outcome>,
+    /// The evaluation of this position itself, `NaN` until the first visit.
+    pub eval: WDLAbs<f32>,
+    /// The value of this position, the average of `eval` and the values of the children weighted by edge visits.
+    pub value: WDLAbs<f32>,
+    /// The outgoing edges, `None` until the node is expanded on its first visit.
+    pub edges: Option<Vec<DagEdge<M>>>,
+}
+
+impl<M> DagNode<M> {
+    fn new(outcome: Option<Outcome>) -> Self {
+        DagNode {
+            visits: 0,
+            outcome,
+            eval: WDLAbs::nan(),
+            value: WDLAbs::nan(),
+            edges: None,
+        }
+    }
+
+    /// The value of this node from the POV of `pov`.
+    pub fn wdl(&self, pov: Player) -> WDL<f32> {
+        self.value.pov(pov)
+    }
+}
+
+/// The search graph built by [mcts_dag_build_tree].
+///
+/// Node `0` is the root, it corresponds to the canonical version of `root_board`.
+pub struct DagTree<B: Board + Hash> {
+    pub root_board: B,
+    pub nodes: Vec<DagNode<B::Move>>,
+    /// Maps canonical boards to their node.
+    index: HashMap<B, usize>,
+}
+
+impl<B: Board + Hash> DagTree<B> {
+    pub fn new(root_board: B) -> Self {
+        assert!(!root_board.is_done(), "Cannot build tree for done board");
+
+        let mut tree = DagTree {
+            root_board,
+            nodes: vec![],
+            index: HashMap::new(),
+        };
+        tree.node_for(tree.root_board.canonicalize());
+        tree
+    }
+
+    /// The number of distinct positions in this tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The node for the canonical board `board`, creating it if it does not exist yet.
+    fn node_for(&mut self, board: B) -> usize {
+        if let Some(&node) = self.index.get(&board) {
+            return node;
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(DagNode::new(board.outcome()));
+        self.index.insert(board, node);
+        node
+    }
+
+    /// The node for `board` if it is part of this tree.
+    pub fn find_node(&self, board: &B) -> Option<usize> {
+        self.index.get(&board.canonicalize()).copied()
+    }
+
+    /// The wdl of `root_board` from the POV of `root_board.next_player`.
+    pub fn wdl(&self) -> WDL<f32> {
+        self[0].wdl(self.root_board.next_player())
+    }
+
+    /// The most visited move of `root_board`.
+    pub fn best_move(&self) -> B::Move {
+        let edges = self[0].edges.as_ref().expect("Root node must have edges");
+        let best = edges.iter().max_by_key(|e| e.visits).unwrap();
+
+        // map the move from the canonical root board back to the original one
+        let canonical = self.root_board.canonicalize();
+        let sym = B::Symmetry::all()
+            .iter()
+            .copied()
+            .find(|&sym| self.root_board.map(sym) == canonical)
+            .expect("The canonical board must be a symmetry of the root board");
+        canonical.map_move(sym.inverse(), best.mv)
+    }
+}
+
+impl<B: Board + Hash> Debug for DagTree<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DagTree")
+            .field("root_board", &self.root_board)
+            .field("nodes", &self.nodes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Board + Hash> Index<usize> for DagTree<B> {
+    type Output = DagNode<B::Move>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.nodes[index]
+    }
+}
+
+/// Run a single MCTS step starting from `curr_node` with canonical board `curr_board`.
+/// `path` contains the nodes that are currently being visited, reaching one of them again is treated as a leaf.
+fn mcts_dag_step<B: Board + Hash>(
+    tree: &mut DagTree<B>,
+    curr_node: usize,
+    curr_board: &B,
+    exploration_weight: f32,
+    evaluator: &mut impl Evaluator<B>,
+    path: &mut Vec<usize>,
+    rng: &mut impl Rng,
+) {
+    let node = &mut tree.nodes[curr_node];
+
+    if let Some(outcome) = node.outcome {
+        node.visits += 1;
+        node.eval = outcome.to_wdl_abs();
+        node.value = node.eval;
+        return;
+    }
+
+    if node.edges.is_none() {
+        let evaluation = evaluator.evaluate(curr_board, rng);
+        let edges = curr_board
+            .available_moves()
+            .unwrap()
+            .map(|mv| DagEdge {
+                mv,
+                child: None,
+                visits: 0,
+            })
+            .collect();
+
+        node.visits += 1;
+        node.eval = evaluation.wdl.un_pov(curr_board.next_player());
+        node.value = node.eval;
+        node.edges = Some(edges);
+        return;
+    }
+
+    // pick an edge
+    let node = &tree.nodes[curr_node];
+    let edges = node.edges.as_ref().unwrap();
+    let picked = if curr_board.is_chance_node() {
+        let mv = curr_board.random_available_move(rng).unwrap();
+        edges.iter().position(|e| e.mv == mv).unwrap()
+    } else {
+        let next_player = curr_board.next_player();
+        let parent_visits = node.visits as f32;
+
+        (0..edges.len())
+            .max_by_key(|&i| {
+                let edge = &edges[i];
+                // children that were never evaluated (eg. because they're part of a cycle) count as unvisited
+                let value = match edge.child {
+                    Some(child) if edge.visits > 0 && !tree.nodes[child].value.win_a.is_nan() => {
+                        let wdl = tree.nodes[child].wdl(next_player);
+                        let explore = (parent_visits.ln() / edge.visits as f32).sqrt();
+                        wdl.win + 0.5 * wdl.draw + exploration_weight * explore
+                    }
+                    _ => f32::INFINITY,
+                };
+                (N32::from(value), rng.gen::<u32>())
+            })
+            .unwrap()
+    };
+    let picked_mv = edges[picked].mv;
+
+    // find or create the child node
+    let next_board = curr_board.clone_and_play(picked_mv).unwrap().canonicalize();
+    let child = match tree.nodes[curr_node].edges.as_ref().unwrap()[picked].child {
+        Some(child) => child,
+        None => {
+            let child = tree.node_for(next_board.clone());
+            tree.nodes[curr_node].edges.as_mut().unwrap()[picked].child = Some(child);
+            child
+        }
+    };
+
+    // continue recursing, unless we've found a cycle
+    if !path.contains(&child) {
+        path.push(child);
+        mcts_dag_step(tree, child, &next_board, exploration_weight, evaluator, path, rng);
+        path.pop();
+    }
+
+    // backup, recomputing the value of this node from scratch
+    tree.nodes[curr_node].edges.as_mut().unwrap()[picked].visits += 1;
+
+    let node = &tree.nodes[curr_node];
+    let mut total = node.eval;
+    let mut total_visits = 1;
+    for edge in node.edges.as_ref().unwrap() {
+        if let Some(child) = edge.child {
+            let child_value = tree.nodes[child].value;
+            if edge.visits > 0 && !child_value.win_a.is_nan() {
+                total += child_value * edge.visits as f32;
+                total_visits += edge.visits;
+            }
+        }
+    }
+
+    let node = &mut tree.nodes[curr_node];
+    node.visits += 1;
+    node.value = total / total_visits as f32;
+}
+
+/// Build a new [DagTree] for `root_board` with the given number of iterations, using random rollouts.
+pub fn mcts_dag_build_tree<B: Board + Hash>(
+    root_board: &B,
+    iterations: u64,
+    exploration_weight: f32,
+    rng: &mut impl Rng,
+) -> DagTree<B> {
+    let mut tree = DagTree::new(root_board.clone());
+    mcts_dag_expand_tree(&mut tree, iterations, exploration_weight, &mut RolloutEvaluator, rng);
+    tree
+}
+
+/// Run `iterations` more iterations on an existing tree, evaluating new positions with `evaluator`.
+/// The [policy](crate::ai::mcts::Evaluation::policy) of the evaluations is not used.
+pub fn mcts_dag_expand_tree<B: Board + Hash>(
+    tree: &mut DagTree<B>,
+    iterations: u64,
+    exploration_weight: f32,
+    evaluator: &mut impl Evaluator<B>,
+    rng: &mut impl Rng,
+) {
+    let root_board = tree.root_board.canonicalize();
+    let mut path = vec![];
+
+    for _ in 0..iterations {
+        path.clear();
+        path.push(0);
+        mcts_dag_step(tree, 0, &root_board, exploration_weight, evaluator, &mut path, rng);
+    }
+}
+
+/// A [Bot] that searches a [DagTree] with random rollouts for every move.
+pub struct DagMCTSBot<R: Rng> {
+    iterations: u64,
+    exploration_weight: f32,
+    rng: R,
+}
+
+impl<R: Rng> Debug for DagMCTSBot<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "DagMCTSBot {{ iterations: {}, exploration_weight: {} }}",
+            self.iterations, self.exploration_weight
+        )
+    }
+}
+
+impl<R: Rng> DagMCTSBot<R> {
+    pub fn new(iterations: u64, exploration_weight: f32, rng: R) -> Self {
+        assert!(iterations > 0);
+        DagMCTSBot {
+            iterations,
+            exploration_weight,
+            rng,
+        }
+    }
+}
+
+impl<B: Board + Hash, R: Rng> Bot<B> for DagMCTSBot<R> {
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        board.check_done()?;
+        let tree = mcts_dag_build_tree(board, self.iterations, self.exploration_weight, &mut self.rng);
+        Ok(tree.best_move())
+    }
+}
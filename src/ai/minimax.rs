@@ -1,13 +1,20 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
 use std::marker::PhantomData;
-use std::ops::{ControlFlow, Neg};
+use std::ops::Neg;
+use std::time::Instant;
 
 use internal_iterator::InternalIterator;
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 
+use crate::ai::budget::{SearchBudget, StopFlag};
 use crate::ai::Bot;
-use crate::board::{Board, BoardDone};
+use crate::board::{Board, BoardDone, PlayError, ZobristBoard};
+use crate::util::tt::{Bound, Replacement, TTEntry, TranspositionTable};
+use crate::util::zobrist::{mix64, ZobristKey};
 
 pub trait Heuristic<B: Board>: Debug {
     /// The type used to represent the heuristic value of a board.
@@ -18,24 +25,130 @@ pub trait Heuristic<B: Board>: Debug {
     /// Can be used to prefer faster wins or slower losses.
     fn value(&self, board: &B, depth: u32) -> Self::V;
 
-    /// Return the value of `child`, given the previous board, its value and the move that was just played.
+    /// Return the value of `child`, given the value of the previous board and the move that was just played.
     /// This function can be overridden to improve performance.
     ///
+    /// The searches play `mv` on the previous board in place when possible, see [Board::play_scoped],
+    /// so the previous board itself is not available any more.
+    ///
     /// Given:
     /// * `child = board.clone_and_play(mv)`
     /// * `board_value = value(board, board_length)`
     /// * `child_length = board_length + 1`
     ///
     /// This function must ensure that
-    /// * `value(child, child_length) == value_update(board, board_value, board_length, mv, child)`
+    /// * `value(child, child_length) == value_update_in_place(board_value, board_length, mv, child)`
     #[allow(unused_variables)]
-    fn value_update(&self, board: &B, board_value: Self::V, board_length: u32, mv: B::Move, child: &B) -> Self::V {
+    fn value_update_in_place(&self, board_value: Self::V, board_length: u32, mv: B::Move, child: &B) -> Self::V {
         self.value(child, board_length + 1)
     }
 
+    /// The searches never call this function, override [Heuristic::value_update_in_place] instead.
+    #[deprecated(note = "the searches only call value_update_in_place, override that instead")]
+    #[allow(unused_variables)]
+    fn value_update(&self, board: &B, board_value: Self::V, board_length: u32, mv: B::Move, child: &B) -> Self::V {
+        self.value_update_in_place(board_value, board_length, mv, child)
+    }
+
     /// Merge old and new into a new value, and compare their values.
     /// For standard minimax searches this can simply be implemented as: `(max(old, new), new.cmp(old))`
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering);
+
+    /// Combine the values of the children of a chance node into the value of the chance node itself,
+    /// given pairs of `(value, probability)`. See [Board::is_chance_node].
+    ///
+    /// By default this is the value of the most likely child, since nothing more is known about [Heuristic::V].
+    /// Heuristics for games with chance nodes should override this with a real expectation,
+    /// for integer values [expectation_i32] can be used.
+    fn expectation(children: &[(Self::V, f32)]) -> Self::V {
+        let mut best = children.first().expect("Chance node must have at least one child");
+        for child in children {
+            if child.1 > best.1 {
+                best = child;
+            }
+        }
+        best.0
+    }
+
+    /// A cheap guess of how good `mv` is for the next player on `board`, used to order the moves before searching them.
+    /// Moves with higher scores are searched first, which makes alpha-beta pruning more effective.
+    ///
+    /// Only used when [MoveOrdering::heuristic] is enabled. The scores don't affect the values returned by the search.
+    #[allow(unused_variables)]
+    fn move_score(&self, board: &B, mv: B::Move) -> i32 {
+        0
+    }
+
+    /// Whether `mv` is a noisy move on `board`, a move that is likely to change the value a lot,
+    /// for example captures and promotions in chess.
+    ///
+    /// Once the search reaches its maximum depth it continues with a
+    /// [quiescence search](https://www.chessprogramming.org/Quiescence_Search) that only plays noisy moves,
+    /// until the position is quiet and the heuristic value can be trusted. The next player can always choose to stop
+    /// playing noisy moves and keep the current value instead.
+    ///
+    /// The quiescence search has no depth limit, so every sequence of noisy moves must eventually end,
+    /// typically because each noisy move makes irreversible progress. By default no moves are noisy.
+    ///
+    /// Only called if [Heuristic::has_noisy_moves] returns true.
+    #[allow(unused_variables)]
+    fn is_noisy(&self, board: &B, mv: B::Move) -> bool {
+        false
+    }
+
+    /// Whether [Heuristic::is_noisy] can return true for any move.
+    /// If not the quiescence search is skipped entirely, avoiding the move generation at every horizon node.
+    /// Heuristics that override [Heuristic::is_noisy] should override this too.
+    fn has_noisy_moves(&self) -> bool {
+        false
+    }
+}
+
+/// The probability-weighted mean of `children`, rounded to the nearest integer.
+/// Can be used to implement [Heuristic::expectation] for heuristics with integer values.
+pub fn expectation_i32(children: &[(i32, f32)]) -> i32 {
+    let mean: f64 = children.iter().map(|&(value, p)| value as f64 * p as f64).sum();
+    mean.round() as i32
+}
+
+/// The move ordering techniques used by the search, on top of trying the best move from the previous iteration or
+/// the [TranspositionTable] first.
+///
+/// Move ordering only affects the number of visited nodes, never the value returned by the search.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MoveOrdering {
+    /// Order moves by [Heuristic::move_score].
+    pub heuristic: bool,
+    /// Try the last two moves that caused a beta cutoff at the same ply first, see
+    /// <https://www.chessprogramming.org/Killer_Heuristic>.
+    pub killers: bool,
+    /// Prefer moves that caused beta cutoffs anywhere in the tree, weighted by the remaining depth, see
+    /// <https://www.chessprogramming.org/History_Heuristic>.
+    pub history: bool,
+}
+
+impl MoveOrdering {
+    pub const NONE: MoveOrdering = MoveOrdering {
+        heuristic: false,
+        killers: false,
+        history: false,
+    };
+
+    pub const ALL: MoveOrdering = MoveOrdering {
+        heuristic: true,
+        killers: true,
+        history: true,
+    };
+
+    pub fn is_none(self) -> bool {
+        self == Self::NONE
+    }
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        MoveOrdering::ALL
+    }
 }
 
 #[derive(Debug)]
@@ -52,27 +165,17 @@ pub struct MinimaxResult<V, R> {
 /// Evaluate the board using minimax with the given heuristic up to the given depth.
 /// Return both the value and the best move. If multiple moves have the same value pick a random one using `rng`.
 /// The returned value is from the POV of `board.next_player`.
+///
+/// Chance nodes are evaluated using [Heuristic::expectation] (expectiminimax), they don't count towards `depth`.
+/// If `board` itself is a chance node no best move is returned.
 pub fn minimax<B: Board, H: Heuristic<B>>(
     board: &B,
     heuristic: &H,
     depth: u32,
     rng: &mut impl Rng,
 ) -> MinimaxResult<H::V, B::Move> {
-    let result = negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        RandomMoveSelector::new(rng),
-    );
-
-    if result.best_move.is_none() {
-        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
-    }
-
+    let result = Search::new(heuristic, &NoTable, NoHistory).root(board, depth, RandomMoveSelector::new(rng));
+    check_root_result(board, depth, &result);
     result
 }
 
@@ -82,44 +185,370 @@ pub fn minimax_all_moves<B: Board, H: Heuristic<B>>(
     heuristic: &H,
     depth: u32,
 ) -> MinimaxResult<H::V, Vec<B::Move>> {
-    let result = negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        AllMoveSelector::new(),
-    );
+    let result = Search::new(heuristic, &NoTable, NoHistory).root(board, depth, AllMoveSelector::new());
+    check_root_result(board, depth, &result);
+    result
+}
+
+/// Variant of [minimax] that only returns the value and not the best move.
+/// The advantage is that no rng is necessary to break ties between best moves.
+pub fn minimax_value<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth: u32) -> H::V {
+    Search::new(heuristic, &NoTable, NoHistory)
+        .root(board, depth, NoMoveSelector)
+        .value
+}
+
+/// Variant of [minimax] that uses a [TranspositionTable] to avoid searching the same position multiple times.
+///
+/// The table can be reused between searches to speed them up,
+/// but only between searches that use the same heuristic.
+/// Heuristic values can depend on the current depth, so positions are only shared between equal depths.
+pub fn minimax_tt<B: ZobristBoard, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    tt: &TranspositionTable<B::Move, H::V>,
+    rng: &mut impl Rng,
+) -> MinimaxResult<H::V, B::Move> {
+    let result = Search::new(heuristic, tt, NoHistory).root(board, depth, RandomMoveSelector::new(rng));
+    check_root_result(board, depth, &result);
+    result
+}
+
+/// Variant of [minimax_tt] that only returns the value and not the best move, see [minimax_value].
+pub fn minimax_value_tt<B: ZobristBoard, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    tt: &TranspositionTable<B::Move, H::V>,
+) -> H::V {
+    Search::new(heuristic, tt, NoHistory)
+        .root(board, depth, NoMoveSelector)
+        .value
+}
+
+#[derive(Debug)]
+pub struct PvResult<V, M> {
+    /// The value of this board.
+    pub value: V,
+
+    /// The principal variation, the line of play expected by the search, starting with the best move.
+    /// Empty if the board is done, the depth was zero or the board is a chance node.
+    ///
+    /// The variation is at most `depth` moves long, it stops early when the game ends or at a chance node.
+    pub pv: Vec<M>,
+}
+
+/// Variant of [minimax] that also returns the principal variation.
+/// The best move, the first move of the variation, is picked the same way as [minimax] does.
+pub fn minimax_pv<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    rng: &mut impl Rng,
+) -> PvResult<H::V, B::Move> {
+    let mut search = Search::new(heuristic, &NoTable, NoHistory);
+    search.collect_pv = true;
+
+    let result = search.root(board, depth, RandomMoveSelector::new(rng));
+    check_root_result(board, depth, &result);
+
+    PvResult {
+        value: result.value,
+        pv: search.root_pv(result.best_move),
+    }
+}
+
+/// Variant of [minimax_all_moves] that returns the principal variation starting with each of the best moves,
+/// see [minimax_pv].
+pub fn minimax_all_moves_pv<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+) -> MinimaxResult<H::V, Vec<Vec<B::Move>>> {
+    let mut search = Search::new(heuristic, &NoTable, NoHistory);
+    search.collect_pv = true;
+
+    let result = search.root(board, depth, AllMoveSelector::new());
+    check_root_result(board, depth, &result);
 
+    MinimaxResult {
+        value: result.value,
+        best_move: result
+            .best_move
+            .map(|moves| moves.into_iter().map(|mv| search.root_pv(Some(mv))).collect()),
+    }
+}
+
+fn check_root_result<B: Board, V, R>(board: &B, depth: u32, result: &MinimaxResult<V, R>) {
     if result.best_move.is_none() {
-        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
+        assert!(
+            board.is_done() || depth == 0 || board.is_chance_node(),
+            "Implementation error in negamax"
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct IterativeResult<V, M> {
+    /// The value of this board, as determined by the deepest completed iteration.
+    pub value: V,
+
+    /// The best move found by the deepest completed iteration.
+    /// `None` if the board is done or a chance node.
+    pub best_move: Option<M>,
+
+    /// The principal variation of the deepest completed iteration, starting with `best_move`, see [PvResult::pv].
+    pub pv: Vec<M>,
+
+    /// The depth of the deepest completed iteration.
+    pub depth: u32,
+
+    /// The total number of nodes visited, including those of the final incomplete iteration.
+    pub nodes: u64,
+}
+
+/// Run [minimax] with increasing depths until either `max_depth` or the budget is reached.
+/// The best move of each iteration is searched first in the next one, which makes pruning more effective.
+/// The remaining moves are ordered as configured by `ordering`,
+/// the killer moves and history scores are kept between iterations.
+///
+/// The first iteration always runs to completion, so there is always a best move unless the board is done.
+/// Searching also stops early once an iteration was not limited by the depth at all,
+/// since deeper iterations would give the same result.
+pub fn minimax_iterative<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    max_depth: u32,
+    budget: &SearchBudget,
+    ordering: MoveOrdering,
+    rng: &mut impl Rng,
+) -> IterativeResult<H::V, B::Move>
+where
+    B::Move: Hash,
+{
+    iterative_deepening(board, heuristic, max_depth, budget, ordering, &NoTable, 0, rng)
+}
+
+/// Variant of [minimax_iterative] that uses a [TranspositionTable], see [minimax_tt].
+/// The table is shared between the iterations, which also provides move ordering for the deeper ones.
+pub fn minimax_iterative_tt<B: ZobristBoard, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    max_depth: u32,
+    budget: &SearchBudget,
+    ordering: MoveOrdering,
+    tt: &TranspositionTable<B::Move, H::V>,
+    rng: &mut impl Rng,
+) -> IterativeResult<H::V, B::Move>
+where
+    B::Move: Hash,
+{
+    iterative_deepening(board, heuristic, max_depth, budget, ordering, tt, 0, rng)
+}
+
+/// Iterative deepening as used by [minimax_iterative] and [minimax_lazy_smp].
+///
+/// `thread` is the index of the thread running this search for Lazy SMP, where `0` is the main thread.
+/// Helper threads start at different depths to diversify the searches,
+/// and they respect the budget from the start since their result is only used to fill the table.
+#[allow(clippy::too_many_arguments)]
+fn iterative_deepening<B: Board, H: Heuristic<B>, T: Table<B, H::V>>(
+    board: &B,
+    heuristic: &H,
+    max_depth: u32,
+    budget: &SearchBudget,
+    ordering: MoveOrdering,
+    table: &T,
+    thread: usize,
+    rng: &mut impl Rng,
+) -> IterativeResult<H::V, B::Move>
+where
+    B::Move: Hash,
+{
+    assert!(max_depth > 0, "requires max_depth>0 to find the best move");
+
+    let start = Instant::now();
+    let mut result = IterativeResult {
+        value: heuristic.value(board, 0),
+        best_move: None,
+        pv: vec![],
+        depth: 0,
+        nodes: 0,
+    };
+
+    if board.is_done() {
+        return result;
+    }
+
+    let mut search = Search::new(heuristic, table, HashMap::new());
+    search.ordering = ordering;
+    search.collect_pv = true;
+
+    let first_depth = if thread == 0 { 1 } else { (1 + thread % 2) as u32 };
+
+    for depth in first_depth.min(max_depth)..=max_depth {
+        search.root_first = result.best_move;
+        search.reached_horizon = false;
+        if depth > 1 || thread != 0 {
+            search.budget = Some((budget, start));
+        }
+
+        let iteration = search.root(board, depth, RandomMoveSelector::new(&mut *rng));
+        let nodes = search.nodes;
+
+        if search.aborted {
+            break;
+        }
+
+        result.value = iteration.value;
+        result.best_move = iteration.best_move;
+        result.pv = search.root_pv(iteration.best_move);
+        result.depth = depth;
+
+        if !search.reached_horizon || budget.is_exhausted(start, nodes) {
+            break;
+        }
     }
 
+    result.nodes = search.nodes;
     result
 }
 
-/// Variant of [minimax] that only returns the value and not the best move.
-/// The advantage is that no rng is necessary to break ties between best moves.
-pub fn minimax_value<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth: u32) -> H::V {
-    negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        NoMoveSelector,
-    )
-    .value
-}
-
-/// The selection procedure for selecting the best move to be returned by [negamax_recurse].
+/// Variant of [minimax_iterative_tt] that searches on `threads` threads at the same time,
+/// using [Lazy SMP](https://www.chessprogramming.org/Lazy_SMP).
+///
+/// All threads run their own iterative deepening search and only communicate through the shared table,
+/// the helper threads mostly serve to fill the table with useful entries for the main thread.
+/// The result is always the one from the main thread, which runs on the calling thread.
+/// The helper threads are stopped once the main thread finishes.
+///
+/// The node limit of the budget only applies to the main thread, [IterativeResult::nodes] is the total for all threads.
+#[allow(clippy::too_many_arguments)]
+pub fn minimax_lazy_smp<B: ZobristBoard, H: Heuristic<B> + Sync>(
+    board: &B,
+    heuristic: &H,
+    max_depth: u32,
+    budget: &SearchBudget,
+    ordering: MoveOrdering,
+    tt: &TranspositionTable<B::Move, H::V>,
+    threads: usize,
+    rng: &mut impl Rng,
+) -> IterativeResult<H::V, B::Move>
+where
+    H::V: Send,
+    B::Move: Hash,
+{
+    assert!(threads > 0, "requires at least one thread");
+
+    let helper_stop = StopFlag::new();
+    let helper_budget = SearchBudget {
+        time: budget.time,
+        nodes: None,
+        stop: Some(helper_stop.clone()),
+    };
+
+    std::thread::scope(|scope| {
+        let helpers: Vec<_> = (1..threads)
+            .map(|thread| {
+                let mut helper_rng = SmallRng::seed_from_u64(rng.gen());
+                let helper_budget = &helper_budget;
+                scope.spawn(move || {
+                    iterative_deepening(
+                        board,
+                        heuristic,
+                        max_depth,
+                        helper_budget,
+                        ordering,
+                        tt,
+                        thread,
+                        &mut helper_rng,
+                    )
+                    .nodes
+                })
+            })
+            .collect();
+
+        let mut result = iterative_deepening(board, heuristic, max_depth, budget, ordering, tt, 0, rng);
+
+        helper_stop.stop();
+        for helper in helpers {
+            result.nodes += helper.join().unwrap();
+        }
+        result
+    })
+}
+
+/// The (optional) transposition table used by [Search].
+trait Table<B: Board, V> {
+    fn get(&self, board: &B, length: u32) -> Option<TTEntry<B::Move, V>>;
+
+    fn insert(&self, board: &B, length: u32, entry: TTEntry<B::Move, V>);
+}
+
+/// Don't use a transposition table.
+#[derive(Debug)]
+struct NoTable;
+
+impl<B: Board, V> Table<B, V> for NoTable {
+    fn get(&self, _: &B, _: u32) -> Option<TTEntry<B::Move, V>> {
+        None
+    }
+
+    fn insert(&self, _: &B, _: u32, _: TTEntry<B::Move, V>) {}
+}
+
+impl<B: ZobristBoard, V: Copy> Table<B, V> for TranspositionTable<B::Move, V> {
+    fn get(&self, board: &B, length: u32) -> Option<TTEntry<B::Move, V>> {
+        TranspositionTable::get(self, table_key(board, length))
+    }
+
+    fn insert(&self, board: &B, length: u32, entry: TTEntry<B::Move, V>) {
+        TranspositionTable::insert(self, table_key(board, length), entry)
+    }
+}
+
+/// The (optional) history scores used by [Search], see [MoveOrdering::history].
+trait HistoryTable<M> {
+    fn get(&self, mv: M) -> u64;
+
+    fn add(&mut self, mv: M, score: u64);
+}
+
+/// Don't keep track of history scores, for searches that don't use [MoveOrdering::history].
+#[derive(Debug)]
+struct NoHistory;
+
+impl<M> HistoryTable<M> for NoHistory {
+    fn get(&self, _: M) -> u64 {
+        0
+    }
+
+    fn add(&mut self, _: M, _: u64) {}
+}
+
+impl<M: Eq + Hash> HistoryTable<M> for HashMap<M, u64> {
+    fn get(&self, mv: M) -> u64 {
+        HashMap::get(self, &mv).copied().unwrap_or(0)
+    }
+
+    fn add(&mut self, mv: M, score: u64) {
+        *self.entry(mv).or_insert(0) += score;
+    }
+}
+
+/// Heuristic values can depend on the length, so it has to be part of the key.
+fn table_key<B: ZobristBoard>(board: &B, length: u32) -> u64 {
+    board.zobrist().to_u64() ^ mix64(length as u64)
+}
+
+/// The selection procedure for selecting the best move to be returned by [Search::negamax].
 trait MoveSelector<M> {
     type Result;
 
+    /// Whether moves that tie with the best move are accepted too, which requires their values to be exact.
+    const ACCEPTS_TIES: bool;
+
     fn reset(&mut self);
 
     fn accept(&mut self, mv: M);
@@ -133,6 +562,7 @@ struct NoMoveSelector;
 
 impl<M> MoveSelector<M> for NoMoveSelector {
     type Result = ();
+    const ACCEPTS_TIES: bool = false;
 
     fn reset(&mut self) {}
 
@@ -162,6 +592,7 @@ impl<R: Rng, M> RandomMoveSelector<M, R> {
 
 impl<M, R: Rng> MoveSelector<M> for RandomMoveSelector<M, R> {
     type Result = M;
+    const ACCEPTS_TIES: bool = true;
 
     fn reset(&mut self) {
         self.count = 0;
@@ -195,6 +626,7 @@ impl<M> AllMoveSelector<M> {
 
 impl<M> MoveSelector<M> for AllMoveSelector<M> {
     type Result = Vec<M>;
+    const ACCEPTS_TIES: bool = true;
 
     fn reset(&mut self) {
         self.moves.clear();
@@ -209,85 +641,456 @@ impl<M> MoveSelector<M> for AllMoveSelector<M> {
     }
 }
 
-/// The core minimax implementation.
-/// Alpha-Beta Negamax, implementation based on
-/// <https://en.wikipedia.org/wiki/Negamax#Negamax_with_alpha_beta_pruning>
-fn negamax_recurse<B: Board, H: Heuristic<B>, S: MoveSelector<B::Move>>(
-    heuristic: &H,
-    board: &B,
-    board_heuristic: H::V,
-    length: u32,
-    depth_left: u32,
-    alpha: Option<H::V>,
-    beta: Option<H::V>,
-    mut move_selector: S,
-) -> MinimaxResult<H::V, S::Result> {
-    if depth_left == 0 || board.is_done() {
-        return MinimaxResult {
-            value: board_heuristic,
-            best_move: None,
-        };
+/// The state of a single search, shared between all visited nodes.
+struct Search<'a, B: Board, H: Heuristic<B>, T: Table<B, H::V>, Y: HistoryTable<B::Move>> {
+    heuristic: &'a H,
+    table: &'a T,
+
+    /// The budget and the time the search started at, `None` if the search should always run to completion.
+    budget: Option<(&'a SearchBudget, Instant)>,
+    /// The move to try first at the root, typically the best move of the previous iteration.
+    root_first: Option<B::Move>,
+
+    ordering: MoveOrdering,
+    /// The killer moves for each ply, the most recent one first.
+    killers: Vec<[Option<B::Move>; 2]>,
+    /// The history score of each move, the sum of `depth_left^2` over all beta cutoffs it caused.
+    history: Y,
+
+    /// Whether to collect the principal variation.
+    collect_pv: bool,
+    /// The principal variations starting with each of the moves accepted by the root move selector.
+    root_pvs: Vec<Vec<B::Move>>,
+
+    nodes: u64,
+    /// Whether the search was aborted because the budget ran out, all values are meaningless if so.
+    aborted: bool,
+    /// Whether any non-terminal node was cut off because the depth ran out.
+    reached_horizon: bool,
+}
+
+impl<'a, B: Board, H: Heuristic<B>, T: Table<B, H::V>, Y: HistoryTable<B::Move>> Search<'a, B, H, T, Y> {
+    fn new(heuristic: &'a H, table: &'a T, history: Y) -> Self {
+        Search {
+            heuristic,
+            table,
+            budget: None,
+            root_first: None,
+            ordering: MoveOrdering::NONE,
+            killers: vec![],
+            history,
+            collect_pv: false,
+            root_pvs: vec![],
+            nodes: 0,
+            aborted: false,
+            reached_horizon: false,
+        }
+    }
+
+    fn root<S: MoveSelector<B::Move>>(
+        &mut self,
+        board: &B,
+        depth: u32,
+        move_selector: S,
+    ) -> MinimaxResult<H::V, S::Result> {
+        let board_heuristic = self.heuristic.value(board, 0);
+        self.root_pvs.clear();
+
+        let mut pv = vec![];
+        self.negamax(
+            &mut board.clone(),
+            board_heuristic,
+            0,
+            depth,
+            None,
+            None,
+            &mut pv,
+            move_selector,
+        )
     }
 
-    let mut best_value = None;
-    let mut alpha = alpha;
+    /// The principal variation starting with the root move `mv`, only available if [Search::collect_pv] is set.
+    fn root_pv(&self, mv: Option<B::Move>) -> Vec<B::Move> {
+        mv.and_then(|mv| self.root_pvs.iter().find(|pv| pv[0] == mv))
+            .cloned()
+            .unwrap_or_default()
+    }
 
-    let early = board.children().unwrap().try_for_each(|(mv, child)| {
-        let child_heuristic = heuristic.value_update(board, board_heuristic, length, mv, &child);
+    /// The core minimax implementation.
+    /// Alpha-Beta Negamax, implementation based on
+    /// <https://en.wikipedia.org/wiki/Negamax#Negamax_with_alpha_beta_pruning>
+    ///
+    /// Children are visited by playing the pseudo-legal moves with [Board::play_scoped],
+    /// `board` is restored to its original state before returning.
+    ///
+    /// Results are stored in the table. Entries are only used to cut off the search below the root,
+    /// since the root always needs the full move selection. The best move of an entry is always searched first,
+    /// the other moves are ordered according to [Search::ordering].
+    ///
+    /// If [Search::collect_pv] is set the principal variation is written to `pv`, which must be empty.
+    /// The variation stops early at chance nodes and at table cutoffs.
+    ///
+    /// Children whose value ties with `alpha` may only be upper bounds, so if the move selector accepts ties
+    /// they are searched again without `alpha` to get their exact value.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax<S: MoveSelector<B::Move>>(
+        &mut self,
+        board: &mut B,
+        board_heuristic: H::V,
+        length: u32,
+        depth_left: u32,
+        alpha: Option<H::V>,
+        beta: Option<H::V>,
+        pv: &mut Vec<B::Move>,
+        mut move_selector: S,
+    ) -> MinimaxResult<H::V, S::Result> {
+        if self.visit_node() || board.is_done() {
+            return MinimaxResult {
+                value: board_heuristic,
+                best_move: None,
+            };
+        }
 
-        let maybe_neg = |v: H::V| {
-            if child.next_player() != board.next_player() {
-                -v
+        if depth_left == 0 && !board.is_chance_node() {
+            let value = if self.heuristic.has_noisy_moves() {
+                self.quiescence(board, board_heuristic, length, alpha, beta)
             } else {
-                v
+                self.reached_horizon = true;
+                board_heuristic
+            };
+            return MinimaxResult { value, best_move: None };
+        }
+
+        if board.is_chance_node() {
+            return MinimaxResult {
+                value: self.expectiminimax_chance(board, board_heuristic, length, depth_left),
+                best_move: None,
+            };
+        }
+
+        let entry = self.table.get(board, length);
+        if let Some(entry) = entry {
+            if length > 0 && entry.depth >= depth_left {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => beta.is_some_and(|beta| H::merge(beta, entry.value).1.is_ge()),
+                    Bound::Upper => alpha.is_some_and(|alpha| H::merge(alpha, entry.value).1.is_le()),
+                };
+                if cutoff {
+                    // we don't know whether the original search reached the horizon, so be conservative
+                    self.reached_horizon = true;
+                    return MinimaxResult {
+                        value: entry.value,
+                        best_move: None,
+                    };
+                }
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best_value = None;
+        let mut best_move = None;
+        let mut alpha = alpha;
+
+        let player = board.next_player();
+        let mut moves: Vec<B::Move> = board.pseudo_legal_moves().unwrap().collect();
+        if !self.ordering.is_none() {
+            self.order_moves(board, length, &mut moves);
+        }
+
+        let first = if length == 0 { self.root_first } else { None };
+        if let Some(first) = first.or(entry.and_then(|e| e.best_move)) {
+            if let Some(index) = moves.iter().position(|&mv| mv == first) {
+                moves[..=index].rotate_right(1);
             }
+        }
+
+        for mv in moves {
+            let mut child_pv = vec![];
+            let child_value = board.play_scoped(mv, |child| {
+                let child_heuristic = self.heuristic.value_update_in_place(board_heuristic, length, mv, child);
+
+                let flip = child.next_player() != player;
+                let maybe_neg = |v: H::V| if flip { -v } else { v };
+
+                let mut search_child = |search: &mut Self, alpha: Option<H::V>, child_pv: &mut Vec<B::Move>| {
+                    let child_value = search
+                        .negamax(
+                            child,
+                            child_heuristic,
+                            length + 1,
+                            depth_left - 1,
+                            beta.map(maybe_neg),
+                            alpha.map(maybe_neg),
+                            child_pv,
+                            NoMoveSelector,
+                        )
+                        .value;
+                    maybe_neg(child_value)
+                };
+
+                let child_value = search_child(self, alpha, &mut child_pv);
+                let is_tie = alpha.is_some_and(|alpha| H::merge(alpha, child_value).1.is_eq());
+
+                if S::ACCEPTS_TIES && is_tie && !self.aborted {
+                    child_pv.clear();
+                    search_child(self, None, &mut child_pv)
+                } else {
+                    child_value
+                }
+            });
+
+            if self.aborted {
+                return MinimaxResult {
+                    value: board_heuristic,
+                    best_move: None,
+                };
+            }
+
+            // skip pseudo-legal moves that turned out to not be available
+            let child_value = match child_value {
+                Ok(child_value) => child_value,
+                Err(PlayError::UnavailableMove) => continue,
+                Err(PlayError::BoardDone) => unreachable!(),
+            };
+
+            let (new_best_value, ordering) = best_value.map_or((child_value, Ordering::Greater), |best_value| {
+                H::merge(best_value, child_value)
+            });
+            let new_alpha = alpha.map_or(new_best_value, |alpha| H::merge(alpha, new_best_value).0);
+
+            best_value = Some(new_best_value);
+
+            if ordering.is_gt() {
+                best_move = Some(mv);
+                move_selector.reset();
+                if S::ACCEPTS_TIES {
+                    self.root_pvs.clear();
+                }
+
+                if self.collect_pv {
+                    pv.clear();
+                    pv.push(mv);
+                    pv.extend_from_slice(&child_pv);
+                }
+            }
+            if ordering.is_ge() {
+                move_selector.accept(mv);
+
+                if self.collect_pv && S::ACCEPTS_TIES {
+                    let mut line = vec![mv];
+                    line.extend_from_slice(&child_pv);
+                    self.root_pvs.push(line);
+                }
+            }
+            alpha = Some(new_alpha);
+
+            if beta.is_some_and(|beta| H::merge(beta, new_alpha).1.is_ge()) {
+                self.record_cutoff(length, depth_left, mv);
+
+                let entry = TTEntry {
+                    depth: depth_left,
+                    value: new_best_value,
+                    bound: Bound::Lower,
+                    best_move,
+                };
+                self.table.insert(board, length, entry);
+
+                return MinimaxResult {
+                    value: new_best_value,
+                    best_move: None,
+                };
+            }
+        }
+
+        let value = best_value.unwrap();
+        let bound = if original_alpha.is_some_and(|alpha| H::merge(alpha, value).1.is_le()) {
+            Bound::Upper
+        } else {
+            Bound::Exact
         };
+        let entry = TTEntry {
+            depth: depth_left,
+            value,
+            bound,
+            best_move,
+        };
+        self.table.insert(board, length, entry);
 
-        let child_value = maybe_neg(
-            negamax_recurse(
-                heuristic,
-                &child,
-                child_heuristic,
-                length + 1,
-                depth_left - 1,
-                beta.map(maybe_neg),
-                alpha.map(maybe_neg),
-                NoMoveSelector,
-            )
-            .value,
-        );
+        MinimaxResult {
+            value,
+            best_move: Some(move_selector.finish()),
+        }
+    }
 
-        let (new_best_value, ordering) = best_value.map_or((child_value, Ordering::Greater), |best_value| {
-            H::merge(best_value, child_value)
-        });
-        let new_alpha = alpha.map_or(new_best_value, |alpha| H::merge(alpha, new_best_value).0);
+    /// Count a newly visited node and check the budget, returns whether the search has been aborted.
+    fn visit_node(&mut self) -> bool {
+        self.nodes += 1;
+        if let Some((budget, start)) = self.budget {
+            if self.aborted || budget.is_exhausted(start, self.nodes) {
+                self.aborted = true;
+            }
+        }
+        self.aborted
+    }
 
-        best_value = Some(new_best_value);
+    /// Search only the noisy moves of `board`, see [Heuristic::is_noisy].
+    /// The next player can "stand pat" and keep the heuristic value instead of playing any of them,
+    /// so that value is a lower bound for the result.
+    fn quiescence(
+        &mut self,
+        board: &mut B,
+        board_heuristic: H::V,
+        length: u32,
+        alpha: Option<H::V>,
+        beta: Option<H::V>,
+    ) -> H::V {
+        self.reached_horizon = true;
 
-        if ordering.is_gt() {
-            move_selector.reset();
+        let mut best_value = board_heuristic;
+        let mut alpha = Some(alpha.map_or(best_value, |alpha| H::merge(alpha, best_value).0));
+        if beta.is_some_and(|beta| H::merge(beta, best_value).1.is_ge()) {
+            return best_value;
         }
-        if ordering.is_ge() {
-            move_selector.accept(mv);
+
+        let player = board.next_player();
+        let mut moves: Vec<B::Move> = board
+            .pseudo_legal_moves()
+            .unwrap()
+            .filter(|&mv| self.heuristic.is_noisy(board, mv))
+            .collect();
+        if self.ordering.heuristic {
+            moves.sort_by_cached_key(|&mv| Reverse(self.heuristic.move_score(board, mv)));
         }
-        alpha = Some(new_alpha);
 
-        if beta.map_or(false, |beta| H::merge(beta, new_alpha).1.is_ge()) {
-            ControlFlow::Break(MinimaxResult {
-                value: new_best_value,
-                best_move: None,
-            })
-        } else {
-            ControlFlow::Continue(())
+        for mv in moves {
+            let child_value = board.play_scoped(mv, |child| {
+                let child_heuristic = self.heuristic.value_update_in_place(board_heuristic, length, mv, child);
+
+                let flip = child.next_player() != player;
+                let maybe_neg = |v: H::V| if flip { -v } else { v };
+
+                let child_value = if self.visit_node() || child.is_done() || child.is_chance_node() {
+                    child_heuristic
+                } else {
+                    self.quiescence(
+                        child,
+                        child_heuristic,
+                        length + 1,
+                        beta.map(maybe_neg),
+                        alpha.map(maybe_neg),
+                    )
+                };
+                maybe_neg(child_value)
+            });
+
+            if self.aborted {
+                return board_heuristic;
+            }
+
+            // skip pseudo-legal moves that turned out to not be available
+            let child_value = match child_value {
+                Ok(child_value) => child_value,
+                Err(PlayError::UnavailableMove) => continue,
+                Err(PlayError::BoardDone) => unreachable!(),
+            };
+
+            best_value = H::merge(best_value, child_value).0;
+            alpha = alpha.map(|alpha| H::merge(alpha, best_value).0);
+
+            if beta.is_some_and(|beta| H::merge(beta, best_value).1.is_ge()) {
+                break;
+            }
         }
-    });
 
-    match early {
-        ControlFlow::Break(early) => early,
-        ControlFlow::Continue(()) => MinimaxResult {
-            value: best_value.unwrap(),
-            best_move: Some(move_selector.finish()),
-        },
+        best_value
+    }
+
+    /// Sort `moves` from most to least promising, keeping the original order for ties.
+    fn order_moves(&self, board: &B, length: u32, moves: &mut [B::Move]) {
+        let ordering = self.ordering;
+        let killers = self.killers.get(length as usize).copied().unwrap_or([None; 2]);
+
+        moves.sort_by_cached_key(|&mv| {
+            let score = if ordering.heuristic {
+                self.heuristic.move_score(board, mv)
+            } else {
+                0
+            };
+            let killer = if !ordering.killers {
+                0
+            } else if killers[0] == Some(mv) {
+                2
+            } else if killers[1] == Some(mv) {
+                1
+            } else {
+                0
+            };
+            let history = if ordering.history { self.history.get(mv) } else { 0 };
+
+            Reverse((score, killer, history))
+        });
+    }
+
+    /// Update the killer moves and history scores after `mv` caused a beta cutoff.
+    fn record_cutoff(&mut self, length: u32, depth_left: u32, mv: B::Move) {
+        if self.ordering.killers {
+            let length = length as usize;
+            if self.killers.len() <= length {
+                self.killers.resize(length + 1, [None; 2]);
+            }
+            let killers = &mut self.killers[length];
+            if killers[0] != Some(mv) {
+                killers[1] = killers[0];
+                killers[0] = Some(mv);
+            }
+        }
+
+        if self.ordering.history {
+            self.history.add(mv, depth_left as u64 * depth_left as u64);
+        }
+    }
+
+    /// Evaluate a chance node by searching all children without pruning and combining their values.
+    /// Chance nodes don't count towards the search depth.
+    fn expectiminimax_chance(&mut self, board: &mut B, board_heuristic: H::V, length: u32, depth_left: u32) -> H::V {
+        let player = board.next_player();
+        let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+
+        let mut children: Vec<(H::V, f32)> = vec![];
+        for mv in moves {
+            let probability = board.chance_probability(mv);
+            let child_value = board
+                .play_scoped(mv, |child| {
+                    let child_heuristic = self.heuristic.value_update_in_place(board_heuristic, length, mv, child);
+                    let child_value = self
+                        .negamax(
+                            child,
+                            child_heuristic,
+                            length + 1,
+                            depth_left,
+                            None,
+                            None,
+                            &mut vec![],
+                            NoMoveSelector,
+                        )
+                        .value;
+
+                    if child.next_player() != player {
+                        -child_value
+                    } else {
+                        child_value
+                    }
+                })
+                .unwrap();
+
+            if self.aborted {
+                return board_heuristic;
+            }
+            children.push((child_value, probability));
+        }
+
+        H::expectation(&children)
     }
 }
 
@@ -336,3 +1139,132 @@ impl<B: Board, H: Heuristic<B> + Debug, R: Rng> Bot<B> for MiniMaxBot<B, H, R> {
         Ok(result.best_move.unwrap())
     }
 }
+
+/// A bot that runs [minimax_iterative] within the given budget, see [SearchBudget].
+pub struct IterativeMiniMaxBot<B: Board, H: Heuristic<B>, R: Rng> {
+    max_depth: u32,
+    budget: SearchBudget,
+    ordering: MoveOrdering,
+    heuristic: H,
+    rng: R,
+    ph: PhantomData<B>,
+}
+
+impl<B: Board, H: Heuristic<B>, R: Rng> Debug for IterativeMiniMaxBot<B, H, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IterativeMiniMaxBot {{ max_depth: {}, budget: {:?}, ordering: {:?}, heuristic: {:?} }}",
+            self.max_depth, self.budget, self.ordering, self.heuristic
+        )
+    }
+}
+
+impl<B: Board, H: Heuristic<B>, R: Rng> IterativeMiniMaxBot<B, H, R> {
+    /// The search can be stopped from another thread by including a [StopFlag](crate::ai::budget::StopFlag)
+    /// in `budget`, the bot then plays the best move of the deepest completed iteration.
+    pub fn new(max_depth: u32, budget: SearchBudget, ordering: MoveOrdering, heuristic: H, rng: R) -> Self {
+        assert!(max_depth > 0, "requires max_depth>0 to find the best move");
+        IterativeMiniMaxBot {
+            max_depth,
+            budget,
+            ordering,
+            heuristic,
+            rng,
+            ph: PhantomData,
+        }
+    }
+}
+
+impl<B: Board, H: Heuristic<B> + Debug, R: Rng> Bot<B> for IterativeMiniMaxBot<B, H, R>
+where
+    B::Move: Hash,
+{
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        board.check_done()?;
+
+        let result = minimax_iterative(
+            board,
+            &self.heuristic,
+            self.max_depth,
+            &self.budget,
+            self.ordering,
+            &mut self.rng,
+        );
+        // the first iteration always completes, see [minimax_iterative]
+        Ok(result.best_move.unwrap())
+    }
+}
+
+/// A bot that runs [minimax_lazy_smp] on multiple threads within the given budget.
+///
+/// The bot keeps its transposition table between moves, entries from previous moves can still be useful.
+pub struct LazySmpBot<B: ZobristBoard, H: Heuristic<B>, R: Rng> {
+    threads: usize,
+    max_depth: u32,
+    budget: SearchBudget,
+    ordering: MoveOrdering,
+    tt: TranspositionTable<B::Move, H::V>,
+    heuristic: H,
+    rng: R,
+}
+
+impl<B: ZobristBoard, H: Heuristic<B>, R: Rng> Debug for LazySmpBot<B, H, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LazySmpBot {{ threads: {}, max_depth: {}, budget: {:?}, ordering: {:?}, tt: {:?}, heuristic: {:?} }}",
+            self.threads, self.max_depth, self.budget, self.ordering, self.tt, self.heuristic
+        )
+    }
+}
+
+impl<B: ZobristBoard, H: Heuristic<B>, R: Rng> LazySmpBot<B, H, R> {
+    /// `threads` is typically set to `num_cpus::get()`,
+    /// `tt_capacity` is the number of entries in the shared [TranspositionTable].
+    pub fn new(
+        threads: usize,
+        max_depth: u32,
+        budget: SearchBudget,
+        ordering: MoveOrdering,
+        tt_capacity: usize,
+        heuristic: H,
+        rng: R,
+    ) -> Self {
+        assert!(threads > 0, "requires at least one thread");
+        assert!(max_depth > 0, "requires max_depth>0 to find the best move");
+        LazySmpBot {
+            threads,
+            max_depth,
+            budget,
+            ordering,
+            tt: TranspositionTable::new(tt_capacity, Replacement::DepthPreferred),
+            heuristic,
+            rng,
+        }
+    }
+}
+
+impl<B: ZobristBoard, H: Heuristic<B> + Sync, R: Rng> Bot<B> for LazySmpBot<B, H, R>
+where
+    H::V: Send,
+    B::Move: Hash,
+{
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        board.check_done()?;
+
+        self.tt.new_generation();
+        let result = minimax_lazy_smp(
+            board,
+            &self.heuristic,
+            self.max_depth,
+            &self.budget,
+            self.ordering,
+            &self.tt,
+            self.threads,
+            &mut self.rng,
+        );
+        // the first iteration of the main thread always completes, see [minimax_iterative]
+        Ok(result.best_move.unwrap())
+    }
+}
@@ -1,15 +1,22 @@
 use crate::board::{Board, BoardDone};
 
+pub mod budget;
 pub mod mcts;
+pub mod mcts_dag;
 pub mod minimax;
+pub mod pns;
+pub mod rollout;
 pub mod simple;
 pub mod solver;
+pub mod tablebase;
 
 pub trait Bot<B: Board> {
     /// Pick a move to play.
     ///
     /// `self` is mutable to allow for random state, this method is not supposed to
     /// modify `self` in any other significant way.
+    ///
+    /// Bots are not expected to pick moves for chance nodes, see [Board::is_chance_node].
     fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone>;
 }
 
@@ -0,0 +1,381 @@
+//! [Proof-number search](https://www.chessprogramming.org/Proof-Number_Search), a best-first solver that
+//! focuses on the parts of the game tree that are the easiest to prove or disprove.
+//!
+//! Unlike [solve](crate::ai::solver::solve) there is no depth limit, so deep but narrow forced wins can be found.
+//! Positions are stored in a table keyed by the board itself, so transpositions are only searched once.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+
+use internal_iterator::InternalIterator;
+
+use crate::ai::budget::SearchBudget;
+use crate::board::{Board, Outcome, Player};
+use crate::pov::NonPov;
+use crate::wdl::OutcomeWDL;
+
+const INFINITE: u64 = u64::MAX;
+
+/// The result of [pns_solve].
+#[derive(Debug, Clone)]
+pub struct PnsResult<M> {
+    /// The value of the board from the POV of the next player, `None` if the budget ran out first.
+    pub value: Option<OutcomeWDL>,
+    /// The proof for `value`, see [Proof].
+    pub proof: Option<Proof<M>>,
+    /// The total number of positions that were stored, summed over the searches.
+    pub nodes: u64,
+}
+
+/// A strategy that guarantees an outcome for one player, the prover.
+///
+/// The prover has a single move in positions where they are to move, all possible moves are included for
+/// the other player. The leaves are done boards with an outcome that is good enough for the prover.
+///
+/// For a win the prover is the next player of the root board, for a draw it is also the next player, who can at
+/// least draw but not force a win. For a loss the prover is the opponent.
+///
+/// Positions that are reached through multiple lines are only stored once, so the proof is a directed acyclic graph
+/// of [ProofNode]s. The root board corresponds to the node at index 0.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Proof<M> {
+    pub nodes: Vec<ProofNode<M>>,
+}
+
+/// A position in a [Proof].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProofNode<M> {
+    /// The included moves, together with the index of the node they lead to.
+    pub children: Vec<(M, usize)>,
+}
+
+impl<M> Proof<M> {
+    pub fn root(&self) -> &ProofNode<M> {
+        &self.nodes[0]
+    }
+
+    /// The number of distinct positions in this proof, including the root.
+    pub fn size(&self) -> u64 {
+        self.nodes.len() as u64
+    }
+
+    /// The length of the longest line in this proof.
+    pub fn depth(&self) -> u32 {
+        let mut depths = vec![None; self.nodes.len()];
+        self.depth_of(0, &mut depths)
+    }
+
+    fn depth_of(&self, node: usize, depths: &mut [Option<u32>]) -> u32 {
+        if let Some(depth) = depths[node] {
+            return depth;
+        }
+
+        let depth = self.nodes[node]
+            .children
+            .iter()
+            .map(|&(_, child)| self.depth_of(child, depths) + 1)
+            .max()
+            .unwrap_or(0);
+        depths[node] = Some(depth);
+        depth
+    }
+}
+
+#[derive(Debug)]
+struct PnsNode<M> {
+    proof: u64,
+    disproof: u64,
+    /// The order in which this node was solved, `None` while it is unsolved.
+    /// Proofs only follow children solved before their parent, otherwise they could loop back on a cycle.
+    solved_order: Option<u64>,
+    /// The value of [Search::generation] when every unsolved child of this node was found to lead back to the path.
+    /// Stuck nodes are skipped during selection until another node is expanded.
+    stuck_generation: Option<u64>,
+    children: Option<Vec<(M, usize)>>,
+}
+
+impl<M> PnsNode<M> {
+    fn is_solved(&self) -> bool {
+        self.proof == 0 || self.disproof == 0
+    }
+}
+
+/// A single search, trying to prove that `player` achieves at least the outcome `target`.
+struct Search<B: Board + Hash> {
+    player: Player,
+    target: OutcomeWDL,
+    nodes: Vec<PnsNode<B::Move>>,
+    index: HashMap<B, usize>,
+    solved_count: u64,
+    /// The number of expansions so far, used to invalidate [PnsNode::stuck_generation].
+    generation: u64,
+}
+
+impl<B: Board + Hash> Search<B> {
+    fn new(root: &B, target: OutcomeWDL) -> Self {
+        let mut search = Search {
+            player: root.next_player(),
+            target,
+            nodes: vec![],
+            index: HashMap::new(),
+            solved_count: 0,
+            generation: 0,
+        };
+        search.node_for(root);
+        search
+    }
+
+    /// Whether the prover is to move on `board`, in which case a single good move is enough.
+    fn is_or_node(&self, board: &B) -> bool {
+        assert!(
+            !board.is_chance_node(),
+            "Proof-number search does not support chance nodes"
+        );
+        board.next_player() == self.player
+    }
+
+    fn satisfies_target(&self, outcome: Outcome) -> bool {
+        let wdl = outcome.pov(self.player);
+        match self.target {
+            OutcomeWDL::Win => wdl == OutcomeWDL::Win,
+            OutcomeWDL::Draw => wdl != OutcomeWDL::Loss,
+            OutcomeWDL::Loss => true,
+        }
+    }
+
+    fn node_for(&mut self, board: &B) -> usize {
+        if let Some(&node) = self.index.get(board) {
+            return node;
+        }
+
+        let (proof, disproof) = match board.outcome() {
+            Some(outcome) if self.satisfies_target(outcome) => (0, INFINITE),
+            Some(_) => (INFINITE, 0),
+            None => (1, 1),
+        };
+
+        let node = self.nodes.len();
+        self.nodes.push(PnsNode {
+            proof,
+            disproof,
+            solved_order: None,
+            stuck_generation: None,
+            children: None,
+        });
+        self.mark_if_solved(node);
+        self.index.insert(board.clone(), node);
+        node
+    }
+
+    fn mark_if_solved(&mut self, node: usize) {
+        let node = &mut self.nodes[node];
+        if node.is_solved() && node.solved_order.is_none() {
+            node.solved_order = Some(self.solved_count);
+            self.solved_count += 1;
+        }
+    }
+
+    /// Recompute the proof and disproof numbers of `node` from its children, if it has any.
+    fn update(&mut self, node: usize, is_or: bool) {
+        let children = match &self.nodes[node].children {
+            Some(children) => children,
+            None => return,
+        };
+
+        let proofs = children.iter().map(|&(_, c)| self.nodes[c].proof);
+        let disproofs = children.iter().map(|&(_, c)| self.nodes[c].disproof);
+
+        let (proof, disproof) = if is_or {
+            (proofs.min().unwrap(), disproofs.fold(0, u64::saturating_add))
+        } else {
+            (proofs.fold(0, u64::saturating_add), disproofs.min().unwrap())
+        };
+
+        let curr = &mut self.nodes[node];
+        curr.proof = proof;
+        curr.disproof = disproof;
+        self.mark_if_solved(node);
+    }
+
+    fn expand(&mut self, node: usize, board: &B) {
+        let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+        let children = moves
+            .into_iter()
+            .map(|mv| (mv, self.node_for(&board.clone_and_play(mv).unwrap())))
+            .collect();
+        self.nodes[node].children = Some(children);
+        self.generation += 1;
+    }
+
+    fn is_stuck(&self, node: usize) -> bool {
+        self.nodes[node].stuck_generation == Some(self.generation)
+    }
+
+    /// Run the search until the root is solved or the budget is exhausted.
+    /// Returns whether the target was proven, or `None` if the search was stopped early.
+    ///
+    /// A node where every unsolved move leads back to the current path is marked as stuck, and the search continues
+    /// elsewhere. Stuck marks are cleared whenever a node is expanded, since that can change which path gets selected.
+    /// Every iteration either expands a node or marks one as stuck, so if the root itself gets stuck no more progress
+    /// is possible and the search stops with an unknown result.
+    fn run(&mut self, root: &B, budget: &SearchBudget, start: Instant, prev_nodes: u64) -> Option<bool> {
+        let mut path = vec![];
+
+        loop {
+            if self.nodes[0].proof == 0 {
+                return Some(true);
+            }
+            if self.nodes[0].disproof == 0 {
+                return Some(false);
+            }
+            if budget.is_exhausted(start, prev_nodes + self.nodes.len() as u64) || self.is_stuck(0) {
+                return None;
+            }
+
+            // select the most proving node
+            path.clear();
+            let mut node = 0;
+            let mut board = root.clone();
+            loop {
+                let is_or = self.is_or_node(&board);
+                path.push((node, is_or));
+                self.update(node, is_or);
+
+                let curr = &self.nodes[node];
+                if curr.is_solved() {
+                    break;
+                }
+                let children = match &curr.children {
+                    Some(children) => children,
+                    None => {
+                        self.expand(node, &board);
+                        break;
+                    }
+                };
+
+                // skip children that are already on the path, they would form a cycle,
+                //   solved children, expanding below them can't change anything, and stuck children
+                let picked = children
+                    .iter()
+                    .filter(|&&(_, c)| {
+                        !path.iter().any(|&(p, _)| p == c) && !self.nodes[c].is_solved() && !self.is_stuck(c)
+                    })
+                    .min_by_key(|&&(_, c)| match is_or {
+                        true => self.nodes[c].proof,
+                        false => self.nodes[c].disproof,
+                    });
+                let (mv, child) = match picked {
+                    Some(&picked) => picked,
+                    None => {
+                        // every unsolved move leads back to the path, try somewhere else
+                        self.nodes[node].stuck_generation = Some(self.generation);
+                        break;
+                    }
+                };
+
+                board.play(mv).unwrap();
+                node = child;
+            }
+
+            // update the ancestors
+            for &(node, is_or) in path.iter().rev() {
+                self.update(node, is_or);
+            }
+        }
+    }
+
+    /// Extract the proof for the root, for the target if `proven` and for the opponent otherwise.
+    fn proof(&self, root: &B, proven: bool) -> Proof<B::Move> {
+        let mut proof = Proof { nodes: vec![] };
+        self.build_proof(0, root, proven, &mut proof, &mut HashMap::new());
+        proof
+    }
+
+    /// Add the proof for `node` to `proof` and return its index.
+    /// `built` maps search nodes to the proof nodes already built for them, so transpositions are shared.
+    fn build_proof(
+        &self,
+        node: usize,
+        board: &B,
+        proven: bool,
+        proof: &mut Proof<B::Move>,
+        built: &mut HashMap<usize, usize>,
+    ) -> usize {
+        if let Some(&index) = built.get(&node) {
+            return index;
+        }
+        let index = proof.nodes.len();
+        proof.nodes.push(ProofNode { children: vec![] });
+        built.insert(node, index);
+
+        let children = match &self.nodes[node].children {
+            Some(children) if !board.is_done() => children,
+            _ => return index,
+        };
+
+        let order = self.nodes[node].solved_order.unwrap();
+        let is_solved_child = |c: usize| {
+            let solved = match proven {
+                true => self.nodes[c].proof == 0,
+                false => self.nodes[c].disproof == 0,
+            };
+            solved && self.nodes[c].solved_order.unwrap() < order
+        };
+        let prover_to_move = self.is_or_node(board) == proven;
+
+        let children = if prover_to_move {
+            let &(mv, child) = children.iter().find(|&&(_, c)| is_solved_child(c)).unwrap();
+            vec![(mv, child)]
+        } else {
+            children.clone()
+        };
+
+        let children = children
+            .into_iter()
+            .map(|(mv, child)| {
+                let child_board = board.clone_and_play(mv).unwrap();
+                (mv, self.build_proof(child, &child_board, proven, proof, built))
+            })
+            .collect();
+        proof.nodes[index].children = children;
+        index
+    }
+}
+
+/// Solve `board` with proof-number search, stopping when `budget` is exhausted.
+/// The node budget counts the positions stored in the tables, so it also limits the memory usage.
+/// The budget may be exceeded by the children of a single position.
+///
+/// Internally this runs up to two searches: first for a win, and if that fails for a draw.
+///
+/// Panics on chance nodes. The search stops with an unknown result if every remaining line gets stuck on a cycle,
+/// which can only happen for boards that don't track their own history.
+pub fn pns_solve<B: Board + Hash>(board: &B, budget: &SearchBudget) -> PnsResult<B::Move> {
+    assert!(!board.is_done(), "Cannot solve done board");
+    let start = Instant::now();
+
+    let mut win = Search::new(board, OutcomeWDL::Win);
+    let win_proven = win.run(board, budget, start, 0);
+    let mut nodes = win.nodes.len() as u64;
+
+    let (value, proof) = match win_proven {
+        None => (None, None),
+        Some(true) => (Some(OutcomeWDL::Win), Some(win.proof(board, true))),
+        Some(false) => {
+            // free the memory of the first search before starting the second one
+            drop(win);
+
+            let mut draw = Search::new(board, OutcomeWDL::Draw);
+            let draw_proven = draw.run(board, budget, start, nodes);
+            nodes += draw.nodes.len() as u64;
+
+            match draw_proven {
+                None => (None, None),
+                Some(true) => (Some(OutcomeWDL::Draw), Some(draw.proof(board, true))),
+                Some(false) => (Some(OutcomeWDL::Loss), Some(draw.proof(board, false))),
+            }
+        }
+    };
+
+    PnsResult { value, proof, nodes }
+}
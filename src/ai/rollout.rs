@@ -0,0 +1,172 @@
+//! Policies that decide how games are played out by [RolloutBot](crate::ai::simple::RolloutBot)
+//! and the rollout [Evaluator](crate::ai::mcts::Evaluator)s of [mcts](crate::ai::mcts), see [RolloutPolicy].
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use internal_iterator::InternalIterator;
+use rand::Rng;
+
+use crate::ai::minimax::Heuristic;
+use crate::board::{Board, Player};
+use crate::pov::Pov;
+use crate::wdl::{WDLAbs, WDL};
+
+/// Decides which moves are played during a rollout, and optionally when to stop it early.
+pub trait RolloutPolicy<B: Board>: Debug {
+    /// Pick the move to play on `board`, which is not done.
+    /// For chance nodes this should sample according to [Board::chance_probability],
+    /// typically by calling [Board::random_available_move].
+    fn pick_move(&mut self, board: &B, rng: &mut impl Rng) -> B::Move;
+
+    /// Stop the rollout at `board` after `plies` moves have been played, with the returned value from the POV of
+    /// `board.next_player()`. Returning `None` continues the rollout. Never called on done boards.
+    ///
+    /// The default implementation never stops early.
+    #[allow(unused_variables)]
+    fn cutoff(&mut self, board: &B, plies: u32) -> Option<WDL<f32>> {
+        None
+    }
+}
+
+/// Play moves picked by `policy` on `board` until the game is done or the policy cuts the rollout off.
+/// Returns the final outcome or the cutoff value. Board can already be done.
+///
+/// The non-chance moves that were played are appended to `played_moves` together with the player that played them.
+pub fn rollout<B: Board, P: RolloutPolicy<B>>(
+    board: &B,
+    policy: &mut P,
+    played_moves: &mut Vec<(Player, B::Move)>,
+    rng: &mut impl Rng,
+) -> WDLAbs<f32> {
+    let mut board = board.clone();
+    let mut plies = 0;
+
+    loop {
+        if let Some(outcome) = board.outcome() {
+            return outcome.to_wdl_abs();
+        }
+        if let Some(wdl) = policy.cutoff(&board, plies) {
+            return wdl.un_pov(board.next_player());
+        }
+
+        let mv = policy.pick_move(&board, rng);
+        if !board.is_chance_node() {
+            played_moves.push((board.next_player(), mv));
+        }
+        board.play(mv).unwrap();
+        plies += 1;
+    }
+}
+
+/// Plays uniformly random moves, the classic rollout policy.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RandomRollout;
+
+impl<B: Board> RolloutPolicy<B> for RandomRollout {
+    fn pick_move(&mut self, board: &B, rng: &mut impl Rng) -> B::Move {
+        board.random_available_move(rng).unwrap()
+    }
+}
+
+/// Plays the move with the best `heuristic` value with probability `1 - epsilon`,
+/// and a uniformly random move otherwise. Ties between the best moves are broken randomly.
+#[derive(Debug, Clone)]
+pub struct EpsilonGreedyRollout<H> {
+    pub heuristic: H,
+    pub epsilon: f32,
+}
+
+impl<H> EpsilonGreedyRollout<H> {
+    pub fn new(heuristic: H, epsilon: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&epsilon),
+            "epsilon must be a probability, got {}",
+            epsilon
+        );
+        EpsilonGreedyRollout { heuristic, epsilon }
+    }
+}
+
+impl<B: Board, H: Heuristic<B>> RolloutPolicy<B> for EpsilonGreedyRollout<H> {
+    fn pick_move(&mut self, board: &B, rng: &mut impl Rng) -> B::Move {
+        if board.is_chance_node() || rng.gen::<f32>() < self.epsilon {
+            return board.random_available_move(rng).unwrap();
+        }
+
+        let player = board.next_player();
+        let mut best: Option<(H::V, B::Move)> = None;
+        let mut ties = 0;
+
+        board.children().unwrap().for_each(|(mv, child)| {
+            // the heuristic value is from the POV of the next player of the child
+            let value = self.heuristic.value(&child, 1);
+            let value = if child.next_player() != player { -value } else { value };
+
+            let ordering = match best {
+                None => Ordering::Greater,
+                Some((best_value, _)) => H::merge(best_value, value).1,
+            };
+            match ordering {
+                Ordering::Greater => {
+                    best = Some((value, mv));
+                    ties = 1;
+                }
+                Ordering::Equal => {
+                    // reservoir sampling, so each of the tied moves is picked with the same probability
+                    ties += 1;
+                    if rng.gen_range(0..ties) == 0 {
+                        best = Some((value, mv));
+                    }
+                }
+                Ordering::Less => {}
+            }
+        });
+
+        best.unwrap().1
+    }
+}
+
+/// Plays moves from `inner` and stops the rollout after `max_plies` moves,
+/// estimating the win probability from the `heuristic` value `v` as `1 / (1 + exp(-v / scale))`.
+#[derive(Debug, Clone)]
+pub struct CutoffRollout<P, H> {
+    pub inner: P,
+    pub heuristic: H,
+    pub max_plies: u32,
+    /// The heuristic value difference that corresponds to a factor `e` in the win/loss odds.
+    pub scale: f32,
+}
+
+impl<P, H> CutoffRollout<P, H> {
+    pub fn new(inner: P, heuristic: H, max_plies: u32, scale: f32) -> Self {
+        assert!(scale > 0.0, "scale must be positive, got {}", scale);
+        CutoffRollout {
+            inner,
+            heuristic,
+            max_plies,
+            scale,
+        }
+    }
+}
+
+impl<B: Board, P: RolloutPolicy<B>, H: Heuristic<B>> RolloutPolicy<B> for CutoffRollout<P, H>
+where
+    H::V: Into<f64>,
+{
+    fn pick_move(&mut self, board: &B, rng: &mut impl Rng) -> B::Move {
+        self.inner.pick_move(board, rng)
+    }
+
+    fn cutoff(&mut self, board: &B, plies: u32) -> Option<WDL<f32>> {
+        if let Some(wdl) = self.inner.cutoff(board, plies) {
+            return Some(wdl);
+        }
+        if plies < self.max_plies {
+            return None;
+        }
+
+        let value: f64 = self.heuristic.value(board, plies).into();
+        let win = (1.0 / (1.0 + (-value / self.scale as f64).exp())) as f32;
+        Some(WDL::new(win, 0.0, 1.0 - win))
+    }
+}
@@ -1,9 +1,11 @@
 //! Two simple bots: `RandomBot` and `RolloutBot`.
 use std::fmt::{Debug, Formatter};
 
+use decorum::N32;
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
+use crate::ai::rollout::{rollout, RandomRollout, RolloutPolicy};
 use crate::ai::Bot;
 use crate::board::{Board, BoardDone};
 use crate::pov::NonPov;
@@ -35,40 +37,60 @@ impl<B: Board, R: Rng> Bot<B> for RandomBot<R> {
 ///
 /// The same number of simulations `rollouts / nb_moves` is done for
 /// each move, and the move resulting in the best average score is selected.
-pub struct RolloutBot<R: Rng> {
+/// The games are played out using a [RolloutPolicy], by default [RandomRollout].
+pub struct RolloutBot<R: Rng, P = RandomRollout> {
     rollouts: u32,
+    policy: P,
     rng: R,
 }
 
-impl<R: Rng> Debug for RolloutBot<R> {
+impl<R: Rng, P: Debug> Debug for RolloutBot<R, P> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "RolloutBot {{ rollouts: {} }}", self.rollouts)
+        write!(
+            f,
+            "RolloutBot {{ rollouts: {}, policy: {:?} }}",
+            self.rollouts, self.policy
+        )
     }
 }
 
 impl<R: Rng> RolloutBot<R> {
     pub fn new(rollouts: u32, rng: R) -> Self {
-        RolloutBot { rollouts, rng }
+        RolloutBot {
+            rollouts,
+            policy: RandomRollout,
+            rng,
+        }
     }
 }
 
-impl<B: Board, R: Rng> Bot<B> for RolloutBot<R> {
+impl<R: Rng, P> RolloutBot<R, P> {
+    /// Play out games using `policy` instead of random moves.
+    pub fn with_policy<P2>(self, policy: P2) -> RolloutBot<R, P2> {
+        RolloutBot {
+            rollouts: self.rollouts,
+            policy,
+            rng: self.rng,
+        }
+    }
+}
+
+impl<B: Board, R: Rng, P: RolloutPolicy<B>> Bot<B> for RolloutBot<R, P> {
     fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
-        let rollouts_per_move = self.rollouts / board.available_moves().unwrap().count() as u32;
+        let rollouts_per_move = self.rollouts / board.available_moves()?.count() as u32;
+        let mut played_moves = vec![];
 
         Ok(board
             .children()?
             .max_by_key(|(_, child)| {
-                let score: i64 = (0..rollouts_per_move)
+                let score: f32 = (0..rollouts_per_move)
                     .map(|_| {
-                        let mut copy = child.clone();
-                        while let Ok(mv) = copy.random_available_move(&mut self.rng) {
-                            copy.play(mv).unwrap();
-                        }
-                        copy.outcome().unwrap().pov(board.next_player()).sign::<i64>()
+                        played_moves.clear();
+                        let wdl = rollout(child, &mut self.policy, &mut played_moves, &mut self.rng);
+                        wdl.pov(board.next_player()).value()
                     })
                     .sum();
-                score
+                N32::from(score)
             })
             .map(|(mv, _)| mv)
             .unwrap())
@@ -5,10 +5,14 @@ use std::ops::Neg;
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
-use crate::ai::minimax::{minimax, minimax_all_moves, minimax_value, Heuristic, MinimaxResult};
+use crate::ai::minimax::{
+    minimax, minimax_all_moves, minimax_all_moves_pv, minimax_pv, minimax_tt, minimax_value, minimax_value_tt,
+    Heuristic, MinimaxResult, PvResult,
+};
 use crate::ai::Bot;
-use crate::board::{Board, BoardDone, Outcome};
+use crate::board::{Board, BoardDone, Outcome, ZobristBoard};
 use crate::pov::NonPov;
+use crate::util::tt::TranspositionTable;
 use crate::wdl::OutcomeWDL;
 
 /// Minimax [Heuristic] that only looks at board outcomes.
@@ -41,6 +45,10 @@ impl<B: Board> Heuristic<B> for SolverHeuristic {
     fn merge(old: SolverValue, new: SolverValue) -> (SolverValue, Ordering) {
         SolverValue::merge(old, new)
     }
+
+    fn expectation(children: &[(SolverValue, f32)]) -> SolverValue {
+        SolverValue::expectation(children.iter().map(|&(value, _)| value))
+    }
 }
 
 impl SolverValue {
@@ -83,6 +91,22 @@ impl SolverValue {
         }
     }
 
+    /// Combine the values of the children of a chance node.
+    /// The result is only known if it's the same for every child, in which case the least favorable length is used.
+    pub fn expectation(children: impl IntoIterator<Item = SolverValue>) -> SolverValue {
+        use SolverValue::*;
+
+        let mut children = children.into_iter();
+        let first = children.next().expect("Chance node must have at least one child");
+
+        children.fold(first, |acc, child| match (acc, child) {
+            (WinIn(acc_n), WinIn(child_n)) => WinIn(acc_n.max(child_n)),
+            (LossIn(acc_n), LossIn(child_n)) => LossIn(acc_n.min(child_n)),
+            (Draw, Draw) => Draw,
+            _ => Unknown,
+        })
+    }
+
     /// Return whether `child` could a child of the optimally combined `parent`.
     pub fn could_be_optimal_child(parent: SolverValue, child: SolverValue) -> bool {
         let best_child = match parent {
@@ -118,12 +142,262 @@ pub fn solve_all_moves<B: Board>(board: &B, depth: u32) -> MinimaxResult<SolverV
     minimax_all_moves(board, &SolverHeuristic, depth)
 }
 
+/// Variant of [solve] that also returns the principal variation, see [minimax_pv].
+/// For solved boards this is the line where the winner wins as fast as possible and the loser loses as slow as possible.
+pub fn solve_pv<B: Board>(board: &B, depth: u32, rng: &mut impl Rng) -> PvResult<SolverValue, B::Move> {
+    minimax_pv(board, &SolverHeuristic, depth, rng)
+}
+
+/// Variant of [solve_all_moves] that returns the principal variation for each best move, see [minimax_all_moves_pv].
+pub fn solve_all_moves_pv<B: Board>(board: &B, depth: u32) -> MinimaxResult<SolverValue, Vec<Vec<B::Move>>> {
+    minimax_all_moves_pv(board, &SolverHeuristic, depth)
+}
+
 pub fn solve_value<B: Board>(board: &B, depth: u32) -> SolverValue {
     minimax_value(board, &SolverHeuristic, depth)
 }
 
+/// Variant of [solve] that uses a transposition table, see [minimax_tt].
+pub fn solve_tt<B: ZobristBoard>(
+    board: &B,
+    depth: u32,
+    tt: &TranspositionTable<B::Move, SolverValue>,
+    rng: &mut impl Rng,
+) -> MinimaxResult<SolverValue, B::Move> {
+    minimax_tt(board, &SolverHeuristic, depth, tt, rng)
+}
+
+/// Variant of [solve_value] that uses a transposition table, see [minimax_tt].
+pub fn solve_value_tt<B: ZobristBoard>(
+    board: &B,
+    depth: u32,
+    tt: &TranspositionTable<B::Move, SolverValue>,
+) -> SolverValue {
+    minimax_value_tt(board, &SolverHeuristic, depth, tt)
+}
+
+/// An interval of possible values for a board, from the POV of the next player, see [solve_bounds].
+///
+/// The bounds are ordered the same way as [SolverValue::merge]: a shorter win is better than a longer one,
+/// and a longer loss is better than a shorter one. Lengths are counted from the root of the search,
+/// like for [SolverHeuristic]. Neither bound is ever [SolverValue::Unknown].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SolverBounds {
+    pub lower: SolverValue,
+    pub upper: SolverValue,
+}
+
+impl SolverBounds {
+    pub fn exact(value: SolverValue) -> Self {
+        assert_ne!(value, SolverValue::Unknown, "Bounds must be known values");
+        SolverBounds {
+            lower: value,
+            upper: value,
+        }
+    }
+
+    /// A window that contains every value, since no value is better than an immediate win or worse than an immediate loss.
+    fn full_window() -> Self {
+        SolverBounds {
+            lower: SolverValue::LossIn(0),
+            upper: SolverValue::WinIn(0),
+        }
+    }
+
+    /// The bounds for a board that is not done after `length` moves and has not been searched any further.
+    /// The game cannot end before the next move, so everything between a loss and a win in `length + 1` is possible.
+    pub fn unknown(length: u32) -> Self {
+        SolverBounds {
+            lower: SolverValue::LossIn(length + 1),
+            upper: SolverValue::WinIn(length + 1),
+        }
+    }
+
+    /// Whether the value is known exactly, including the distance for wins and losses.
+    pub fn is_exact(self) -> bool {
+        self.lower == self.upper
+    }
+
+    /// The exact value if it is known, [SolverValue::Unknown] otherwise.
+    pub fn value(self) -> SolverValue {
+        if self.is_exact() {
+            self.lower
+        } else {
+            SolverValue::Unknown
+        }
+    }
+
+    /// The worst and best outcome that are still possible.
+    pub fn outcome_bounds(self) -> (OutcomeWDL, OutcomeWDL) {
+        (
+            self.lower.to_outcome_wdl().unwrap(),
+            self.upper.to_outcome_wdl().unwrap(),
+        )
+    }
+
+    /// The outcome if it is proven, even if the exact distance is not known yet.
+    pub fn outcome(self) -> Option<OutcomeWDL> {
+        let (lower, upper) = self.outcome_bounds();
+        if lower == upper {
+            Some(lower)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this board is proven to be a draw, as opposed to a draw being one of multiple possible outcomes.
+    pub fn is_proven_draw(self) -> bool {
+        self.outcome() == Some(OutcomeWDL::Draw)
+    }
+
+    /// The number of moves until the game ends for a proven win or loss,
+    /// when the winner wins as fast as possible and the loser loses as slow as possible.
+    /// Returns `None` for draws and if the exact distance is not known.
+    pub fn mate_distance(self) -> Option<u32> {
+        match self.value() {
+            SolverValue::WinIn(n) | SolverValue::LossIn(n) => Some(n),
+            SolverValue::Draw | SolverValue::Unknown => None,
+        }
+    }
+
+    fn max(self, other: SolverBounds) -> SolverBounds {
+        SolverBounds {
+            lower: SolverValue::merge(self.lower, other.lower).0,
+            upper: SolverValue::merge(self.upper, other.upper).0,
+        }
+    }
+
+    /// The smallest interval that contains both `self` and `other`.
+    fn union(self, other: SolverBounds) -> SolverBounds {
+        SolverBounds {
+            lower: -SolverValue::merge(-self.lower, -other.lower).0,
+            upper: SolverValue::merge(self.upper, other.upper).0,
+        }
+    }
+}
+
+impl Neg for SolverBounds {
+    type Output = SolverBounds;
+
+    fn neg(self) -> Self::Output {
+        SolverBounds {
+            lower: -self.upper,
+            upper: -self.lower,
+        }
+    }
+}
+
+/// Solve `board` up to `depth` moves deep, returning the interval of values that are still possible.
+///
+/// Unlike [solve_value] this distinguishes proven draws from unknown values,
+/// and it returns the exact distance to the end of the game for wins and losses that are found.
+/// The values of chance nodes are only bounded by the union of the bounds of their children.
+pub fn solve_bounds<B: Board>(board: &B, depth: u32) -> SolverBounds {
+    solve_bounds_impl(board, 0, depth, SolverBounds::full_window())
+}
+
+/// Variant of [solve_bounds] that returns the bounds for each move, from the POV of the next player of `board`.
+/// Lengths are still counted from `board`, so they include the move itself.
+pub fn solve_bounds_all_moves<B: Board>(board: &B, depth: u32) -> Result<Vec<(B::Move, SolverBounds)>, BoardDone> {
+    assert!(depth > 0, "Need to search at least one move deep");
+    let window = SolverBounds::full_window();
+    let moves = board
+        .children()?
+        .map(|(mv, child)| (mv, child_bounds(board, &child, 1, depth - 1, window)))
+        .collect();
+    Ok(moves)
+}
+
+/// Whether `a >= b`, in the order of [SolverValue::merge].
+fn is_ge(a: SolverValue, b: SolverValue) -> bool {
+    SolverValue::merge(b, a).1.is_ge()
+}
+
+/// The bounds of `child` from the POV of the next player of `parent`.
+/// `window` is the alpha-beta window, also from the POV of that player.
+fn child_bounds<B: Board>(parent: &B, child: &B, length: u32, depth: u32, window: SolverBounds) -> SolverBounds {
+    if child.next_player() == parent.next_player() {
+        solve_bounds_impl(child, length, depth, window)
+    } else {
+        -solve_bounds_impl(child, length, depth, -window)
+    }
+}
+
+/// Alpha-beta search on the bound pair, `window.lower` is alpha and `window.upper` is beta.
+/// The returned bounds always contain the real value, but they are only as tight as possible
+/// if the real value is within the window.
+fn solve_bounds_impl<B: Board>(board: &B, length: u32, depth: u32, window: SolverBounds) -> SolverBounds {
+    if let Some(outcome) = board.outcome() {
+        let value = match outcome.pov(board.next_player()) {
+            OutcomeWDL::Win => SolverValue::WinIn(length),
+            OutcomeWDL::Draw => SolverValue::Draw,
+            OutcomeWDL::Loss => SolverValue::LossIn(length),
+        };
+        return SolverBounds::exact(value);
+    }
+
+    // prune if even the fastest possible win or loss can't get inside the window
+    let possible = SolverBounds::unknown(length);
+    if depth == 0 || is_ge(window.lower, possible.upper) || is_ge(possible.lower, window.upper) {
+        return possible;
+    }
+
+    if board.is_chance_node() {
+        return board
+            .children()
+            .unwrap()
+            .map(|(_, child)| child_bounds(board, &child, length + 1, depth - 1, window))
+            .fold(None, |acc: Option<SolverBounds>, bounds| {
+                Some(acc.map_or(bounds, |acc| acc.union(bounds)))
+            })
+            .unwrap();
+    }
+
+    // clamp beta to the fastest possible win, so we also stop once that is found
+    let mut alpha = window.lower;
+    let beta = if is_ge(window.upper, possible.upper) {
+        possible.upper
+    } else {
+        window.upper
+    };
+
+    let mut result: Option<SolverBounds> = None;
+    let cutoff = board
+        .children()
+        .unwrap()
+        .find(|(_, child)| {
+            let child_window = SolverBounds {
+                lower: alpha,
+                upper: beta,
+            };
+            let bounds = child_bounds(board, child, length + 1, depth - 1, child_window);
+
+            let curr = result.map_or(bounds, |result| result.max(bounds));
+            result = Some(curr);
+            if is_ge(curr.lower, alpha) {
+                alpha = curr.lower;
+            }
+            is_ge(curr.lower, beta)
+        })
+        .is_some();
+
+    let result = result.unwrap();
+    if cutoff {
+        // the remaining children could still be better
+        SolverBounds {
+            lower: result.lower,
+            upper: possible.upper,
+        }
+    } else {
+        result
+    }
+}
+
 /// Return whether this board is a double forced draw, ie. no matter what either player does the game can only end in a draw.
 /// Returns `None` if the result is unknown.
+///
+/// This is a single search that stops as soon as any line reaches a win or loss within `depth`,
+/// it is a forced draw if every line reaches a drawn outcome instead.
 pub fn is_double_forced_draw(board: &impl Board, depth: u32) -> Option<bool> {
     if let Some(outcome) = board.outcome() {
         return Some(outcome == Outcome::Draw);
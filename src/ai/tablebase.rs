@@ -0,0 +1,353 @@
+//! Exhaustive solving of small games with [retrograde analysis](https://www.chessprogramming.org/Retrograde_Analysis),
+//! see [Tablebase].
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryInto;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+
+use internal_iterator::InternalIterator;
+
+use crate::ai::solver::SolverValue;
+use crate::ai::Bot;
+use crate::board::{BoardDone, ZobristBoard};
+use crate::pov::NonPov;
+use crate::util::game_stats::all_possible_boards;
+use crate::util::zobrist::ZobristKey;
+use crate::wdl::OutcomeWDL;
+
+const MAGIC: &[u8; 4] = b"BGTB";
+const VERSION: u8 = 3;
+
+/// The solved value of a single position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TablebaseEntry {
+    /// The value from the POV of the next player.
+    pub value: OutcomeWDL,
+    /// The number of moves until the end of the game, when the winner wins as fast as possible
+    /// and the loser loses as slow as possible. Always zero for draws.
+    pub distance: u32,
+}
+
+impl TablebaseEntry {
+    pub fn to_solver_value(self) -> SolverValue {
+        match self.value {
+            OutcomeWDL::Win => SolverValue::WinIn(self.distance),
+            OutcomeWDL::Draw => SolverValue::Draw,
+            OutcomeWDL::Loss => SolverValue::LossIn(self.distance),
+        }
+    }
+}
+
+/// The solved values of all positions reachable from a start position, generated with [Tablebase::generate].
+///
+/// Positions are stored by the [Zobrist key](ZobristBoard::zobrist) of their
+/// [canonical](crate::board::BoardSymmetry::canonicalize) board, folded into 64 bits.
+/// These keys include all state that affects the rest of the game and are built from fixed tables,
+/// so unlike a [Hash] they don't depend on the platform or compiler version.
+/// The generator checks that the keys of all positions in the table are distinct,
+/// but looking up a position that is not part of the table can still return the entry of a different position.
+///
+/// The table also stores the key of the start position it was generated from,
+/// so [Tablebase::read] can detect files generated for a different game, variant or key scheme.
+///
+/// Positions that are part of a cycle that neither player can force their way out of are considered draws.
+/// Chance nodes are not supported.
+pub struct Tablebase<B> {
+    start_key: u64,
+    entries: HashMap<u64, TablebaseEntry>,
+    ph: PhantomData<B>,
+}
+
+/// The key of `board` in a [Tablebase], see the type documentation.
+fn key<B: ZobristBoard>(board: &B) -> u64 {
+    board.canonicalize().zobrist().to_u64()
+}
+
+impl<B: ZobristBoard + Hash> Tablebase<B> {
+    /// Solve all positions reachable from `start`.
+    ///
+    /// **Warning**: This enumerates all of these positions, so it only terminates in a reasonable amount of time for small games.
+    pub fn generate(start: &B) -> Self {
+        Self::generate_with_positions(start).0
+    }
+
+    /// Variant of [Tablebase::generate] that also returns the positions in the table,
+    /// as distinct canonical boards in the order they were found, starting with `start`.
+    pub fn generate_with_positions(start: &B) -> (Self, Vec<B>) {
+        // collect the distinct canonical positions
+        let mut index = HashMap::new();
+        let mut boards = vec![];
+        for board in all_possible_boards(start, u32::MAX, true) {
+            let board = board.canonicalize();
+            if !index.contains_key(&board) {
+                index.insert(board.clone(), boards.len());
+                boards.push(board);
+            }
+        }
+
+        // build the graph between them
+        let mut parents = vec![vec![]; boards.len()];
+        let mut remaining = vec![0; boards.len()];
+        for (i, board) in boards.iter().enumerate() {
+            if board.is_done() {
+                continue;
+            }
+            assert!(!board.is_chance_node(), "Tablebases do not support chance nodes");
+
+            let mut children = vec![];
+            board.children().unwrap().for_each(|(_, child)| {
+                children.push(index[&child.canonicalize()]);
+            });
+
+            // multiple moves can lead to the same canonical child
+            children.sort_unstable();
+            children.dedup();
+            remaining[i] = children.len();
+            for child in children {
+                parents[child].push(i);
+            }
+        }
+
+        // propagate values backwards from the done positions
+        let mut values: Vec<Option<TablebaseEntry>> = vec![None; boards.len()];
+        let mut has_draw_child = vec![false; boards.len()];
+        let mut queue = VecDeque::new();
+
+        for (i, board) in boards.iter().enumerate() {
+            if let Some(outcome) = board.outcome() {
+                let value = outcome.pov(board.next_player());
+                values[i] = Some(TablebaseEntry { value, distance: 0 });
+                queue.push_back(i);
+            }
+        }
+
+        while let Some(child) = queue.pop_front() {
+            let child_entry = values[child].unwrap();
+            let child_value = child_entry.value.un_pov(boards[child].next_player());
+
+            for &parent in &parents[child] {
+                if values[parent].is_some() {
+                    continue;
+                }
+
+                let distance = child_entry.distance + 1;
+                match child_value.pov(boards[parent].next_player()) {
+                    OutcomeWDL::Win => {
+                        // the queue is ordered by distance, so this is the fastest win
+                        values[parent] = Some(TablebaseEntry {
+                            value: OutcomeWDL::Win,
+                            distance,
+                        });
+                        queue.push_back(parent);
+                        continue;
+                    }
+                    OutcomeWDL::Draw => has_draw_child[parent] = true,
+                    OutcomeWDL::Loss => {}
+                }
+
+                remaining[parent] -= 1;
+                if remaining[parent] == 0 {
+                    // all children are known and none of them are winning
+                    values[parent] = Some(match has_draw_child[parent] {
+                        true => TablebaseEntry {
+                            value: OutcomeWDL::Draw,
+                            distance: 0,
+                        },
+                        false => TablebaseEntry {
+                            value: OutcomeWDL::Loss,
+                            distance,
+                        },
+                    });
+                    queue.push_back(parent);
+                }
+            }
+        }
+
+        // the remaining positions can't be forced out of a cycle
+        let mut entries = HashMap::with_capacity(boards.len());
+        for (board, value) in boards.iter().zip(values) {
+            let value = value.unwrap_or(TablebaseEntry {
+                value: OutcomeWDL::Draw,
+                distance: 0,
+            });
+            let prev = entries.insert(key(board), value);
+            assert!(prev.is_none(), "Key collision between positions in the tablebase");
+        }
+
+        let tablebase = Tablebase {
+            start_key: key(start),
+            entries,
+            ph: PhantomData,
+        };
+        (tablebase, boards)
+    }
+
+    /// The number of positions in this table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, board: &B) -> Option<TablebaseEntry> {
+        self.entries.get(&key(board)).copied()
+    }
+
+    /// The best move for `board`: the fastest win, any draw, or the slowest loss.
+    /// Returns `None` if `board` or one of its children is not part of the table.
+    pub fn best_move(&self, board: &B) -> Result<Option<B::Move>, BoardDone> {
+        Ok(self.best_moves(board)?.map(|moves| moves[0]))
+    }
+
+    /// All moves for `board` that are as good as [Tablebase::best_move], in the order they are generated.
+    /// Returns `None` if `board` or one of its children is not part of the table.
+    pub fn best_moves(&self, board: &B) -> Result<Option<Vec<B::Move>>, BoardDone> {
+        let mut best: Option<(SolverValue, Vec<B::Move>)> = None;
+        let mut missing = false;
+
+        board.children()?.for_each(|(mv, child)| {
+            let value = match self.get(&child) {
+                Some(entry) if child.next_player() == board.next_player() => entry.to_solver_value(),
+                Some(entry) => -entry.to_solver_value(),
+                None => {
+                    missing = true;
+                    return;
+                }
+            };
+            // account for the move we're playing now
+            let value = match value {
+                SolverValue::WinIn(n) => SolverValue::WinIn(n + 1),
+                SolverValue::LossIn(n) => SolverValue::LossIn(n + 1),
+                value => value,
+            };
+
+            match &mut best {
+                Some((best_value, moves)) => match SolverValue::merge(*best_value, value).1 {
+                    Ordering::Greater => best = Some((value, vec![mv])),
+                    Ordering::Equal => moves.push(mv),
+                    Ordering::Less => {}
+                },
+                None => best = Some((value, vec![mv])),
+            }
+        });
+
+        Ok(best.filter(|_| !missing).map(|(_, moves)| moves))
+    }
+
+    /// Write this table to `writer`, the format is:
+    /// * the magic bytes `BGTB` followed by a version byte,
+    /// * the key of the start position as a little-endian `u64`,
+    /// * the number of entries as a little-endian `u64`,
+    /// * for each entry, sorted by key: the key as a little-endian `u64`,
+    ///   the value as a byte (0 = loss, 1 = draw, 2 = win) and the distance as a little-endian `u32`.
+    pub fn write(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.start_key.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_unstable_by_key(|&(&key, _)| key);
+
+        for (&key, entry) in entries {
+            let value = match entry.value {
+                OutcomeWDL::Loss => 0,
+                OutcomeWDL::Draw => 1,
+                OutcomeWDL::Win => 2,
+            };
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&[value])?;
+            writer.write_all(&entry.distance.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a table previously written with [Tablebase::write], which must have been generated from `start`.
+    /// Returns an [ErrorKind::InvalidData] error if the stored start key doesn't match,
+    /// which means the table is for a different game or variant, or the key scheme of the game has changed.
+    pub fn read(mut reader: impl Read, start: &B) -> std::io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Not a tablebase file or unsupported version",
+            ));
+        }
+
+        let mut start_key = [0; 8];
+        reader.read_exact(&mut start_key)?;
+        let start_key = u64::from_le_bytes(start_key);
+        if start_key != key(start) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Tablebase was generated from a different start position, game or key scheme",
+            ));
+        }
+
+        let mut len = [0; 8];
+        reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+
+        let mut entries = HashMap::new();
+        for _ in 0..len {
+            let mut bytes = [0; 13];
+            reader.read_exact(&mut bytes)?;
+
+            let key = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+            let value = match bytes[8] {
+                0 => OutcomeWDL::Loss,
+                1 => OutcomeWDL::Draw,
+                2 => OutcomeWDL::Win,
+                _ => return Err(Error::new(ErrorKind::InvalidData, "Invalid tablebase value")),
+            };
+            let distance = u32::from_le_bytes(bytes[9..13].try_into().unwrap());
+
+            entries.insert(key, TablebaseEntry { value, distance });
+        }
+
+        Ok(Tablebase {
+            start_key,
+            entries,
+            ph: PhantomData,
+        })
+    }
+}
+
+impl<B> Debug for Tablebase<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tablebase {{ len: {} }}", self.entries.len())
+    }
+}
+
+/// Bot that plays perfectly using a [Tablebase], see [Tablebase::best_move].
+/// Panics if the board is not part of the table.
+#[derive(Debug)]
+pub struct TablebaseBot<B> {
+    tablebase: Tablebase<B>,
+}
+
+impl<B> TablebaseBot<B> {
+    pub fn new(tablebase: Tablebase<B>) -> Self {
+        TablebaseBot { tablebase }
+    }
+
+    pub fn tablebase(&self) -> &Tablebase<B> {
+        &self.tablebase
+    }
+}
+
+impl<B: ZobristBoard + Hash> Bot<B> for TablebaseBot<B> {
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        Ok(self
+            .tablebase
+            .best_move(board)?
+            .expect("Board is not part of the tablebase"))
+    }
+}
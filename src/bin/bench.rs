@@ -1,11 +1,17 @@
 #![allow(clippy::assertions_on_constants)]
 
+use std::hash::Hash;
 use std::time::Instant;
 
-use board_game::ai::mcts::mcts_build_tree;
+use board_game::ai::budget::SearchBudget;
+use board_game::ai::mcts::{mcts_build_tree, mcts_build_tree_parallel};
+use board_game::ai::minimax::{minimax_iterative, Heuristic, MoveOrdering};
+use board_game::board::Board;
 use board_game::games::ataxx::AtaxxBoard;
 use board_game::games::chess::ChessBoard;
 use board_game::games::sttt::STTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
 use itertools::Itertools;
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
@@ -22,6 +28,86 @@ fn main() {
     bench("mcts_chess", || {
         mcts_build_tree(&ChessBoard::default(), 1_000, 2.0, &mut SmallRng::from_entropy());
     });
+
+    bench_mcts_threads("mcts_threads_ataxx", &AtaxxBoard::default(), 40_000);
+
+    bench_ordering(
+        "minimax_ataxx",
+        &AtaxxBoard::default(),
+        &AtaxxTileHeuristic::default(),
+        5,
+    );
+    bench_ordering("minimax_chess", &ChessBoard::default(), &ChessPieceValueHeuristic, 5);
+}
+
+/// Report how the number of MCTS nodes per second scales with the number of threads.
+fn bench_mcts_threads<B: Board>(name: &str, board: &B, iterations: u64) {
+    println!("Running benchmark {}", name);
+
+    let max_threads = num_cpus::get();
+    let thread_counts = (0..)
+        .map(|i| 1 << i)
+        .take_while(|&t| t < max_threads)
+        .chain(std::iter::once(max_threads));
+
+    let mut base_speed = None;
+    for threads in thread_counts {
+        let start = Instant::now();
+        mcts_build_tree_parallel(board, iterations, 2.0, threads, &mut SmallRng::from_entropy());
+        let speed = iterations as f32 / start.elapsed().as_secs_f32();
+
+        let base_speed = *base_speed.get_or_insert(speed);
+        println!(
+            "  {} threads: {:.0} nodes/s, {:.2}x",
+            threads,
+            speed,
+            speed / base_speed
+        );
+    }
+}
+
+/// Compare the number of visited nodes and the time of a minimax search with and without move ordering.
+fn bench_ordering<B: Board, H: Heuristic<B>>(name: &str, board: &B, heuristic: &H, depth: u32)
+where
+    B::Move: Hash,
+{
+    let orderings = [
+        ("none", MoveOrdering::NONE),
+        (
+            "heuristic",
+            MoveOrdering {
+                heuristic: true,
+                ..MoveOrdering::NONE
+            },
+        ),
+        (
+            "killers+history",
+            MoveOrdering {
+                heuristic: false,
+                ..MoveOrdering::ALL
+            },
+        ),
+        ("all", MoveOrdering::ALL),
+    ];
+
+    for (ordering_name, ordering) in orderings {
+        let budget = SearchBudget::unlimited();
+        let rng = &mut SmallRng::seed_from_u64(0);
+        let nodes = minimax_iterative(board, heuristic, depth, &budget, ordering, rng).nodes;
+
+        let name = format!("{}_{}", name, ordering_name);
+        bench(&name, || {
+            minimax_iterative(
+                board,
+                heuristic,
+                depth,
+                &budget,
+                ordering,
+                &mut SmallRng::from_entropy(),
+            );
+        });
+        println!("  {} nodes", nodes);
+    }
 }
 
 const ITERATION_COUNT: usize = 10;
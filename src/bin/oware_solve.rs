@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+use itertools::Itertools;
+
+use board_game::games::oware::solver::solve_oware;
+use board_game::games::oware::OwareBoard;
+use board_game::wdl::OutcomeWDL;
+
+fn main() {
+    let args = std::env::args().skip(1).collect_vec();
+
+    let (pits, seeds, output) = match args.as_slice() {
+        [pits, seeds, output] => (pits, seeds, output),
+        _ => usage(),
+    };
+
+    let pits = pits
+        .parse::<usize>()
+        .unwrap_or_else(|_| error(&format!("Invalid pit count {:?}", pits)));
+    let seeds = seeds
+        .parse::<u8>()
+        .unwrap_or_else(|_| error(&format!("Invalid seed count {:?}", seeds)));
+
+    match pits {
+        1 => main_impl::<1>(seeds, output),
+        2 => main_impl::<2>(seeds, output),
+        3 => main_impl::<3>(seeds, output),
+        4 => main_impl::<4>(seeds, output),
+        5 => main_impl::<5>(seeds, output),
+        6 => main_impl::<6>(seeds, output),
+        _ => error(&format!("Unsupported pit count {}", pits)),
+    }
+}
+
+fn main_impl<const P: usize>(seeds: u8, output: &str) {
+    let start = OwareBoard::<P>::new(seeds);
+
+    println!("Settings");
+    println!("  pits: {}, seeds: {}", P, seeds);
+    println!();
+    println!("{}", start);
+
+    let start_time = Instant::now();
+    let solution = solve_oware(&start);
+    let elapsed = start_time.elapsed();
+
+    let value = solution.value();
+    let best_moves = &solution.positions.iter().find(|p| p.board == start).unwrap().best_moves;
+
+    println!("Solved {} positions in {:.2?}", solution.positions.len(), elapsed);
+    match value.value {
+        OutcomeWDL::Draw => println!("  value: Draw"),
+        _ => println!("  value: {:?} in {}", value.value, value.distance),
+    }
+    println!("  best moves: {:?}", best_moves);
+
+    let file = File::create(output).unwrap_or_else(|e| error(&format!("Cannot create {:?}: {}", output, e)));
+    let mut writer = BufWriter::new(file);
+    solution
+        .write_csv(&mut writer)
+        .and_then(|()| writer.flush())
+        .unwrap_or_else(|e| error(&format!("Failed to write {:?}: {}", output, e)));
+    println!("Written to {:?}", output);
+}
+
+fn error(str: &str) -> ! {
+    eprintln!("{}", str);
+    usage()
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: oware_solve <pits> <seeds> <output>");
+    eprintln!("  pits is the number of pits per player, between 1 and 6");
+    eprintln!("  the results are written to the output file as comma-separated values");
+    std::process::exit(1);
+}
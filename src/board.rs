@@ -7,8 +7,7 @@ use internal_iterator::InternalIterator;
 use rand::Rng;
 
 use crate::symmetry::Symmetry;
-
-// TODO consider adding pseudo-legal movegen and a play variant that can reject non-available moves
+use crate::util::zobrist::ZobristKey;
 
 /// One of the two players.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -59,6 +58,9 @@ where
 
     /// Pick a random move from the `available_moves` with a uniform distribution.
     /// Can be overridden for better performance.
+    ///
+    /// For chance nodes this must instead sample according to [Board::chance_probability],
+    /// boards with non-uniform chance nodes have to override this function.
     fn random_available_move(&self, rng: &mut impl Rng) -> Result<Self::Move, BoardDone> {
         let count = self.available_moves()?.count();
         let index = rng.gen_range(0..count);
@@ -71,6 +73,17 @@ where
     /// Play the move `mv`, modifying this board.
     fn play(&mut self, mv: Self::Move) -> Result<(), PlayError>;
 
+    /// Play a move yielded by [Board::pseudo_legal_moves], which may turn out to not be available.
+    /// In that case `Err(PlayError::UnavailableMove)` is returned and the board is left unchanged.
+    ///
+    /// Compared to [Board::play] this can skip the checks that are already guaranteed by pseudo-legal move generation.
+    /// Passing any other move is a logic error and can panic or leave the board in an inconsistent state.
+    ///
+    /// The default implementation simply calls [Board::play].
+    fn try_play_pseudo(&mut self, mv: Self::Move) -> Result<(), PlayError> {
+        self.play(mv)
+    }
+
     /// Clone this board, play `mv` on it and return the new board.
     /// Can be overridden for better performance.
     fn clone_and_play(&self, mv: Self::Move) -> Result<Self, PlayError> {
@@ -79,6 +92,20 @@ where
         Ok(next)
     }
 
+    /// Play `mv` on this board, call `f` with the resulting board and finally restore this board to its original state.
+    /// Searches use this instead of [Board::clone_and_play] so they don't need to clone boards that can undo moves.
+    ///
+    /// `mv` only has to be pseudo-legal, if it turns out to not be available `Err(PlayError::UnavailableMove)`
+    /// is returned without calling `f`, see [Board::try_play_pseudo].
+    ///
+    /// The default implementation plays the move on a clone of this board.
+    /// Boards that implement [UndoBoard] should override this function with [play_scoped_undo].
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        let mut next = self.clone();
+        next.try_play_pseudo(mv)?;
+        Ok(f(&mut next))
+    }
+
     /// The outcome of this board, is `None` when this games is not done yet.
     fn outcome(&self) -> Option<Outcome>;
 
@@ -87,6 +114,27 @@ where
         self.outcome().is_some()
     }
 
+    /// Whether the next move on this board is a random event instead of a choice made by a player.
+    ///
+    /// The available moves of a chance node are the possible outcomes of the event,
+    /// and their probabilities are given by [Board::chance_probability].
+    /// [Board::next_player] is still used to determine the POV of the values of chance nodes,
+    /// typically it is the player that will make the next real move.
+    ///
+    /// The default implementation returns `false`, which is correct for deterministic games.
+    fn is_chance_node(&self) -> bool {
+        false
+    }
+
+    /// The probability that the chance event of this board results in `mv`.
+    /// Only valid if [Board::is_chance_node] is true and `mv` is available.
+    ///
+    /// The default implementation returns a uniform distribution over the available moves.
+    fn chance_probability(&self, mv: Self::Move) -> f32 {
+        debug_assert!(self.is_chance_node() && self.is_available_move(mv) == Ok(true));
+        1.0 / self.available_moves().unwrap().count() as f32
+    }
+
     /// Whether the player who plays a move can lose by playing that move.
     /// Symbolically whether `b.won_by() == Some(Winner::Player(b.next_player()))` can ever be true.
     /// This may be pessimistic, returning `true` is always correct.
@@ -120,13 +168,75 @@ where
         Ok(())
     }
 
+    /// Return an iterator over the pseudo-legal moves of this board.
+    /// These are a superset of the available moves that is cheaper to generate, leaving the expensive legality checks
+    /// until the move is actually played with [Board::try_play_pseudo].
+    fn pseudo_legal_moves(&self) -> Result<PseudoLegalMovesIterator<'_, Self>, BoardDone> {
+        PseudoLegalMovesIterator::new(self)
+    }
+
+    /// The implementation of the iterator returned by [Board::pseudo_legal_moves],
+    /// boards with cheaper pseudo-legal move generation should override this function.
+    ///
+    /// The default implementation yields the available moves.
+    /// This function is only called on boards that are not done.
+    fn try_for_each_pseudo_legal_move<R>(&self, f: impl FnMut(Self::Move) -> ControlFlow<R>) -> ControlFlow<R> {
+        self.available_moves().unwrap().try_for_each(f)
+    }
+
     /// The same as `self.available_moves().map(|mv| self.clone_and_play(mv))`, but needs less error handling.
     /// Can be overridden for better performance by skipping the valid move check.
-    fn children(&self) -> Result<BoardChildrenIterator<Self>, BoardDone> {
+    fn children(&self) -> Result<BoardChildrenIterator<'_, Self>, BoardDone> {
         BoardChildrenIterator::new(self)
     }
 }
 
+/// A [Board] that can undo moves, which is typically a lot cheaper than cloning the entire board.
+///
+/// Implementations should also override [Board::play_scoped] with [play_scoped_undo],
+/// that is how the search algorithms in this crate take advantage of this trait.
+pub trait UndoBoard: Board {
+    /// The information needed to undo a move.
+    type Undo;
+
+    /// Play the move `mv` the same way as [Board::play], and return a token that can later be used to undo it.
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError>;
+
+    /// Undo the move that returned `undo`, restoring this board to the exact state it had before that move.
+    /// Moves must be undone in the reverse order they were played in,
+    /// passing the token of any other move is a logic error and can panic or leave the board in an inconsistent state.
+    fn undo(&mut self, undo: Self::Undo);
+}
+
+/// A [Board] that maintains a [Zobrist hash](https://www.chessprogramming.org/Zobrist_Hashing) of its state.
+///
+/// The key is typically updated incrementally while moves are played, which makes it a lot cheaper than [Hash]ing
+/// the full board. This makes it suitable for transposition tables and caches like the one in
+/// [perft_zobrist](crate::util::game_stats::perft_zobrist).
+///
+/// Equal boards must have equal keys. Different boards should have different keys with high probability.
+/// Only state that has no effect at all on the rest of the game can be left out of the key,
+/// anything that can change the available moves or the outcome, like move counters or a history used to detect
+/// repetitions, must be included.
+pub trait ZobristBoard: Board {
+    type Key: ZobristKey;
+
+    /// The Zobrist key of the current state of this board.
+    fn zobrist(&self) -> Self::Key;
+}
+
+/// Implementation of [Board::play_scoped] for boards that implement [UndoBoard].
+pub fn play_scoped_undo<B: UndoBoard, R>(
+    board: &mut B,
+    mv: B::Move,
+    f: impl FnOnce(&mut B) -> R,
+) -> Result<R, PlayError> {
+    let undo = board.play_undoable(mv)?;
+    let result = f(board);
+    board.undo(undo);
+    Ok(result)
+}
+
 /// A marker trait for boards which guarantee that [Board::next_player] flips after a move is played.
 pub trait Alternating {}
 
@@ -262,6 +372,12 @@ pub struct AvailableMovesIterator<'a, B: Board> {
     board: &'a B,
 }
 
+/// The iterator returned by [Board::pseudo_legal_moves].
+#[derive(Debug, Clone)]
+pub struct PseudoLegalMovesIterator<'a, B: Board> {
+    board: &'a B,
+}
+
 /// A helper struct function can be used to implement [InternalIterator] for [AvailableMovesIterator].
 /// based on [BoardMoves::all_possible_moves] and [Board::is_available_move].
 /// This may be a lot slower then directly generating the available moves.
@@ -281,6 +397,24 @@ impl<'a, B: Board> AvailableMovesIterator<'a, B> {
     }
 }
 
+impl<'a, B: Board> PseudoLegalMovesIterator<'a, B> {
+    pub fn new(board: &'a B) -> Result<Self, BoardDone> {
+        board.check_done()?;
+        Ok(PseudoLegalMovesIterator { board })
+    }
+}
+
+impl<'a, B: Board> InternalIterator for PseudoLegalMovesIterator<'a, B> {
+    type Item = B::Move;
+
+    fn try_for_each<R, F>(self, f: F) -> ControlFlow<R>
+    where
+        F: FnMut(Self::Item) -> ControlFlow<R>,
+    {
+        self.board.try_for_each_pseudo_legal_move(f)
+    }
+}
+
 impl<'a, B: Board> BruteforceMoveIterator<'a, B> {
     pub fn new(board: &'a B) -> Result<Self, BoardDone> {
         board.check_done()?;
@@ -3,19 +3,26 @@ use std::hash::{Hash, Hasher};
 use std::ops::ControlFlow;
 use std::str::FromStr;
 
-use arimaa_engine_step::{Action, Direction, GameState, Piece, Square, Terminal};
+use arimaa_engine_step::{Action, Direction, GameState, Piece, Square, Terminal, Zobrist};
 use internal_iterator::InternalIterator;
 use once_cell::sync::OnceCell;
 
 use crate::board::{
-    AllMovesIterator, AvailableMovesIterator, Board, BoardDone, BoardMoves, Outcome, PlayError, Player,
+    AllMovesIterator, AvailableMovesIterator, Board, BoardDone, BoardMoves, Outcome, PlayError, Player, ZobristBoard,
 };
 use crate::impl_unit_symmetry_board;
 use crate::util::bitboard::BitBoard8;
+use crate::util::zobrist::zobrist_value;
+
+const ZOBRIST_MOVE_START_SEED: u64 = 0xa1a1;
+const ZOBRIST_HISTORY_SEED: u64 = 0xa1a2;
+const ZOBRIST_TRAPPED: u64 = zobrist_value(0xa1a3, 0);
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ArimaaBoard {
     state: GameState,
+    /// Order-independent combination of the repetition history, see [ZobristBoard::zobrist].
+    history_hash: u64,
 
     // careful, this should be invalidated whenever the state is modified
     available_moves_cache: OnceCell<Vec<Action>>,
@@ -32,8 +39,10 @@ impl ArimaaBoard {
     pub const TRAP_MASK: BitBoard8 = BitBoard8(0x240000240000);
 
     pub fn from_state(state: GameState) -> Self {
+        let history_hash = history_hash_full(&state);
         ArimaaBoard {
             state,
+            history_hash,
             available_moves_cache: OnceCell::new(),
         }
     }
@@ -88,9 +97,24 @@ impl Board for ArimaaBoard {
     fn play(&mut self, mv: Action) -> Result<(), PlayError> {
         self.check_can_play(mv)?;
 
+        let prev_history_len = self.history_len();
         self.state = self.state.take_action(&mv);
         self.available_moves_cache = OnceCell::new();
 
+        // the engine either keeps the history, appends the position at the end of a move to it
+        //   or clears it when a piece gets trapped (possibly followed by an append)
+        let history_len = self.history_len();
+        if history_len > 1 && history_len == prev_history_len + 1 {
+            let added = self
+                .state
+                .as_play_phase()
+                .and_then(|p| p.hash_history().head())
+                .unwrap();
+            self.history_hash = self.history_hash.wrapping_add(history_entry_hash(added));
+        } else if history_len != prev_history_len || history_len <= 1 {
+            self.history_hash = history_hash_full(&self.state);
+        }
+
         Ok(())
     }
 
@@ -106,6 +130,19 @@ impl Board for ArimaaBoard {
     }
 }
 
+fn history_entry_hash(entry: &Zobrist) -> u64 {
+    zobrist_value(ZOBRIST_HISTORY_SEED, entry.board_state_hash())
+}
+
+// repetitions only depend on how often each position occurs, so combine the history in an order-independent way
+fn history_hash_full(state: &GameState) -> u64 {
+    state.as_play_phase().map_or(0, |p| {
+        p.hash_history()
+            .iter()
+            .fold(0u64, |acc, prev| acc.wrapping_add(history_entry_hash(prev)))
+    })
+}
+
 pub fn player_from_bool(player_bool: bool) -> Player {
     match player_bool {
         true => Player::A,
@@ -172,6 +209,30 @@ impl Hash for ArimaaBoard {
     }
 }
 
+impl ZobristBoard for ArimaaBoard {
+    type Key = u64;
+
+    /// Combines the transposition hash that is incrementally updated by the engine with the state that decides
+    /// which steps are still available: the position at the start of the current move, whether a piece has been
+    /// trapped during it and the history used for the repetition rule.
+    /// The history part is updated in [Board::play], so this takes constant time.
+    fn zobrist(&self) -> u64 {
+        let mut result = self.state.transposition_hash();
+
+        if let Some(play_phase) = self.state.as_play_phase() {
+            if let Some(start) = play_phase.previous_piece_boards().first() {
+                let start = Zobrist::from_piece_board(start.piece_board(), self.state.is_p1_turn_to_move(), 0);
+                result ^= zobrist_value(ZOBRIST_MOVE_START_SEED, start.board_state_hash());
+            }
+            if play_phase.piece_trapped_this_turn() {
+                result ^= ZOBRIST_TRAPPED;
+            }
+        }
+
+        result ^ self.history_hash
+    }
+}
+
 impl_unit_symmetry_board!(ArimaaBoard);
 
 impl Display for ArimaaBoard {
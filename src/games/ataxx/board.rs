@@ -5,12 +5,13 @@ use internal_iterator::InternalIterator;
 use rand::Rng;
 
 use crate::board::{
-    AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardDone, BoardMoves, BoardSymmetry, Outcome,
-    PlayError, Player,
+    play_scoped_undo, AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardDone, BoardMoves,
+    BoardSymmetry, Outcome, PlayError, Player, UndoBoard, ZobristBoard,
 };
 use crate::symmetry::D4Symmetry;
 use crate::util::bitboard::BitBoard8;
 use crate::util::coord::Coord8;
+use crate::util::zobrist::{zobrist_table, zobrist_value};
 
 pub const MAX_MOVES_SINCE_LAST_COPY: u8 = 100;
 
@@ -23,6 +24,7 @@ pub struct AtaxxBoard {
     pub(super) moves_since_last_copy: u8,
     pub(super) next_player: Player,
     pub(super) outcome: Option<Outcome>,
+    pub(super) zobrist: u64,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -32,6 +34,20 @@ pub enum Move {
     Jump { from: Coord8, to: Coord8 },
 }
 
+/// The information needed to undo a move on an [AtaxxBoard], see [UndoBoard].
+#[derive(Debug, Copy, Clone)]
+pub struct AtaxxUndo {
+    tiles_a: BitBoard8,
+    tiles_b: BitBoard8,
+    moves_since_last_copy: u8,
+    zobrist: u64,
+}
+
+/// Zobrist values for the tiles of player A, player B and the gaps, followed by the value toggled after every move.
+const ZOBRIST: [u64; 3 * 64 + 1] = zobrist_table(0xa7a7);
+const ZOBRIST_TURN: u64 = ZOBRIST[3 * 64];
+const ZOBRIST_COPY_COUNTER_SEED: u64 = 0xa7a8;
+
 impl Default for AtaxxBoard {
     fn default() -> Self {
         AtaxxBoard::diagonal(7)
@@ -57,8 +73,10 @@ impl AtaxxBoard {
             moves_since_last_copy,
             next_player,
             outcome: None,
+            zobrist: 0,
         };
         result.update_outcome();
+        result.update_zobrist();
         result.assert_valid();
         result
     }
@@ -71,7 +89,7 @@ impl AtaxxBoard {
         let tiles_a = BitBoard8::coord(Coord8::from_xy(0, corner)) | BitBoard8::coord(Coord8::from_xy(corner, 0));
         let tiles_b = BitBoard8::coord(Coord8::from_xy(0, 0)) | BitBoard8::coord(Coord8::from_xy(corner, corner));
 
-        AtaxxBoard {
+        let mut result = AtaxxBoard {
             size,
             tiles_a,
             tiles_b,
@@ -79,7 +97,10 @@ impl AtaxxBoard {
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: if size == 2 { Some(Outcome::Draw) } else { None },
-        }
+            zobrist: 0,
+        };
+        result.update_zobrist();
+        result
     }
 
     pub fn empty(size: u8) -> Self {
@@ -92,6 +113,7 @@ impl AtaxxBoard {
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: Some(Outcome::Draw),
+            zobrist: 0,
         }
     }
 
@@ -195,6 +217,16 @@ impl AtaxxBoard {
         self.outcome = outcome;
     }
 
+    /// Recompute the zobrist key from scratch, for when the fields have been modified directly.
+    pub(super) fn update_zobrist(&mut self) {
+        let turn = match self.next_player {
+            Player::A => 0,
+            Player::B => ZOBRIST_TURN,
+        };
+        self.zobrist =
+            zobrist_tiles(0, self.tiles_a) ^ zobrist_tiles(1, self.tiles_b) ^ zobrist_tiles(2, self.gaps) ^ turn;
+    }
+
     pub fn assert_valid(&self) {
         // no invalid bits
         let invalid_mask = !self.full_mask();
@@ -211,6 +243,10 @@ impl AtaxxBoard {
         let mut clone = self.clone();
         clone.update_outcome();
         assert_eq!(self.outcome, clone.outcome);
+
+        // correct zobrist
+        clone.update_zobrist();
+        assert_eq!(self.zobrist, clone.zobrist);
     }
 
     pub fn map_coord(&self, coord: Coord8, sym: D4Symmetry) -> Coord8 {
@@ -310,6 +346,8 @@ impl Board for AtaxxBoard {
     fn play(&mut self, mv: Self::Move) -> Result<(), PlayError> {
         self.check_can_play(mv)?;
 
+        let prev_tiles_a = self.tiles_a;
+        let prev_tiles_b = self.tiles_b;
         let (next_tiles, other_tiles) = self.tiles_pov_mut();
 
         let to = match mv {
@@ -318,6 +356,7 @@ impl Board for AtaxxBoard {
                 //   a real move, since otherwise the game would have finished already
                 self.next_player = self.next_player.other();
                 self.moves_since_last_copy += 1;
+                self.zobrist ^= ZOBRIST_TURN;
                 return Ok(());
             }
             Move::Copy { to } => to,
@@ -339,10 +378,17 @@ impl Board for AtaxxBoard {
 
         self.update_outcome();
         self.next_player = self.next_player.other();
+        self.zobrist ^= zobrist_tiles(0, prev_tiles_a ^ self.tiles_a)
+            ^ zobrist_tiles(1, prev_tiles_b ^ self.tiles_b)
+            ^ ZOBRIST_TURN;
 
         Ok(())
     }
 
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.outcome
     }
@@ -352,6 +398,40 @@ impl Board for AtaxxBoard {
     }
 }
 
+impl UndoBoard for AtaxxBoard {
+    type Undo = AtaxxUndo;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        let undo = AtaxxUndo {
+            tiles_a: self.tiles_a,
+            tiles_b: self.tiles_b,
+            moves_since_last_copy: self.moves_since_last_copy,
+            zobrist: self.zobrist,
+        };
+        self.play(mv)?;
+        Ok(undo)
+    }
+
+    fn undo(&mut self, undo: Self::Undo) {
+        self.tiles_a = undo.tiles_a;
+        self.tiles_b = undo.tiles_b;
+        self.moves_since_last_copy = undo.moves_since_last_copy;
+        self.next_player = self.next_player.other();
+        self.outcome = None;
+        self.zobrist = undo.zobrist;
+    }
+}
+
+impl ZobristBoard for AtaxxBoard {
+    type Key = u64;
+
+    /// The key includes the tiles, the gaps, the next player and [AtaxxBoard::moves_since_last_copy],
+    /// since the latter can end the game in a draw.
+    fn zobrist(&self) -> u64 {
+        self.zobrist ^ zobrist_value(ZOBRIST_COPY_COUNTER_SEED, self.moves_since_last_copy as u64)
+    }
+}
+
 impl Alternating for AtaxxBoard {}
 
 impl BoardSymmetry<AtaxxBoard> for AtaxxBoard {
@@ -359,7 +439,7 @@ impl BoardSymmetry<AtaxxBoard> for AtaxxBoard {
     type CanonicalKey = (u64, u64, u64);
 
     fn map(&self, sym: Self::Symmetry) -> Self {
-        AtaxxBoard {
+        let mut result = AtaxxBoard {
             size: self.size,
             tiles_a: self.map_tiles(self.tiles_a, sym),
             tiles_b: self.map_tiles(self.tiles_b, sym),
@@ -367,7 +447,10 @@ impl BoardSymmetry<AtaxxBoard> for AtaxxBoard {
             moves_since_last_copy: self.moves_since_last_copy,
             next_player: self.next_player,
             outcome: self.outcome,
-        }
+            zobrist: 0,
+        };
+        result.update_zobrist();
+        result
     }
 
     fn map_move(&self, sym: Self::Symmetry, mv: Move) -> Move {
@@ -491,6 +574,13 @@ pub fn coord_to_ring(coord: Coord8) -> BitBoard8 {
     COORD_TO_RING_VALUES[coord.index() as usize]
 }
 
+/// The combined zobrist value of the given tiles for `kind` (player A, player B or gaps).
+fn zobrist_tiles(kind: usize, tiles: BitBoard8) -> u64 {
+    tiles
+        .into_iter()
+        .fold(0, |acc, coord| acc ^ ZOBRIST[64 * kind + coord.index() as usize])
+}
+
 #[cfg(test)]
 mod tests {
     use crate::games::ataxx::coord_to_ring;
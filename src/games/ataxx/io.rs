@@ -127,6 +127,7 @@ impl AtaxxBoard {
         let _ = full_str.parse::<u32>().map_err(|_| err("Invalid full counter"))?;
 
         board.update_outcome();
+        board.update_zobrist();
         board.assert_valid();
 
         Ok(board)
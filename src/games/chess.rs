@@ -5,15 +5,23 @@ use std::hash::Hash;
 use std::ops::ControlFlow;
 use std::str::FromStr;
 
-use chess::{BoardStatus, ChessMove, Color, File, MoveGen, Piece, Square};
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_pawn_quiets, get_rook_moves, BitBoard,
+    BoardStatus, ChessMove, Color, File, MoveGen, Piece, Square, EMPTY,
+};
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
 use crate::board::{
     AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardDone, BoardMoves, Outcome, PlayError, Player,
+    ZobristBoard,
 };
 use crate::impl_unit_symmetry_board;
 use crate::util::bot_game::Replay;
+use crate::util::zobrist::zobrist_value;
+
+const ZOBRIST_COUNTER_SEED: u64 = 0xc4c4;
+const ZOBRIST_HISTORY_SEED: u64 = 0xc4c5;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Rules {
@@ -27,6 +35,8 @@ pub struct ChessBoard {
 
     inner: chess::Board,
     history: Vec<chess::Board>,
+    /// Order-independent combination of the history, see [ZobristBoard::zobrist].
+    history_hash: u64,
 
     // cached values
     non_pawn_or_capture_moves: u16,
@@ -64,6 +74,7 @@ impl ChessBoard {
             rules,
             inner,
             history: vec![],
+            history_hash: 0,
             non_pawn_or_capture_moves: 0,
             repetitions: 0,
             outcome: None,
@@ -220,7 +231,109 @@ impl Board for ChessBoard {
 
     fn play(&mut self, mv: Self::Move) -> Result<(), PlayError> {
         self.check_can_play(mv)?;
+        self.play_unchecked(mv, self.inner.make_move_new(mv));
+        Ok(())
+    }
+
+    fn try_play_pseudo(&mut self, mv: Self::Move) -> Result<(), PlayError> {
+        // the only thing pseudo-legal moves don't guarantee yet is that our own king is safe afterwards,
+        //   which is a lot cheaper to check than generating all legal moves again
+        self.check_done()?;
+        let us = self.inner.side_to_move();
+        let next = self.inner.make_move_new(mv);
+        if is_attacked(&next, next.king_square(us), !us) {
+            return Err(PlayError::UnavailableMove);
+        }
+        self.play_unchecked(mv, next);
+        Ok(())
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    fn can_lose_after_move() -> bool {
+        false
+    }
+
+    fn try_for_each_pseudo_legal_move<R>(&self, mut f: impl FnMut(Self::Move) -> ControlFlow<R>) -> ControlFlow<R> {
+        // all moves that follow the movement rules of the pieces, including ones that leave our own king in check
+        let board = &self.inner;
+        let us = board.side_to_move();
+        let ours = *board.color_combined(us);
+        let theirs = *board.color_combined(!us);
+        let combined = *board.combined();
+
+        for from in ours {
+            let piece = board.piece_on(from).unwrap();
+            let targets = match piece {
+                Piece::Pawn => {
+                    let mut victims = theirs;
+                    if let Some(ep) = board.en_passant() {
+                        victims |= BitBoard::from_square(ep.uforward(us));
+                    }
+                    get_pawn_quiets(from, us, combined) | get_pawn_attacks(from, us, victims)
+                }
+                Piece::Knight => get_knight_moves(from),
+                Piece::Bishop => get_bishop_moves(from, combined),
+                Piece::Rook => get_rook_moves(from, combined),
+                Piece::Queen => get_bishop_moves(from, combined) | get_rook_moves(from, combined),
+                Piece::King => get_king_moves(from),
+            };
+
+            let promotion = piece == Piece::Pawn && from.get_rank() == us.to_seventh_rank();
+            for to in targets & !ours {
+                if promotion {
+                    for piece in chess::PROMOTION_PIECES {
+                        f(ChessMove::new(from, to, Some(piece)))?;
+                    }
+                } else {
+                    f(ChessMove::new(from, to, None))?;
+                }
+            }
+        }
+
+        // castling can't pass through check, so that is checked here already
+        let king = board.king_square(us);
+        let rights = board.my_castle_rights();
+        if *board.checkers() == EMPTY {
+            if rights.has_kingside() && combined & rights.kingside_squares(us) == EMPTY {
+                let to = king.uright().uright();
+                if !is_attacked(board, king.uright(), !us) {
+                    f(ChessMove::new(king, to, None))?;
+                }
+            }
+            if rights.has_queenside() && combined & rights.queenside_squares(us) == EMPTY {
+                let to = king.uleft().uleft();
+                if !is_attacked(board, king.uleft(), !us) {
+                    f(ChessMove::new(king, to, None))?;
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
 
+/// Whether `square` is attacked by any piece of color `by`.
+fn is_attacked(board: &chess::Board, square: Square, by: Color) -> bool {
+    let combined = *board.combined();
+    let attackers = *board.color_combined(by);
+    let pieces = |piece: Piece| *board.pieces(piece) & attackers;
+
+    let diagonal = pieces(Piece::Bishop) | pieces(Piece::Queen);
+    let straight = pieces(Piece::Rook) | pieces(Piece::Queen);
+
+    get_bishop_moves(square, combined) & diagonal != EMPTY
+        || get_rook_moves(square, combined) & straight != EMPTY
+        || get_knight_moves(square) & pieces(Piece::Knight) != EMPTY
+        || get_king_moves(square) & pieces(Piece::King) != EMPTY
+        || get_pawn_attacks(square, !by, pieces(Piece::Pawn)) != EMPTY
+}
+
+impl ChessBoard {
+    /// Play the given move, which must be legal. `next` is the inner board after playing the move.
+    fn play_unchecked(&mut self, mv: ChessMove, next: chess::Board) {
         // keep track of stats for reversible moves
         let prev = self.inner;
         let old_side_to_move = prev.side_to_move();
@@ -231,7 +344,7 @@ impl Board for ChessBoard {
         let was_pawn_move = moved_piece == Piece::Pawn;
 
         // make the move
-        self.inner = prev.make_move_new(mv);
+        self.inner = next;
 
         // collect more stats
         let removed_castle = old_castle_rights != self.inner.castle_rights(old_side_to_move);
@@ -246,9 +359,14 @@ impl Board for ChessBoard {
         // update history
         let reset_history = was_capture || was_pawn_move || removed_castle || self.rules.max_repetitions.is_none();
         if reset_history {
-            self.history.clear()
+            self.history.clear();
+            self.history_hash = 0;
         } else {
-            self.history.push(prev)
+            self.history.push(prev);
+            // repetitions only depend on how often each position occurs, so combine the history in an order-independent way
+            self.history_hash = self
+                .history_hash
+                .wrapping_add(zobrist_value(ZOBRIST_HISTORY_SEED, prev.get_hash()));
         }
 
         // update repetition counter based on history
@@ -264,16 +382,23 @@ impl Board for ChessBoard {
                 BoardStatus::Checkmate => Some(Outcome::WonBy(self.next_player().other())),
             }
         };
-
-        Ok(())
     }
+}
 
-    fn outcome(&self) -> Option<Outcome> {
-        self.outcome
-    }
+impl ZobristBoard for ChessBoard {
+    type Key = u64;
+
+    /// Combines the incrementally updated hash of the inner board, which includes the side to move,
+    /// castling rights and en passant square, with the history and the reversible move counter,
+    /// since those can end the game in a draw.
+    /// The history part is updated while playing moves, so this takes constant time.
+    fn zobrist(&self) -> u64 {
+        let mut result = self.inner.get_hash();
+        if self.rules.max_moves_without_pawn_or_capture.is_some() {
+            result ^= zobrist_value(ZOBRIST_COUNTER_SEED, self.non_pawn_or_capture_moves as u64);
+        }
 
-    fn can_lose_after_move() -> bool {
-        false
+        result ^ self.history_hash
     }
 }
 
@@ -349,10 +474,10 @@ impl Rules {
     }
 
     pub fn is_draw(self, board: &ChessBoard) -> bool {
-        let draw_repetitions = self.max_repetitions.map_or(false, |m| board.repetitions >= m);
+        let draw_repetitions = self.max_repetitions.is_some_and(|m| board.repetitions >= m);
         let draw_reversible = self
             .max_moves_without_pawn_or_capture
-            .map_or(false, |m| board.non_pawn_or_capture_moves >= m);
+            .is_some_and(|m| board.non_pawn_or_capture_moves >= m);
         let only_kings = board.inner.combined().popcnt() == 2;
         draw_repetitions || draw_reversible || only_kings
     }
@@ -3,10 +3,14 @@ use std::hash::{Hash, Hasher};
 use std::ops::Range;
 
 use crate::board::{
-    Alternating, Board, BoardDone, BoardMoves, BoardSymmetry, BruteforceMoveIterator, Outcome, PlayError, Player,
+    play_scoped_undo, Alternating, Board, BoardDone, BoardMoves, BoardSymmetry, BruteforceMoveIterator, Outcome,
+    PlayError, Player, UndoBoard, ZobristBoard,
 };
 use crate::symmetry::D1Symmetry;
 use crate::util::iter::ClonableInternal;
+use crate::util::zobrist::mix64;
+
+pub mod solver;
 
 /// The Connect4 game on a 7x6 board.
 ///
@@ -54,7 +58,7 @@ impl Board for Connect4 {
     type Move = u8;
 
     fn next_player(&self) -> Player {
-        if self.tiles_occupied.count_ones() % 2 == 0 {
+        if self.tiles_occupied.count_ones().is_multiple_of(2) {
             Player::A
         } else {
             Player::B
@@ -92,6 +96,10 @@ impl Board for Connect4 {
         Ok(())
     }
 
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.outcome
     }
@@ -101,6 +109,36 @@ impl Board for Connect4 {
     }
 }
 
+impl UndoBoard for Connect4 {
+    type Undo = u8;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        self.play(mv)?;
+        Ok(mv)
+    }
+
+    fn undo(&mut self, mv: Self::Undo) {
+        // the last move was played on the highest occupied tile of the column
+        let column = self.tiles_occupied & (0xff << (mv * 8));
+        debug_assert!(column != 0, "can only undo the last move");
+        let tile = 1 << (63 - column.leading_zeros());
+
+        self.tiles_occupied &= !tile;
+        self.tiles_next ^= self.tiles_occupied;
+        self.outcome = None;
+    }
+}
+
+impl ZobristBoard for Connect4 {
+    type Key = u64;
+
+    /// The [perfect_hash](Connect4::perfect_hash) is already cheap to compute and collision-free,
+    /// so we just mix its bits instead of tracking a separate key.
+    fn zobrist(&self) -> u64 {
+        mix64(self.perfect_hash())
+    }
+}
+
 impl Alternating for Connect4 {}
 
 impl<'a> BoardMoves<'a, Connect4> for Connect4 {
@@ -122,9 +160,10 @@ impl BoardSymmetry<Connect4> for Connect4 {
 
     fn map(&self, sym: Self::Symmetry) -> Self {
         if sym.mirror {
+            // each column is a byte and the top byte is unused, so shift the reversed columns back down
             Connect4 {
-                tiles_next: self.tiles_next.swap_bytes(),
-                tiles_occupied: self.tiles_occupied.swap_bytes(),
+                tiles_next: self.tiles_next.swap_bytes() >> 8,
+                tiles_occupied: self.tiles_occupied.swap_bytes() >> 8,
                 outcome: self.outcome,
             }
         } else {
@@ -0,0 +1,575 @@
+//! A strong solver for [Connect4], based on <http://blog.gamesolver.org/solving-connect-four/>.
+//!
+//! Positions are scored from the POV of the next player. A positive score means the next player can force a win,
+//! the score is then the number of their own stones they still have left when winning, plus one.
+//! Similarly a negative score is a forced loss and zero is a draw.
+//! Scores can be converted to the more generic [SolverValue] with [score_to_value].
+//!
+//! The first moves are by far the slowest to solve, even the outcome of the empty board takes minutes in release mode.
+//! [OpeningBook::start] contains the scores of all positions with at most two moves played,
+//! with it the empty board and its children are solved instantly.
+//! Generate an [OpeningBook] for other early positions once and reuse it with [Connect4Solver::with_book].
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt::{Debug, Formatter};
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::ai::solver::SolverValue;
+use crate::ai::Bot;
+use crate::board::{Board, BoardDone};
+use crate::games::connect4::Connect4;
+use crate::util::tt::{Bound, Replacement, TTEntry, TranspositionTable};
+use crate::util::zobrist::mix64;
+use crate::wdl::OutcomeWDL;
+
+const SIZE: i32 = Connect4::TILES as i32;
+
+const BOTTOM_MASK: u64 = 0x01_0101_0101_0101;
+const BOARD_MASK: u64 = BOTTOM_MASK * 0x3f;
+
+/// Center columns are more likely to be good, so they are tried first.
+const COLUMN_ORDER: [u8; Connect4::WIDTH as usize] = [3, 2, 4, 1, 5, 0, 6];
+
+const MAGIC: &[u8; 4] = b"C4BK";
+const VERSION: u8 = 1;
+
+fn column_mask(col: u8) -> u64 {
+    0x3f << (col * 8)
+}
+
+fn mirror(tiles: u64) -> u64 {
+    tiles.swap_bytes() >> 8
+}
+
+/// The empty tiles that would complete a line of four for the player with stones `tiles`.
+fn winning_tiles(tiles: u64, occupied: u64) -> u64 {
+    // vertical
+    let mut result = (tiles << 1) & (tiles << 2) & (tiles << 3);
+
+    // horizontal and both diagonals, the unused top rows of each column prevent wrapping around
+    for shift in [8, 7, 9] {
+        let pair = (tiles << shift) & (tiles << (2 * shift));
+        result |= pair & (tiles << (3 * shift));
+        result |= pair & (tiles >> shift);
+
+        let pair = (tiles >> shift) & (tiles >> (2 * shift));
+        result |= pair & (tiles << shift);
+        result |= pair & (tiles >> (3 * shift));
+    }
+
+    result & (BOARD_MASK ^ occupied)
+}
+
+/// A lightweight copy of the bitboards of a [Connect4] board, without the outcome tracking.
+#[derive(Debug, Copy, Clone)]
+struct Position {
+    tiles_next: u64,
+    tiles_occupied: u64,
+    moves: i32,
+}
+
+impl Position {
+    fn new(board: &Connect4) -> Self {
+        Position {
+            tiles_next: board.tiles_next,
+            tiles_occupied: board.tiles_occupied,
+            moves: board.game_length() as i32,
+        }
+    }
+
+    /// The same key as [ZobristBoard::zobrist](crate::board::ZobristBoard::zobrist) for the corresponding board.
+    fn key(&self) -> u64 {
+        mix64(self.tiles_next + self.tiles_occupied + BOTTOM_MASK)
+    }
+
+    /// The [perfect_hash](Connect4::perfect_hash) of the canonical version of this position.
+    fn canonical_hash(&self) -> u64 {
+        let hash = self.tiles_next + self.tiles_occupied + BOTTOM_MASK;
+        let mirrored = mirror(self.tiles_next) + mirror(self.tiles_occupied) + BOTTOM_MASK;
+        hash.min(mirrored)
+    }
+
+    /// The tiles where a stone can be played, one per column that is not full.
+    fn possible(&self) -> u64 {
+        (self.tiles_occupied + BOTTOM_MASK) & BOARD_MASK
+    }
+
+    fn can_win_next(&self) -> bool {
+        winning_tiles(self.tiles_next, self.tiles_occupied) & self.possible() != 0
+    }
+
+    /// The moves that don't allow the opponent to win immediately.
+    /// Assumes the next player can't win immediately.
+    fn non_losing_moves(&self) -> u64 {
+        let mut possible = self.possible();
+        let opponent_win = winning_tiles(self.tiles_next ^ self.tiles_occupied, self.tiles_occupied);
+
+        let forced = possible & opponent_win;
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                // the opponent has multiple winning moves, we can only block one of them
+                return 0;
+            }
+            possible = forced;
+        }
+
+        // don't play directly below a tile where the opponent would win
+        possible & !(opponent_win >> 1)
+    }
+
+    /// The number of winning tiles we would have after playing `mv`, used for move ordering.
+    fn move_score(&self, mv: u64) -> u32 {
+        winning_tiles(self.tiles_next | mv, self.tiles_occupied).count_ones()
+    }
+
+    fn play(&mut self, mv: u64) {
+        self.tiles_next ^= self.tiles_occupied;
+        self.tiles_occupied |= mv;
+        self.moves += 1;
+    }
+}
+
+/// Convert a score as returned by [Connect4Solver::solve] for `board` to a [SolverValue],
+/// where the lengths are counted from `board`.
+pub fn score_to_value(board: &Connect4, score: i32) -> SolverValue {
+    let moves = board.game_length() as i32;
+    let parity = moves % 2;
+
+    // the winner plays their winning move when `SIZE + 1 - 2 * |score|` moves have been played,
+    // up to the parity of the player that wins
+    match score {
+        0 => SolverValue::Draw,
+        s if s > 0 => SolverValue::WinIn((SIZE + 1 - moves - 2 * s + parity) as u32),
+        s => SolverValue::LossIn((SIZE + 2 - moves + 2 * s - parity) as u32),
+    }
+}
+
+/// Negamax solver for [Connect4] with alpha-beta pruning, a [TranspositionTable] and an optional [OpeningBook].
+///
+/// The transposition table is kept between calls, so solving related positions gets faster over time.
+pub struct Connect4Solver {
+    tt: TranspositionTable<(), i8>,
+    book: Option<OpeningBook>,
+    nodes: u64,
+}
+
+impl Connect4Solver {
+    /// Create a new solver with a transposition table with space for `tt_capacity` entries.
+    /// A few million entries are needed to solve positions close to the start in a reasonable amount of time,
+    /// the very first positions are best covered by an [OpeningBook].
+    pub fn new(tt_capacity: usize) -> Self {
+        Connect4Solver {
+            tt: TranspositionTable::new(tt_capacity, Replacement::Always),
+            book: None,
+            nodes: 0,
+        }
+    }
+
+    /// Use `book` for the positions it contains instead of searching them.
+    pub fn with_book(self, book: OpeningBook) -> Self {
+        Connect4Solver {
+            book: Some(book),
+            ..self
+        }
+    }
+
+    pub fn book(&self) -> Option<&OpeningBook> {
+        self.book.as_ref()
+    }
+
+    /// The total number of positions searched by this solver.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Clear the transposition table.
+    pub fn clear(&mut self) {
+        self.tt.clear();
+    }
+
+    /// The exact score of `board`, see the [module documentation](self) for its meaning.
+    pub fn solve(&mut self, board: &Connect4) -> Result<i32, BoardDone> {
+        board.check_done()?;
+        Ok(self.solve_position(&Position::new(board), false))
+    }
+
+    /// Only solve the outcome of `board`, which can be much faster than [Connect4Solver::solve].
+    pub fn solve_outcome(&mut self, board: &Connect4) -> Result<OutcomeWDL, BoardDone> {
+        board.check_done()?;
+        let score = self.solve_position(&Position::new(board), true);
+        Ok(match score {
+            0 => OutcomeWDL::Draw,
+            s if s > 0 => OutcomeWDL::Win,
+            _ => OutcomeWDL::Loss,
+        })
+    }
+
+    /// Variant of [Connect4Solver::solve] that returns a [SolverValue], see [score_to_value].
+    pub fn solve_value(&mut self, board: &Connect4) -> Result<SolverValue, BoardDone> {
+        let score = self.solve(board)?;
+        Ok(score_to_value(board, score))
+    }
+
+    /// The score of each available move, from the POV of the next player of `board`.
+    pub fn solve_all_moves(&mut self, board: &Connect4) -> Result<Vec<(u8, i32)>, BoardDone> {
+        board.check_done()?;
+        let pos = Position::new(board);
+        let winning = winning_tiles(pos.tiles_next, pos.tiles_occupied);
+
+        let mut result = vec![];
+        for col in 0..Connect4::WIDTH {
+            let mv = pos.possible() & column_mask(col);
+            if mv == 0 {
+                continue;
+            }
+
+            let score = if winning & mv != 0 {
+                (SIZE + 1 - pos.moves) / 2
+            } else {
+                let mut child = pos;
+                child.play(mv);
+                -self.solve_position(&child, false)
+            };
+            result.push((col, score));
+        }
+
+        Ok(result)
+    }
+
+    /// A move with the best score, preferring the center columns if there are multiple.
+    pub fn best_move(&mut self, board: &Connect4) -> Result<u8, BoardDone> {
+        let moves = self.solve_all_moves(board)?;
+        let best = COLUMN_ORDER
+            .iter()
+            .filter_map(|&col| moves.iter().find(|&&(mv, _)| mv == col))
+            .fold(None, |best: Option<(u8, i32)>, &(mv, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((mv, score)),
+            });
+        Ok(best.unwrap().0)
+    }
+
+    fn solve_position(&mut self, pos: &Position, weak: bool) -> i32 {
+        if pos.can_win_next() {
+            return (SIZE + 1 - pos.moves) / 2;
+        }
+
+        let (mut min, mut max) = match weak {
+            true => (-1, 1),
+            false => (-(SIZE - pos.moves) / 2, (SIZE + 1 - pos.moves) / 2),
+        };
+
+        // narrow down the score with null window searches
+        while min < max {
+            // search around zero first, that quickly determines the outcome
+            let mut med = min + (max - min) / 2;
+            if med <= 0 && min / 2 < med {
+                med = min / 2;
+            } else if med >= 0 && max / 2 > med {
+                med = max / 2;
+            }
+
+            let score = self.negamax(pos, med, med + 1);
+            if score <= med {
+                max = score;
+            } else {
+                min = score;
+            }
+        }
+
+        min
+    }
+
+    /// Assumes the next player can't win immediately.
+    fn negamax(&mut self, pos: &Position, mut alpha: i32, mut beta: i32) -> i32 {
+        debug_assert!(alpha < beta);
+        debug_assert!(!pos.can_win_next());
+        self.nodes += 1;
+
+        let next = pos.non_losing_moves();
+        if next == 0 {
+            // the opponent wins on their next move
+            return -(SIZE - pos.moves) / 2;
+        }
+        if pos.moves >= SIZE - 2 {
+            return 0;
+        }
+
+        if let Some(book) = &self.book {
+            if let Some(score) = book.get_position(pos) {
+                return score;
+            }
+        }
+
+        // the opponent can't win on their next move and we can't win on this one
+        let min = -(SIZE - 2 - pos.moves) / 2;
+        let mut max = (SIZE - 1 - pos.moves) / 2;
+
+        let key = pos.key();
+        if let Some(entry) = self.tt.get(key) {
+            let value = entry.value as i32;
+            match entry.bound {
+                Bound::Exact => return value,
+                Bound::Lower => alpha = alpha.max(value),
+                Bound::Upper => max = max.min(value),
+            }
+        }
+
+        alpha = alpha.max(min);
+        beta = beta.min(max);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let mut moves = [(0, 0); Connect4::WIDTH as usize];
+        let mut len = 0;
+        for &col in &COLUMN_ORDER {
+            let mv = next & column_mask(col);
+            if mv != 0 {
+                moves[len] = (pos.move_score(mv), mv);
+                len += 1;
+            }
+        }
+        // stable, so ties keep the column order
+        moves[..len].sort_by_key(|&(score, _)| u32::MAX - score);
+
+        for &(_, mv) in &moves[..len] {
+            let mut child = *pos;
+            child.play(mv);
+
+            let score = -self.negamax(&child, -beta, -alpha);
+            if score >= beta {
+                self.store(key, score, Bound::Lower);
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        self.store(key, alpha, Bound::Upper);
+        alpha
+    }
+
+    fn store(&self, key: u64, value: i32, bound: Bound) {
+        let entry = TTEntry {
+            depth: 0,
+            value: value as i8,
+            bound,
+            best_move: None,
+        };
+        self.tt.insert(key, entry);
+    }
+}
+
+impl Debug for Connect4Solver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Connect4Solver")
+            .field("tt", &self.tt)
+            .field("book", &self.book)
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
+/// The precomputed scores of all positions up to a number of moves, see [Connect4Solver::with_book].
+///
+/// The early positions take the longest to solve, so a book that covers them saves most of the search time.
+/// Positions are stored by the [perfect_hash](Connect4::perfect_hash) of their canonical board.
+pub struct OpeningBook {
+    max_moves: u32,
+    scores: HashMap<u64, i8>,
+}
+
+impl OpeningBook {
+    /// Solve all positions reachable from `root` that have at most `max_moves` moves played.
+    ///
+    /// **Warning**: The number of positions grows exponentially with `max_moves`,
+    /// and the earliest positions are the slowest to solve.
+    pub fn generate(solver: &mut Connect4Solver, root: &Connect4, max_moves: u32) -> Self {
+        let mut scores = HashMap::new();
+        if !root.is_done() {
+            generate_book(solver, &Position::new(root), max_moves as i32, &mut scores);
+        }
+        OpeningBook { max_moves, scores }
+    }
+
+    /// The book with all positions that have at most two moves played, shipped with this crate.
+    ///
+    /// Generating it with [OpeningBook::generate] from the empty board takes over an hour in release mode.
+    pub fn start() -> Self {
+        let scores = START_BOOK.iter().copied().collect();
+        OpeningBook { max_moves: 2, scores }
+    }
+
+    /// Positions with more moves played than this are never part of the book.
+    pub fn max_moves(&self) -> u32 {
+        self.max_moves
+    }
+
+    /// The number of positions in this book.
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn get(&self, board: &Connect4) -> Option<i32> {
+        if board.is_done() {
+            return None;
+        }
+        self.get_position(&Position::new(board))
+    }
+
+    fn get_position(&self, pos: &Position) -> Option<i32> {
+        if pos.moves > self.max_moves as i32 {
+            return None;
+        }
+        self.scores.get(&pos.canonical_hash()).map(|&score| score as i32)
+    }
+
+    /// Write this book to `writer`, the format is:
+    /// * the magic bytes `C4BK` followed by a version byte,
+    /// * the maximum number of moves as a little-endian `u32`,
+    /// * the number of entries as a little-endian `u64`,
+    /// * for each entry, sorted by key: the key as a little-endian `u64` and the score as a signed byte.
+    pub fn write(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.max_moves.to_le_bytes())?;
+        writer.write_all(&(self.scores.len() as u64).to_le_bytes())?;
+
+        let mut scores: Vec<_> = self.scores.iter().collect();
+        scores.sort_unstable_by_key(|&(&key, _)| key);
+
+        for (&key, &score) in scores {
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&score.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a book previously written with [OpeningBook::write].
+    pub fn read(mut reader: impl Read) -> std::io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Not an opening book file or unsupported version",
+            ));
+        }
+
+        let mut max_moves = [0; 4];
+        reader.read_exact(&mut max_moves)?;
+        let max_moves = u32::from_le_bytes(max_moves);
+
+        let mut len = [0; 8];
+        reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+
+        let mut scores = HashMap::new();
+        for _ in 0..len {
+            let mut bytes = [0; 9];
+            reader.read_exact(&mut bytes)?;
+
+            let key = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+            let score = i8::from_le_bytes([bytes[8]]);
+            scores.insert(key, score);
+        }
+
+        Ok(OpeningBook { max_moves, scores })
+    }
+}
+
+/// The [canonical](Position::canonical_hash) keys and scores of [OpeningBook::start].
+const START_BOOK: [(u64, i8); 30] = [
+    (0x0001010101010101, 1),
+    (0x0001010101010102, 2),
+    (0x0001010101010105, 1),
+    (0x0001010101010201, 1),
+    (0x0001010101010203, -2),
+    (0x0001010101010302, 2),
+    (0x0001010101010501, 0),
+    (0x0001010101020101, 0),
+    (0x0001010101020103, -1),
+    (0x0001010101020301, -1),
+    (0x0001010101030102, 2),
+    (0x0001010101030201, 2),
+    (0x0001010101050101, 0),
+    (0x0001010102010101, -1),
+    (0x0001010102010103, -2),
+    (0x0001010102010301, 0),
+    (0x0001010102030101, 0),
+    (0x0001010103010102, 4),
+    (0x0001010103010201, 2),
+    (0x0001010103020101, 2),
+    (0x0001010105010101, 1),
+    (0x0001010201010103, 1),
+    (0x0001010201010301, 2),
+    (0x0001010201030101, 0),
+    (0x0001010301010102, 3),
+    (0x0001010301010201, 0),
+    (0x0001020101010103, -1),
+    (0x0001020101010301, 2),
+    (0x0001030101010102, 3),
+    (0x0002010101010103, 2),
+];
+
+/// Add `pos` and all positions reachable from it to `scores`, children first so they can fill the tt.
+fn generate_book(solver: &mut Connect4Solver, pos: &Position, max_moves: i32, scores: &mut HashMap<u64, i8>) {
+    if pos.moves > max_moves || scores.contains_key(&pos.canonical_hash()) {
+        return;
+    }
+
+    let winning = winning_tiles(pos.tiles_next, pos.tiles_occupied);
+    let mut possible = pos.possible();
+    while possible != 0 {
+        let mv = possible & possible.wrapping_neg();
+        possible &= possible - 1;
+
+        // skip done children
+        if winning & mv == 0 && pos.moves + 1 < SIZE {
+            let mut child = *pos;
+            child.play(mv);
+            generate_book(solver, &child, max_moves, scores);
+        }
+    }
+
+    let score = solver.solve_position(pos, false);
+    scores.insert(pos.canonical_hash(), score as i8);
+}
+
+impl Debug for OpeningBook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "OpeningBook {{ max_moves: {}, len: {} }}",
+            self.max_moves,
+            self.scores.len()
+        )
+    }
+}
+
+/// Bot that plays perfectly using a [Connect4Solver], see [Connect4Solver::best_move].
+#[derive(Debug)]
+pub struct Connect4SolverBot {
+    solver: Connect4Solver,
+}
+
+impl Connect4SolverBot {
+    pub fn new(solver: Connect4Solver) -> Self {
+        Connect4SolverBot { solver }
+    }
+
+    pub fn solver(&self) -> &Connect4Solver {
+        &self.solver
+    }
+}
+
+impl Bot<Connect4> for Connect4SolverBot {
+    fn select_move(&mut self, board: &Connect4) -> Result<u8, BoardDone> {
+        self.solver.best_move(board)
+    }
+}
@@ -8,9 +8,10 @@ use nohash_hasher::IntSet;
 use rand::Rng;
 
 use crate::board::{
-    AllMovesIterator, AvailableMovesIterator, Board, BoardDone, BoardMoves, Outcome, PlayError, Player,
+    play_scoped_undo, AllMovesIterator, AvailableMovesIterator, Board, BoardDone, BoardMoves, Outcome, PlayError,
+    Player, UndoBoard, ZobristBoard,
 };
-use crate::games::go::chains::Chains;
+use crate::games::go::chains::{Chains, ChainsUndo};
 use crate::games::go::tile::Tile;
 use crate::games::go::{PlacementKind, Rules, Territory, TileOccupied, Zobrist, GO_MAX_SIZE};
 use crate::impl_unit_symmetry_board;
@@ -24,6 +25,8 @@ pub struct GoBoard {
     next_player: Player,
     state: State,
     history: IntSet<Zobrist>,
+    /// Running value of [Zobrist::for_history] for `history`.
+    history_zobrist: Zobrist,
     komi: Komi,
 }
 
@@ -33,6 +36,17 @@ pub enum Move {
     Place(Tile),
 }
 
+/// The information needed to undo a move on a [GoBoard], see [UndoBoard].
+///
+/// Only the parts of the chains touched by the placement are saved, see [ChainsUndo].
+/// The history on the other hand can be large and only ever grows by a single value, so it's not copied.
+#[derive(Debug, Clone)]
+pub struct GoUndo {
+    state: State,
+    chains: Option<ChainsUndo>,
+    inserted_history: Option<Zobrist>,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Score {
     pub a: u32,
@@ -73,6 +87,7 @@ impl GoBoard {
             next_player: Player::A,
             state: State::Normal,
             history: Default::default(),
+            history_zobrist: Zobrist::default(),
             komi,
         }
     }
@@ -85,12 +100,14 @@ impl GoBoard {
         history: IntSet<Zobrist>,
         komi: Komi,
     ) -> GoBoard {
+        let history_zobrist = Zobrist::for_history(&history);
         GoBoard {
             rules,
             chains,
             next_player,
             state,
             history,
+            history_zobrist,
             komi,
         }
     }
@@ -125,11 +142,13 @@ impl GoBoard {
 
     // TODO optimize perft by messing around with history
     pub fn replace_history(&mut self, history: IntSet<Zobrist>) -> IntSet<Zobrist> {
+        self.history_zobrist = Zobrist::for_history(&history);
         std::mem::replace(&mut self.history, history)
     }
 
     pub fn clear_history(&mut self) {
         self.history.clear();
+        self.history_zobrist = Zobrist::default();
     }
 
     pub fn clone_without_history(&self) -> Self {
@@ -200,6 +219,72 @@ impl GoBoard {
         Ok(mv)
     }
 
+    /// Shared implementation of [Board::play] and [UndoBoard::play_undoable].
+    /// The chains are only saved if `undoable` is true, the rest of the undo information is always cheap.
+    fn play_impl(&mut self, mv: Move, undoable: bool) -> Result<GoUndo, PlayError> {
+        // usually we'd check if the move is available too, but here we do that later
+        self.check_done()?;
+
+        let curr = self.next_player;
+        let other = curr.other();
+        let mut undo = GoUndo {
+            state: self.state,
+            chains: None,
+            inserted_history: None,
+        };
+
+        match mv {
+            Move::Pass => {
+                // pass is always available
+                // pass doesn't create history values or care about them
+
+                // auxiliary state update
+                self.next_player = other;
+                self.state = match self.state {
+                    State::Normal => State::Passed,
+                    State::Passed => State::Done(self.current_score().to_outcome(self.komi)),
+                    State::Done(_) => unreachable!(),
+                };
+            }
+            Move::Place(tile) => {
+                let prev_zobrist = self.chains.zobrist();
+
+                // place the tile if the corresponding move is actually available, return error otherwise
+                {
+                    let tile = tile.to_flat(self.size());
+                    let rules = &self.rules;
+                    let history = &self.history;
+                    let has_had_same_color = self.chains.has_had_stone_at(tile, curr) | true;
+                    let sim = match self.chains.simulate_place_stone(tile, curr) {
+                        Ok(sim) => sim,
+                        Err(TileOccupied) => return Err(PlayError::UnavailableMove),
+                    };
+                    if !is_available_move_sim(rules, history, sim.kind, has_had_same_color, sim.next_zobrist) {
+                        return Err(PlayError::UnavailableMove);
+                    }
+                    if undoable {
+                        undo.chains = Some(self.chains.apply_simulated_placement_undoable(&sim));
+                    } else {
+                        self.chains.apply_simulated_placement(&sim);
+                    }
+                }
+
+                // update history
+                //   the history is a set, so the value might have already been present
+                if self.rules.needs_history() && self.history.insert(prev_zobrist) {
+                    self.history_zobrist ^= Zobrist::for_history_entry(prev_zobrist);
+                    undo.inserted_history = Some(prev_zobrist);
+                }
+
+                // update auxiliary state
+                self.next_player = other;
+                self.state = State::Normal;
+            }
+        }
+
+        Ok(undo)
+    }
+
     pub fn assert_valid(&self) {
         // TODO can we add more asserts?
         self.chains().assert_valid();
@@ -207,6 +292,7 @@ impl GoBoard {
         if !self.rules().needs_history() {
             assert!(self.history.is_empty())
         }
+        assert_eq!(self.history_zobrist, Zobrist::for_history(&self.history));
     }
 }
 
@@ -280,56 +366,11 @@ impl Board for GoBoard {
     }
 
     fn play(&mut self, mv: Self::Move) -> Result<(), PlayError> {
-        // usually we'd check if the move is available too, but here we do that later
-        self.check_done()?;
-
-        let curr = self.next_player;
-        let other = curr.other();
-
-        match mv {
-            Move::Pass => {
-                // pass is always available
-                // pass doesn't create history values or care about them
-
-                // auxiliary state update
-                self.next_player = other;
-                self.state = match self.state {
-                    State::Normal => State::Passed,
-                    State::Passed => State::Done(self.current_score().to_outcome(self.komi)),
-                    State::Done(_) => unreachable!(),
-                };
-            }
-            Move::Place(tile) => {
-                let prev_zobrist = self.chains.zobrist();
-
-                // place the tile if the corresponding move is actually available, return error otherwise
-                {
-                    let tile = tile.to_flat(self.size());
-                    let rules = &self.rules;
-                    let history = &self.history;
-                    let has_had_same_color = self.chains.has_had_stone_at(tile, curr) | true;
-                    let place_result = self.chains.place_stone_if(tile, curr, |sim| {
-                        is_available_move_sim(rules, history, sim.kind, has_had_same_color, sim.next_zobrist)
-                    });
-                    match place_result {
-                        Ok((_, true)) => {}
-                        Ok((_, false)) => return Err(PlayError::UnavailableMove),
-                        Err(TileOccupied) => return Err(PlayError::UnavailableMove),
-                    }
-                }
-
-                // update history
-                if self.rules.needs_history() {
-                    self.history.insert(prev_zobrist);
-                }
-
-                // update auxiliary state
-                self.next_player = other;
-                self.state = State::Normal;
-            }
-        }
+        self.play_impl(mv, false).map(|_| ())
+    }
 
-        Ok(())
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
     }
 
     fn outcome(&self) -> Option<Outcome> {
@@ -342,6 +383,37 @@ impl Board for GoBoard {
     fn can_lose_after_move() -> bool {
         true
     }
+
+    fn try_for_each_pseudo_legal_move<R>(&self, mut f: impl FnMut(Self::Move) -> ControlFlow<R>) -> ControlFlow<R> {
+        // any empty tile is pseudo-legal, the suicide and super-ko checks are left to `play`,
+        //   which already simulates the placement only once
+        f(Move::Pass)?;
+        for tile in self.empty_tiles() {
+            f(Move::Place(tile))?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl UndoBoard for GoBoard {
+    type Undo = GoUndo;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        self.play_impl(mv, true)
+    }
+
+    fn undo(&mut self, undo: Self::Undo) {
+        if let Some(chains) = undo.chains {
+            self.chains.undo_placement(chains);
+        }
+        if let Some(zobrist) = undo.inserted_history {
+            let removed = self.history.remove(&zobrist);
+            debug_assert!(removed);
+            self.history_zobrist ^= Zobrist::for_history_entry(zobrist);
+        }
+        self.state = undo.state;
+        self.next_player = self.next_player.other();
+    }
 }
 
 impl<'a> BoardMoves<'a, GoBoard> for GoBoard {
@@ -429,6 +501,16 @@ impl InternalIterator for AvailableMovesIterator<'_, GoBoard> {
 }
 
 // TODO implement D4Symmetry again, but how to handle history hashes?
+impl ZobristBoard for GoBoard {
+    type Key = Zobrist;
+
+    /// Unlike [GoBoard::zobrist] this also includes the history, which affects the available moves through super-ko.
+    /// The history part is kept up to date while playing, so this takes constant time.
+    fn zobrist(&self) -> Zobrist {
+        GoBoard::zobrist(self) ^ self.history_zobrist
+    }
+}
+
 impl_unit_symmetry_board!(GoBoard);
 
 impl Hash for GoBoard {
@@ -111,6 +111,22 @@ pub enum PlacementKind {
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct TileOccupied;
 
+/// The information needed to undo a placement, see [Chains::apply_simulated_placement_undoable].
+///
+/// Only the tiles and groups that the placement can touch are saved,
+/// which for the common case without captures or merges is just a handful of entries.
+#[derive(Debug, Clone)]
+pub struct ChainsUndo {
+    tiles: Vec<(u16, TileContent)>,
+    groups: Vec<(u16, Group)>,
+    group_count: usize,
+
+    stones_a: u16,
+    empty_list: LinkHead,
+    dead_groups: LinkHead,
+    zobrist: Zobrist,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Territory {
     Stone(Player),
@@ -626,6 +642,129 @@ impl Chains {
         debug_assert_eq!(self.stone_count(), next_stone_count);
     }
 
+    /// Same as [Self::apply_simulated_placement], but also returns the information needed to undo it again.
+    pub fn apply_simulated_placement_undoable(&mut self, simulated: &SimulatedPlacement) -> ChainsUndo {
+        let undo = self.save_for_placement(simulated);
+        self.apply_simulated_placement(simulated);
+        undo
+    }
+
+    /// Undo a placement previously applied with [Self::apply_simulated_placement_undoable].
+    /// Placements must be undone in the reverse order they were applied in.
+    pub fn undo_placement(&mut self, undo: ChainsUndo) {
+        let ChainsUndo {
+            tiles,
+            groups,
+            group_count,
+            stones_a,
+            empty_list,
+            dead_groups,
+            zobrist,
+        } = undo;
+
+        // everything was saved before any modification, so the restore order doesn't matter
+        self.groups.truncate(group_count);
+        for (index, group) in groups {
+            self.groups[index as usize] = group;
+        }
+        for (index, content) in tiles {
+            self.tiles[index as usize] = content;
+        }
+
+        self.stones_a = stones_a;
+        self.empty_list = empty_list;
+        self.dead_groups = dead_groups;
+        self.zobrist = zobrist;
+    }
+
+    /// Save every tile and group that [Self::apply_simulated_placement] can modify for the given placement.
+    /// This may include some duplicates or entries that don't end up changing.
+    fn save_for_placement(&self, simulated: &SimulatedPlacement) -> ChainsUndo {
+        let size = self.size();
+        let mut tiles = vec![];
+        let mut groups = vec![];
+
+        let save_tile = |tiles: &mut Vec<(u16, TileContent)>, index: OptionU16| {
+            if let Some(index) = index.to_option() {
+                tiles.push((index, self.tiles[index as usize].clone()));
+            }
+        };
+        let save_group = |groups: &mut Vec<(u16, Group)>, index: OptionU16| {
+            if let Some(index) = index.to_option() {
+                groups.push((index, self.groups[index as usize].clone()));
+            }
+        };
+        let save_adjacent_groups = |groups: &mut Vec<(u16, Group)>, tile: FlatTile| {
+            for adj in tile.all_adjacent(size) {
+                save_group(groups, self.tiles[adj.index() as usize].group_id);
+            }
+        };
+        // stones are moved to the empty list and liberties of surrounding groups change
+        let save_cleared_group = |tiles: &mut Vec<(u16, TileContent)>, groups: &mut Vec<(u16, Group)>, id: u16| {
+            save_group(groups, OptionU16::Some(id));
+            for stone in self.groups[id as usize].stones.iter(&self.tiles) {
+                save_tile(tiles, OptionU16::Some(stone));
+                save_adjacent_groups(groups, FlatTile::new(stone));
+            }
+        };
+        // groups can be freed and allocated, which touches the first two entries of the dead list
+        let save_dead_list = |groups: &mut Vec<(u16, Group)>| {
+            if let Some(first) = self.dead_groups.first.to_option() {
+                save_group(groups, OptionU16::Some(first));
+                save_group(groups, self.groups[first as usize].dead_link.next);
+            }
+        };
+
+        let tile_index = simulated.tile.index();
+        let merge_friendly = &simulated.merge_friendly;
+        let clear_enemy = &simulated.clear_enemy;
+
+        match simulated.kind {
+            PlacementKind::Normal | PlacementKind::Capture => {
+                // the tile itself and its neighbors in the empty list
+                let content = &self.tiles[tile_index as usize];
+                save_tile(&mut tiles, OptionU16::Some(tile_index));
+                save_tile(&mut tiles, content.link.prev);
+                save_tile(&mut tiles, content.link.next);
+                save_tile(&mut tiles, self.empty_list.first);
+
+                // all adjacent groups, including the friendly groups that will be merged
+                save_adjacent_groups(&mut groups, simulated.tile);
+                save_dead_list(&mut groups);
+
+                if merge_friendly.len() <= 1 && clear_enemy.is_empty() {
+                    // fast case: the stone is only linked in front of the existing group
+                    if let Some(group_id) = merge_friendly.first() {
+                        save_tile(&mut tiles, self.groups[group_id as usize].stones.first);
+                    }
+                } else {
+                    merge_friendly.for_each(|group_id| {
+                        for stone in self.groups[group_id as usize].stones.iter(&self.tiles) {
+                            save_tile(&mut tiles, OptionU16::Some(stone));
+                        }
+                    });
+                    clear_enemy.for_each(|group_id| save_cleared_group(&mut tiles, &mut groups, group_id));
+                }
+            }
+            PlacementKind::SuicideSingle => {}
+            PlacementKind::SuicideMulti => {
+                save_tile(&mut tiles, self.empty_list.first);
+                save_dead_list(&mut groups);
+                merge_friendly.for_each(|group_id| save_cleared_group(&mut tiles, &mut groups, group_id));
+            }
+        }
+
+        ChainsUndo {
+            tiles,
+            groups,
+            group_count: self.groups.len(),
+            stones_a: self.stones_a,
+            empty_list: self.empty_list.clone(),
+            dead_groups: self.dead_groups.clone(),
+            zobrist: self.zobrist,
+        }
+    }
+
     // TODO merge into largest existing merged group so we can skip changing those tiles?
     fn build_merged_group(
         &mut self,
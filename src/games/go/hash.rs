@@ -8,6 +8,7 @@ use rand::Rng;
 use crate::board::Player;
 use crate::games::go::{FlatTile, State, GO_MAX_AREA};
 use crate::util::tiny::consistent_rng;
+use crate::util::zobrist::{mix64, ZobristKey};
 
 type Inner = u128;
 
@@ -29,6 +30,24 @@ impl Zobrist {
         HASH_DATA.color_turn[color.index() as usize]
     }
 
+    /// The key of a set of previous positions, stable under reordering.
+    /// The entries are remixed first, so they don't cancel against the key of the current position.
+    pub fn for_history<'a>(history: impl IntoIterator<Item = &'a Zobrist>) -> Zobrist {
+        let mut result = Zobrist::default();
+        for &entry in history {
+            result ^= Zobrist::for_history_entry(entry);
+        }
+        result
+    }
+
+    /// The contribution of a single entry to [Zobrist::for_history],
+    /// can be used to add or remove an entry from a combined history key.
+    pub fn for_history_entry(entry: Zobrist) -> Zobrist {
+        let high = (entry.0 >> 64) as u64;
+        let low = entry.0 as u64;
+        Zobrist(((mix64(high ^ low) as Inner) << 64) | mix64(low) as Inner)
+    }
+
     pub fn for_pass_state(state: State) -> Zobrist {
         // don't include outcome, that is implicit from the other tiles anyway
         let state_index = match state {
@@ -109,6 +128,12 @@ impl std::ops::BitXorAssign for Zobrist {
     }
 }
 
+impl ZobristKey for Zobrist {
+    fn to_u64(self) -> u64 {
+        self.0.to_u64()
+    }
+}
+
 impl nohash_hasher::IsEnabled for Zobrist {}
 
 impl Hash for Zobrist {
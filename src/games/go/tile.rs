@@ -120,7 +120,7 @@ impl FlatTile {
             Direction::Up => self.index.checked_add(size as u16)?,
             Direction::Down => self.index.checked_sub(size as u16)?,
             Direction::Left => {
-                if self.index % size as u16 == 0 {
+                if self.index.is_multiple_of(size as u16) {
                     return None;
                 }
                 self.index.checked_sub(1)?
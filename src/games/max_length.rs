@@ -1,4 +1,5 @@
 use std::fmt::{Display, Formatter};
+use std::ops::ControlFlow;
 
 use rand::Rng;
 
@@ -57,6 +58,13 @@ impl<B: Board> Board for MaxMovesBoard<B> {
         Ok(())
     }
 
+    fn try_play_pseudo(&mut self, mv: Self::Move) -> Result<(), PlayError> {
+        self.check_done()?;
+        self.inner.try_play_pseudo(mv)?;
+        self.moves += 1;
+        Ok(())
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         if self.moves == self.max_moves {
             Some(Outcome::Draw)
@@ -65,9 +73,21 @@ impl<B: Board> Board for MaxMovesBoard<B> {
         }
     }
 
+    fn is_chance_node(&self) -> bool {
+        self.inner.is_chance_node()
+    }
+
+    fn chance_probability(&self, mv: Self::Move) -> f32 {
+        self.inner.chance_probability(mv)
+    }
+
     fn can_lose_after_move() -> bool {
         B::can_lose_after_move()
     }
+
+    fn try_for_each_pseudo_legal_move<R>(&self, f: impl FnMut(Self::Move) -> ControlFlow<R>) -> ControlFlow<R> {
+        self.inner.try_for_each_pseudo_legal_move(f)
+    }
 }
 
 impl<B: Board> BoardSymmetry<MaxMovesBoard<B>> for MaxMovesBoard<B> {
@@ -8,8 +8,12 @@ pub mod chess;
 pub mod connect4;
 #[cfg(feature = "game_go")]
 pub mod go;
+#[cfg(feature = "game_multi_ttt")]
+pub mod multi_ttt;
 #[cfg(feature = "game_oware")]
 pub mod oware;
+#[cfg(feature = "game_pig")]
+pub mod pig;
 #[cfg(feature = "game_sttt")]
 pub mod sttt;
 #[cfg(feature = "game_ttt")]
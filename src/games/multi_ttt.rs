@@ -0,0 +1,158 @@
+//! Tic-tac-toe generalized to any number of players, board size and line length.
+//!
+//! Players take turns placing a stone on an empty tile,
+//! the first player to get `line` stones in a row (horizontally, vertically or diagonally) wins.
+//! If the board is full without any line the game is a draw between all players.
+use std::fmt::{Display, Formatter};
+
+use crate::board::{BoardDone, PlayError};
+use crate::multi::board::{NBoard, NOutcome, PlayerIndex};
+use crate::util::coord::Coord8;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MultiTTTBoard {
+    size: u8,
+    player_count: u8,
+    line: u8,
+    tiles: Vec<Option<PlayerIndex>>,
+    next_player: PlayerIndex,
+    winner: Option<PlayerIndex>,
+    full: bool,
+}
+
+impl Default for MultiTTTBoard {
+    /// Three players on a 6x6 board, four in a row wins.
+    fn default() -> Self {
+        MultiTTTBoard::new(6, 3, 4)
+    }
+}
+
+impl MultiTTTBoard {
+    pub const MAX_SIZE: u8 = 8;
+
+    pub fn new(size: u8, player_count: u8, line: u8) -> Self {
+        assert!(0 < size && size <= Self::MAX_SIZE, "invalid size {}", size);
+        assert!(player_count > 0, "there must be at least one player");
+        assert!(
+            0 < line && line <= size,
+            "invalid line length {} for size {}",
+            line,
+            size
+        );
+
+        MultiTTTBoard {
+            size,
+            player_count,
+            line,
+            tiles: vec![None; size as usize * size as usize],
+            next_player: PlayerIndex::new(0),
+            winner: None,
+            full: false,
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn line(&self) -> u8 {
+        self.line
+    }
+
+    pub fn tile(&self, coord: Coord8) -> Option<PlayerIndex> {
+        assert!(coord.valid_for_size(self.size));
+        self.tiles[coord.dense_index(self.size)]
+    }
+
+    /// Count the number of consecutive stones of `player` starting next to `coord` in the direction `(dx, dy)`.
+    fn count_direction(&self, coord: Coord8, player: PlayerIndex, dx: i8, dy: i8) -> u8 {
+        let mut count = 0;
+        let mut x = coord.x() as i8;
+        let mut y = coord.y() as i8;
+
+        loop {
+            x += dx;
+            y += dy;
+            if x < 0 || y < 0 || x >= self.size as i8 || y >= self.size as i8 {
+                return count;
+            }
+            if self.tile(Coord8::from_xy(x as u8, y as u8)) != Some(player) {
+                return count;
+            }
+            count += 1;
+        }
+    }
+}
+
+impl NBoard for MultiTTTBoard {
+    type Move = Coord8;
+
+    fn player_count(&self) -> u8 {
+        self.player_count
+    }
+
+    fn next_player(&self) -> PlayerIndex {
+        self.next_player
+    }
+
+    fn is_available_move(&self, mv: Self::Move) -> Result<bool, BoardDone> {
+        self.check_done()?;
+        Ok(mv.valid_for_size(self.size) && self.tile(mv).is_none())
+    }
+
+    fn available_moves(&self) -> Result<Vec<Self::Move>, BoardDone> {
+        self.check_done()?;
+        let moves = Coord8::all()
+            .filter(|&c| c.valid_for_size(self.size) && self.tile(c).is_none())
+            .collect();
+        Ok(moves)
+    }
+
+    fn play(&mut self, mv: Self::Move) -> Result<(), PlayError> {
+        self.check_can_play(mv)?;
+
+        let player = self.next_player;
+        self.tiles[mv.dense_index(self.size)] = Some(player);
+
+        let won = [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|&(dx, dy)| {
+            let count = 1 + self.count_direction(mv, player, dx, dy) + self.count_direction(mv, player, -dx, -dy);
+            count >= self.line
+        });
+
+        if won {
+            self.winner = Some(player);
+        }
+        self.full = self.tiles.iter().all(|t| t.is_some());
+        self.next_player = player.next(self.player_count);
+
+        Ok(())
+    }
+
+    fn outcome(&self) -> Option<NOutcome> {
+        if let Some(winner) = self.winner {
+            Some(NOutcome::won_by(winner, self.player_count))
+        } else if self.full {
+            Some(NOutcome::draw(self.player_count))
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for MultiTTTBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                match self.tile(Coord8::from_xy(x, y)) {
+                    None => write!(f, ".")?,
+                    Some(p) => write!(f, "{}", p.index())?,
+                }
+            }
+            if y == 0 {
+                write!(f, "    next: {}", self.next_player)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
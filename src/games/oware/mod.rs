@@ -20,8 +20,14 @@ use std::ops::Range;
 
 use itertools::join;
 
-use crate::board::{Alternating, Board, BoardDone, BoardMoves, BruteforceMoveIterator, Outcome, PlayError, Player};
+use crate::board::{
+    play_scoped_undo, Alternating, Board, BoardDone, BoardMoves, BruteforceMoveIterator, Outcome, PlayError, Player,
+    UndoBoard, ZobristBoard,
+};
 use crate::util::iter::ClonableInternal;
+use crate::util::zobrist::zobrist_value;
+
+pub mod solver;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct OwareBoard<const PITS_PER_PLAYER: usize> {
@@ -30,8 +36,22 @@ pub struct OwareBoard<const PITS_PER_PLAYER: usize> {
     next_player: Player,
     outcome: Option<Outcome>,
     init_seeds: u8,
+    zobrist: u64,
 }
 
+/// The information needed to undo a move on an [OwareBoard], see [UndoBoard].
+#[derive(Debug, Copy, Clone)]
+pub struct OwareUndo<const PITS_PER_PLAYER: usize> {
+    pits: [[u8; PITS_PER_PLAYER]; 2],
+    scores: [u8; 2],
+    zobrist: u64,
+}
+
+// the number of pits is a generic parameter, so we can't use a fixed size table
+const ZOBRIST_PIT_SEED: u64 = 0x0a0a;
+const ZOBRIST_SCORE_SEED: u64 = 0x0a0b;
+const ZOBRIST_TURN: u64 = zobrist_value(0x0a0c, 0);
+
 impl<const P: usize> Default for OwareBoard<P> {
     fn default() -> Self {
         Self::new(4)
@@ -40,13 +60,21 @@ impl<const P: usize> Default for OwareBoard<P> {
 
 impl<const PITS: usize> OwareBoard<PITS> {
     pub fn new(init_seeds: u8) -> Self {
-        Self {
+        let mut result = Self {
             pits: [[init_seeds; PITS]; 2],
             init_seeds,
             scores: Default::default(),
             next_player: Player::A,
             outcome: None,
+            zobrist: 0,
+        };
+        for side in 0..2 {
+            for idx in 0..PITS {
+                result.zobrist ^= zobrist_pit::<PITS>(side, idx, init_seeds);
+            }
+            result.zobrist ^= zobrist_score(side, 0);
         }
+        result
     }
 
     pub fn score(&self, player: Player) -> u8 {
@@ -89,11 +117,24 @@ impl<const PITS: usize> OwareBoard<PITS> {
     }
 
     fn capture(&mut self, idx: usize) -> u8 {
-        let seeds = self.pits[usize::from(idx >= PITS) ^ self.next_player.index() as usize][idx % PITS];
-        self.pits[usize::from(idx >= PITS) ^ self.next_player.index() as usize][idx % PITS] = 0;
+        let side = usize::from(idx >= PITS) ^ self.next_player.index() as usize;
+        let seeds = self.pits[side][idx % PITS];
+        self.set_pit(side, idx % PITS, 0);
         seeds
     }
 
+    fn set_pit(&mut self, side: usize, idx: usize, seeds: u8) {
+        let pit = &mut self.pits[side][idx];
+        self.zobrist ^= zobrist_pit::<PITS>(side, idx, *pit) ^ zobrist_pit::<PITS>(side, idx, seeds);
+        *pit = seeds;
+    }
+
+    fn add_score(&mut self, player: usize, seeds: u8) {
+        let score = &mut self.scores[player];
+        self.zobrist ^= zobrist_score(player, *score) ^ zobrist_score(player, *score + seeds);
+        *score += seeds;
+    }
+
     fn can_overflow(&self, mv: usize) -> bool {
         mv % PITS + self.at(mv) as usize >= PITS
     }
@@ -142,13 +183,15 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
         while seeds > 0 {
             idx = (idx + usize::from((idx + 1) % (PITS * 2) == mv) + 1) % (PITS * 2);
             seeds -= 1;
-            self.pits[usize::from(idx >= PITS) ^ player][idx % PITS] += 1;
+            let side = usize::from(idx >= PITS) ^ player;
+            self.set_pit(side, idx % PITS, self.pits[side][idx % PITS] + 1);
         }
 
         // capture
         if !self.grand_slam(idx) {
             while idx >= PITS && matches!(self.at(idx), 2 | 3) {
-                self.scores[player] += self.capture(idx);
+                let seeds = self.capture(idx);
+                self.add_score(player, seeds);
                 idx = (idx + (PITS * 2) - 1) % (PITS * 2);
             }
         }
@@ -156,14 +199,15 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
         // No move endgame
         if self.pl_pits().all(|x| self.at(x) == 0) && !self.opp_pits().any(|x| self.can_overflow(x)) {
             self.opp_pits().for_each(|x| {
-                self.scores[(player + 1) % 2] += self.capture(x);
+                let seeds = self.capture(x);
+                self.add_score((player + 1) % 2, seeds);
             })
         }
 
         // Stalemate endgame
         if self.is_stalemate() {
             (0..PITS * 2).for_each(|x| _ = self.capture(x));
-            self.scores.iter_mut().for_each(|x| *x += 1);
+            (0..2).for_each(|p| self.add_score(p, 1));
         }
 
         assert!(
@@ -183,10 +227,15 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
             });
 
         self.next_player = self.next_player.other();
+        self.zobrist ^= ZOBRIST_TURN;
 
         Ok(())
     }
 
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.outcome
     }
@@ -196,6 +245,45 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
     }
 }
 
+impl<const PITS: usize> UndoBoard for OwareBoard<PITS> {
+    type Undo = OwareUndo<PITS>;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        // sowing and capturing can touch every pit, so just store all of them
+        let undo = OwareUndo {
+            pits: self.pits,
+            scores: self.scores,
+            zobrist: self.zobrist,
+        };
+        self.play(mv)?;
+        Ok(undo)
+    }
+
+    fn undo(&mut self, undo: Self::Undo) {
+        self.pits = undo.pits;
+        self.scores = undo.scores;
+        self.next_player = self.next_player.other();
+        self.outcome = None;
+        self.zobrist = undo.zobrist;
+    }
+}
+
+impl<const PITS: usize> ZobristBoard for OwareBoard<PITS> {
+    type Key = u64;
+
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
+fn zobrist_pit<const PITS: usize>(side: usize, idx: usize, seeds: u8) -> u64 {
+    zobrist_value(ZOBRIST_PIT_SEED, ((side * PITS + idx) * 256 + seeds as usize) as u64)
+}
+
+fn zobrist_score(player: usize, score: u8) -> u64 {
+    zobrist_value(ZOBRIST_SCORE_SEED, (player * 256 + score as usize) as u64)
+}
+
 impl<const PITS: usize> Alternating for OwareBoard<PITS> {}
 
 impl<const PITS: usize> crate::board::BoardSymmetry<OwareBoard<PITS>> for OwareBoard<PITS> {
@@ -0,0 +1,134 @@
+//! Exhaustive solving of small [OwareBoard] variants, see [solve_oware].
+//!
+//! The standard game with 6 pits of 4 seeds is far too large for this, but variants with only a few pits and seeds
+//! are small enough to enumerate every reachable position. The results can be written out as a reference
+//! to test heuristics against, see [OwareSolution::write_csv].
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+
+use itertools::join;
+
+use crate::ai::tablebase::{Tablebase, TablebaseEntry};
+use crate::board::{Board, Player};
+use crate::games::oware::OwareBoard;
+use crate::wdl::OutcomeWDL;
+
+/// A solved position, part of an [OwareSolution].
+#[derive(Debug, Clone)]
+pub struct OwarePosition<const P: usize> {
+    pub board: OwareBoard<P>,
+    /// The value from the POV of the next player.
+    pub entry: TablebaseEntry,
+    /// All moves that achieve `entry`, see [Tablebase::best_moves]. Empty for done boards.
+    pub best_moves: Vec<usize>,
+}
+
+/// The game-theoretic value and the optimal moves of every position reachable from a start position.
+pub struct OwareSolution<const P: usize> {
+    pub start: OwareBoard<P>,
+    /// All positions reachable from `start`, including `start` itself and done positions.
+    pub positions: Vec<OwarePosition<P>>,
+    tablebase: Tablebase<OwareBoard<P>>,
+}
+
+/// Solve all positions reachable from `start`.
+///
+/// Positions that are part of a cycle neither player can force their way out of are considered draws,
+/// see [Tablebase].
+///
+/// **Warning**: The number of positions grows very quickly with the number of pits and seeds,
+/// only small variants like 3 pits of 3 seeds can be solved in a reasonable amount of time.
+pub fn solve_oware<const P: usize>(start: &OwareBoard<P>) -> OwareSolution<P> {
+    // oware has no symmetries, so the canonical positions are all of the reachable positions
+    let (tablebase, boards) = Tablebase::generate_with_positions(start);
+
+    let positions = boards
+        .into_iter()
+        .map(|board| {
+            let entry = tablebase.get(&board).unwrap();
+            let best_moves = match board.is_done() {
+                true => vec![],
+                false => tablebase.best_moves(&board).unwrap().unwrap(),
+            };
+            OwarePosition {
+                board,
+                entry,
+                best_moves,
+            }
+        })
+        .collect();
+
+    OwareSolution {
+        start: start.clone(),
+        positions,
+        tablebase,
+    }
+}
+
+impl<const P: usize> OwareSolution<P> {
+    /// The value of `start` from the POV of its next player.
+    pub fn value(&self) -> TablebaseEntry {
+        self.tablebase.get(&self.start).unwrap()
+    }
+
+    pub fn tablebase(&self) -> &Tablebase<OwareBoard<P>> {
+        &self.tablebase
+    }
+
+    /// Turn this solution into its underlying table, for example to use in a
+    /// [TablebaseBot](crate::ai::tablebase::TablebaseBot).
+    pub fn into_tablebase(self) -> Tablebase<OwareBoard<P>> {
+        self.tablebase
+    }
+
+    /// Write all positions to `writer` as comma-separated values, with a header row. The columns are:
+    /// * `a0, a1, ...` and `b0, b1, ...`: the seeds in the pits of both players,
+    /// * `score_a`, `score_b`: the number of seeds captured by both players,
+    /// * `next`: the next player, `A` or `B`,
+    /// * `value`: `win`, `draw` or `loss` from the POV of the next player,
+    /// * `distance`: see [TablebaseEntry::distance],
+    /// * `best_moves`: the optimal moves separated by spaces, empty for done positions.
+    pub fn write_csv(&self, mut writer: impl Write) -> std::io::Result<()> {
+        let pit_names = (0..P)
+            .map(|i| format!("a{}", i))
+            .chain((0..P).map(|i| format!("b{}", i)));
+        writeln!(
+            writer,
+            "{},score_a,score_b,next,value,distance,best_moves",
+            join(pit_names, ",")
+        )?;
+
+        for position in &self.positions {
+            let board = &position.board;
+            let value = match position.entry.value {
+                OutcomeWDL::Win => "win",
+                OutcomeWDL::Draw => "draw",
+                OutcomeWDL::Loss => "loss",
+            };
+
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                join(board.pits().iter().flatten(), ","),
+                board.score(Player::A),
+                board.score(Player::B),
+                board.next_player().to_char(),
+                value,
+                position.entry.distance,
+                join(&position.best_moves, " "),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const P: usize> Debug for OwareSolution<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwareSolution")
+            .field("start", &self.start)
+            .field("positions", &self.positions.len())
+            .field("value", &self.value())
+            .finish()
+    }
+}
@@ -0,0 +1,206 @@
+//! Pig is a simple dice game, included as an example of a game with chance nodes.
+//!
+//! # Rules
+//! 1. On their turn a player repeatedly rolls a die, adding the rolled number to their turn total.
+//! 2. If a `1` is rolled the turn total is lost and the turn passes to the other player.
+//! 3. Once the player has rolled at least once they can choose to hold instead, which adds the turn total to their
+//!    score and passes the turn to the other player.
+//! 4. The first player to reach the target score wins.
+//!
+//! Each roll is represented by two moves: [Move::Roll] made by the player,
+//! followed by the chance node that resolves it with [Move::Die].
+//!
+//! See <https://en.wikipedia.org/wiki/Pig_(dice_game)> for more information.
+use std::fmt::{Display, Formatter};
+
+use rand::Rng;
+
+use crate::board::{Board, BoardDone, BoardMoves, BruteforceMoveIterator, Outcome, PlayError, Player};
+use crate::impl_unit_symmetry_board;
+use crate::util::iter::ClonableInternal;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PigBoard {
+    target: u32,
+    scores: [u32; 2],
+    turn_total: u32,
+    rolling: bool,
+    next_player: Player,
+    outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Move {
+    Roll,
+    Hold,
+    Die(u8),
+}
+
+impl Default for PigBoard {
+    fn default() -> Self {
+        PigBoard::new(100)
+    }
+}
+
+impl PigBoard {
+    pub const DIE_SIDES: u8 = 6;
+
+    pub fn new(target: u32) -> Self {
+        assert!(target > 0, "target must be positive");
+        PigBoard {
+            target,
+            scores: [0; 2],
+            turn_total: 0,
+            rolling: false,
+            next_player: Player::A,
+            outcome: None,
+        }
+    }
+
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    pub fn score(&self, player: Player) -> u32 {
+        self.scores[player.index() as usize]
+    }
+
+    /// The points collected so far during the current turn, not yet added to the score.
+    pub fn turn_total(&self) -> u32 {
+        self.turn_total
+    }
+
+    fn end_turn(&mut self) {
+        self.turn_total = 0;
+        self.next_player = self.next_player.other();
+    }
+}
+
+impl Board for PigBoard {
+    type Move = Move;
+
+    fn next_player(&self) -> Player {
+        self.next_player
+    }
+
+    fn is_available_move(&self, mv: Self::Move) -> Result<bool, BoardDone> {
+        self.check_done()?;
+
+        let result = match mv {
+            Move::Roll => !self.rolling,
+            Move::Hold => !self.rolling && self.turn_total > 0,
+            Move::Die(value) => self.rolling && (1..=Self::DIE_SIDES).contains(&value),
+        };
+        Ok(result)
+    }
+
+    fn random_available_move(&self, rng: &mut impl Rng) -> Result<Self::Move, BoardDone> {
+        self.check_done()?;
+
+        let mv = if self.rolling {
+            Move::Die(rng.gen_range(1..=Self::DIE_SIDES))
+        } else if self.turn_total == 0 || rng.gen() {
+            Move::Roll
+        } else {
+            Move::Hold
+        };
+        Ok(mv)
+    }
+
+    fn play(&mut self, mv: Self::Move) -> Result<(), PlayError> {
+        self.check_can_play(mv)?;
+
+        match mv {
+            Move::Roll => self.rolling = true,
+            Move::Hold => {
+                let score = &mut self.scores[self.next_player.index() as usize];
+                *score += self.turn_total;
+                if *score >= self.target {
+                    self.outcome = Some(Outcome::WonBy(self.next_player));
+                }
+                self.end_turn();
+            }
+            Move::Die(value) => {
+                self.rolling = false;
+                if value == 1 {
+                    self.end_turn();
+                } else {
+                    self.turn_total += value as u32;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    fn is_chance_node(&self) -> bool {
+        self.rolling
+    }
+
+    fn chance_probability(&self, mv: Self::Move) -> f32 {
+        debug_assert!(self.is_available_move(mv) == Ok(true));
+        1.0 / Self::DIE_SIDES as f32
+    }
+
+    fn can_lose_after_move() -> bool {
+        false
+    }
+}
+
+impl_unit_symmetry_board!(PigBoard);
+
+impl<'a> BoardMoves<'a, PigBoard> for PigBoard {
+    type AllMovesIterator = ClonableInternal<std::array::IntoIter<Move, 8>>;
+    type AvailableMovesIterator = BruteforceMoveIterator<'a, PigBoard>;
+
+    fn all_possible_moves() -> Self::AllMovesIterator {
+        let moves = [
+            Move::Roll,
+            Move::Hold,
+            Move::Die(1),
+            Move::Die(2),
+            Move::Die(3),
+            Move::Die(4),
+            Move::Die(5),
+            Move::Die(6),
+        ];
+        ClonableInternal::new(IntoIterator::into_iter(moves))
+    }
+
+    fn available_moves(&'a self) -> Result<Self::AvailableMovesIterator, BoardDone> {
+        BruteforceMoveIterator::new(self)
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Roll => write!(f, "roll"),
+            Move::Hold => write!(f, "hold"),
+            Move::Die(value) => write!(f, "die{}", value),
+        }
+    }
+}
+
+impl Display for PigBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "target: {}", self.target)?;
+        for player in Player::BOTH {
+            let marker = if player == self.next_player { '>' } else { ' ' };
+            writeln!(f, "{} {}: {}", marker, player.to_char(), self.score(player))?;
+        }
+        write!(f, "turn total: {}", self.turn_total)?;
+        if self.rolling {
+            write!(f, ", rolling")?;
+        }
+        writeln!(f)?;
+        if let Some(outcome) = self.outcome {
+            writeln!(f, "outcome: {:?}", outcome)?;
+        }
+        Ok(())
+    }
+}
@@ -7,16 +7,27 @@ use itertools::Itertools;
 use rand::Rng;
 
 use crate::board::{
-    AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardDone, BoardMoves, BoardSymmetry, Outcome,
-    PlayError, Player,
+    play_scoped_undo, AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardDone, BoardMoves,
+    BoardSymmetry, Outcome, PlayError, Player, UndoBoard, ZobristBoard,
 };
 use crate::symmetry::D4Symmetry;
 use crate::util::bits::{get_nth_set_bit, BitIter};
 use crate::util::iter::ClonableInternal;
+use crate::util::zobrist::{zobrist_table, zobrist_value};
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Coord(u8);
 
+/// The information needed to undo a move on a [STTTBoard], see [UndoBoard].
+#[derive(Debug, Copy, Clone)]
+pub struct STTTUndo {
+    mv: Coord,
+    main_grid: u32,
+    last_move: Option<Coord>,
+    macro_mask: u32,
+    macro_open: u32,
+}
+
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct STTTBoard {
     grids: [u32; 9],
@@ -28,8 +39,15 @@ pub struct STTTBoard {
 
     macro_mask: u32,
     macro_open: u32,
+
+    // only includes the tiles, the other fields are cheap to add in when needed
+    zobrist_tiles: u64,
 }
 
+/// Zobrist values for each (player, tile) pair, followed by the values for the next player.
+const ZOBRIST: [u64; 2 * 81 + 2] = zobrist_table(0x5777);
+const ZOBRIST_MACRO_MASK_SEED: u64 = 0x5778;
+
 impl Default for STTTBoard {
     fn default() -> STTTBoard {
         STTTBoard {
@@ -40,6 +58,7 @@ impl Default for STTTBoard {
             outcome: None,
             macro_mask: STTTBoard::FULL_MASK,
             macro_open: STTTBoard::FULL_MASK,
+            zobrist_tiles: 0,
         }
     }
 }
@@ -84,6 +103,7 @@ impl STTTBoard {
         //set tile and macro, check win
         let new_grid = self.grids[om as usize] | (1 << (os + p));
         self.grids[om as usize] = new_grid;
+        self.zobrist_tiles ^= zobrist_tile(player, coord);
 
         let grid_win = is_win_grid((new_grid >> p) & STTTBoard::FULL_MASK);
         if grid_win {
@@ -105,6 +125,12 @@ impl STTTBoard {
         self.macro_mask = self.calc_macro_mask(os);
     }
 
+    fn zobrist_tiles_from_scratch(&self) -> u64 {
+        Coord::all()
+            .filter_map(|coord| self.tile(coord).map(|player| zobrist_tile(player, coord)))
+            .fold(0, |acc, value| acc ^ value)
+    }
+
     fn calc_macro_mask(&self, os: u8) -> u32 {
         if has_bit(self.macro_open, os) {
             1u32 << os
@@ -171,6 +197,10 @@ impl Board for STTTBoard {
         Ok(())
     }
 
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.outcome
     }
@@ -180,6 +210,52 @@ impl Board for STTTBoard {
     }
 }
 
+impl UndoBoard for STTTBoard {
+    type Undo = STTTUndo;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        let undo = STTTUndo {
+            mv,
+            main_grid: self.main_grid,
+            last_move: self.last_move,
+            macro_mask: self.macro_mask,
+            macro_open: self.macro_open,
+        };
+        self.play(mv)?;
+        Ok(undo)
+    }
+
+    fn undo(&mut self, undo: Self::Undo) {
+        let player = self.next_player.other();
+        let bit = 1 << (undo.mv.os() + 9 * player.index());
+        debug_assert!(
+            self.grids[undo.mv.om() as usize] & bit != 0,
+            "can only undo the last move"
+        );
+
+        self.grids[undo.mv.om() as usize] &= !bit;
+        self.zobrist_tiles ^= zobrist_tile(player, undo.mv);
+        self.main_grid = undo.main_grid;
+        self.last_move = undo.last_move;
+        self.next_player = player;
+        self.outcome = None;
+        self.macro_mask = undo.macro_mask;
+        self.macro_open = undo.macro_open;
+    }
+}
+
+impl ZobristBoard for STTTBoard {
+    type Key = u64;
+
+    /// The key includes the tiles, the next player and the macros the next move can be played in.
+    /// The last move itself is not included, it only matters through the latter.
+    fn zobrist(&self) -> u64 {
+        self.zobrist_tiles
+            ^ ZOBRIST[2 * 81 + self.next_player.index() as usize]
+            ^ zobrist_value(ZOBRIST_MACRO_MASK_SEED, self.macro_mask as u64)
+    }
+}
+
 impl Alternating for STTTBoard {}
 
 impl BoardSymmetry<STTTBoard> for STTTBoard {
@@ -192,7 +268,7 @@ impl BoardSymmetry<STTTBoard> for STTTBoard {
             grids[map_oo(sym, oo) as usize] = map_grid(sym, self.grids[oo as usize])
         }
 
-        STTTBoard {
+        let mut result = STTTBoard {
             grids,
             main_grid: map_grid(sym, self.main_grid),
             last_move: self.last_move.map(|c| self.map_move(sym, c)),
@@ -200,7 +276,10 @@ impl BoardSymmetry<STTTBoard> for STTTBoard {
             outcome: self.outcome,
             macro_mask: map_grid(sym, self.macro_mask),
             macro_open: map_grid(sym, self.macro_open),
-        }
+            zobrist_tiles: 0,
+        };
+        result.zobrist_tiles = result.zobrist_tiles_from_scratch();
+        result
     }
 
     fn map_move(&self, sym: D4Symmetry, mv: Coord) -> Coord {
@@ -433,6 +512,10 @@ impl fmt::Display for STTTBoard {
     }
 }
 
+fn zobrist_tile(player: Player, coord: Coord) -> u64 {
+    ZOBRIST[81 * player.index() as usize + coord.o() as usize]
+}
+
 pub fn board_to_compact_string(board: &STTTBoard) -> String {
     Coord::all().map(|coord| symbol_from_tile(board, coord)).join("")
 }
@@ -451,9 +534,7 @@ pub fn board_from_compact_string(s: &str) -> STTTBoard {
             assert!(last_move.is_none(), "Compact string cannot contain multiple last moves");
             let player = player.expect("Last move must have been played by a player");
             last_move = Some((player, coord));
-        }
-
-        if let Some(player) = player {
+        } else if let Some(player) = player {
             board.set_tile_and_update(player, coord);
         }
     }
@@ -1,15 +1,20 @@
 use std::fmt::{Debug, Display, Formatter};
 
-use crate::board::{Alternating, Board, BoardDone, BoardMoves, BruteforceMoveIterator, Outcome, PlayError, Player};
+use crate::board::{
+    play_scoped_undo, Alternating, Board, BoardDone, BoardMoves, BruteforceMoveIterator, Outcome, PlayError, Player,
+    UndoBoard, ZobristBoard,
+};
 use crate::impl_unit_symmetry_board;
 use crate::util::coord::{Coord3, CoordAllIter};
 use crate::util::iter::ClonableInternal;
+use crate::util::zobrist::zobrist_table;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct TTTBoard {
     tiles: [Option<Player>; 9],
     next_player: Player,
     outcome: Option<Outcome>,
+    zobrist: u64,
 }
 
 const LINES: &[[(usize, usize); 3]] = &[
@@ -23,12 +28,17 @@ const LINES: &[[(usize, usize); 3]] = &[
     [(0, 2), (1, 1), (2, 0)],
 ];
 
+/// Zobrist values for each (player, tile) pair, followed by the value toggled after every move.
+const ZOBRIST: [u64; 2 * 9 + 1] = zobrist_table(0x7474);
+const ZOBRIST_TURN: u64 = ZOBRIST[2 * 9];
+
 impl Default for TTTBoard {
     fn default() -> Self {
         TTTBoard {
             tiles: Default::default(),
             next_player: Player::A,
             outcome: None,
+            zobrist: 0,
         }
     }
 }
@@ -37,6 +47,10 @@ impl TTTBoard {
     pub fn tile(&self, coord: Coord3) -> Option<Player> {
         self.tiles[coord.index() as usize]
     }
+
+    fn zobrist_for_move(player: Player, mv: Coord3) -> u64 {
+        ZOBRIST[9 * player.index() as usize + mv.index() as usize] ^ ZOBRIST_TURN
+    }
 }
 
 impl Board for TTTBoard {
@@ -55,6 +69,7 @@ impl Board for TTTBoard {
         self.check_can_play(mv)?;
 
         self.tiles[mv.index() as usize] = Some(self.next_player);
+        self.zobrist ^= Self::zobrist_for_move(self.next_player, mv);
 
         let won = LINES.iter().any(|line| {
             line.iter().all(|&(lx, ly)| {
@@ -76,6 +91,10 @@ impl Board for TTTBoard {
         Ok(())
     }
 
+    fn play_scoped<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> Result<R, PlayError> {
+        play_scoped_undo(self, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.outcome
     }
@@ -85,6 +104,33 @@ impl Board for TTTBoard {
     }
 }
 
+impl UndoBoard for TTTBoard {
+    type Undo = Coord3;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Result<Self::Undo, PlayError> {
+        self.play(mv)?;
+        Ok(mv)
+    }
+
+    fn undo(&mut self, mv: Self::Undo) {
+        let tile = &mut self.tiles[mv.index() as usize];
+        debug_assert_eq!(*tile, Some(self.next_player.other()), "can only undo the last move");
+
+        *tile = None;
+        self.next_player = self.next_player.other();
+        self.outcome = None;
+        self.zobrist ^= Self::zobrist_for_move(self.next_player, mv);
+    }
+}
+
+impl ZobristBoard for TTTBoard {
+    type Key = u64;
+
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
 impl Alternating for TTTBoard {}
 
 impl_unit_symmetry_board!(TTTBoard);
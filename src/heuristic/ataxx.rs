@@ -3,8 +3,9 @@ use std::cmp::{max, Ordering};
 use crate::ai::minimax::Heuristic;
 use crate::ai::solver::SolverHeuristic;
 use crate::board::Board;
-use crate::games::ataxx::AtaxxBoard;
+use crate::games::ataxx::{AtaxxBoard, Move};
 use crate::util::bitboard::BitBoard8;
+use crate::util::coord::Coord8;
 
 #[derive(Debug)]
 pub struct AtaxxTileHeuristic {
@@ -12,6 +13,15 @@ pub struct AtaxxTileHeuristic {
     pub surface_factor: i32,
 }
 
+/// The same values as the inner [AtaxxTileHeuristic], but with a quiescence search over copy moves
+/// that convert at least `conversions` tiles, see [Heuristic::is_noisy].
+/// Jumps are never noisy, since they could go back and forth forever.
+#[derive(Debug)]
+pub struct AtaxxTileQuiescenceHeuristic {
+    pub inner: AtaxxTileHeuristic,
+    pub conversions: u8,
+}
+
 impl AtaxxTileHeuristic {
     pub fn new(tile_factor: i32, surface_factor: i32) -> Self {
         AtaxxTileHeuristic {
@@ -62,4 +72,56 @@ impl Heuristic<AtaxxBoard> for AtaxxTileHeuristic {
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
         (max(old, new), new.cmp(&old))
     }
+
+    /// Prefer moves that convert many opponent tiles, and copies over jumps since they gain an extra tile.
+    fn move_score(&self, board: &AtaxxBoard, mv: Move) -> i32 {
+        let (to, gained) = match mv {
+            Move::Pass => return 0,
+            Move::Copy { to } => (to, 1),
+            Move::Jump { to, .. } => (to, 0),
+        };
+
+        let converted = converted_count(board, to) as i32;
+        2 * converted + gained
+    }
+}
+
+impl AtaxxTileQuiescenceHeuristic {
+    pub fn new(inner: AtaxxTileHeuristic, conversions: u8) -> Self {
+        AtaxxTileQuiescenceHeuristic { inner, conversions }
+    }
+}
+
+impl Heuristic<AtaxxBoard> for AtaxxTileQuiescenceHeuristic {
+    type V = i32;
+
+    fn value(&self, board: &AtaxxBoard, length: u32) -> Self::V {
+        self.inner.value(board, length)
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        AtaxxTileHeuristic::merge(old, new)
+    }
+
+    fn move_score(&self, board: &AtaxxBoard, mv: Move) -> i32 {
+        self.inner.move_score(board, mv)
+    }
+
+    /// Copies always add a tile to the board, so the quiescence search is guaranteed to end.
+    fn is_noisy(&self, board: &AtaxxBoard, mv: Move) -> bool {
+        match mv {
+            Move::Copy { to } => converted_count(board, to) >= self.conversions,
+            _ => false,
+        }
+    }
+
+    fn has_noisy_moves(&self) -> bool {
+        true
+    }
+}
+
+/// The number of opponent tiles that would be converted by moving to `to`.
+fn converted_count(board: &AtaxxBoard, to: Coord8) -> u8 {
+    let (_, other) = board.tiles_pov();
+    (other & BitBoard8::coord(to).adjacent()).count()
 }
@@ -1,6 +1,6 @@
 use std::cmp::{max, Ordering};
 
-use chess::{Piece, ALL_PIECES};
+use chess::{ChessMove, Piece, ALL_PIECES};
 
 use crate::ai::minimax::Heuristic;
 use crate::ai::solver::SolverHeuristic;
@@ -10,6 +10,11 @@ use crate::games::chess::ChessBoard;
 #[derive(Debug)]
 pub struct ChessPieceValueHeuristic;
 
+/// The same values as [ChessPieceValueHeuristic],
+/// but with a quiescence search over captures and promotions, see [Heuristic::is_noisy].
+#[derive(Debug)]
+pub struct ChessPieceValueQuiescenceHeuristic;
+
 impl Heuristic<ChessBoard> for ChessPieceValueHeuristic {
     type V = i32;
 
@@ -21,13 +26,7 @@ impl Heuristic<ChessBoard> for ChessPieceValueHeuristic {
         let mut total = 0;
 
         for piece in ALL_PIECES {
-            let value = match piece {
-                Piece::Pawn => 1,
-                Piece::Knight | Piece::Bishop => 3,
-                Piece::Rook => 5,
-                Piece::Queen => 9,
-                Piece::King => 0,
-            };
+            let value = piece_value(piece);
 
             for square in *board.inner().pieces(piece) {
                 // we can unwrap here since we're iterating over the squares that contain the current piece,
@@ -46,4 +45,65 @@ impl Heuristic<ChessBoard> for ChessPieceValueHeuristic {
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
         (max(old, new), new.cmp(&old))
     }
+
+    /// Order captures and promotions before quiet moves, captures are ordered by
+    /// [MVV-LVA](https://www.chessprogramming.org/MVV-LVA): most valuable victim first, then least valuable attacker.
+    fn move_score(&self, board: &ChessBoard, mv: ChessMove) -> i32 {
+        let inner = board.inner();
+
+        let victim = inner.piece_on(mv.get_dest()).map_or(0, piece_value);
+        let promotion = mv.get_promotion().map_or(0, piece_value);
+        if victim == 0 && promotion == 0 {
+            return 0;
+        }
+
+        // the king is the most valuable attacker, it can't be recaptured but it can't be protected either
+        let attacker = match inner.piece_on(mv.get_source()) {
+            Some(Piece::King) | None => 10,
+            Some(piece) => piece_value(piece),
+        };
+        16 * (victim + promotion) - attacker + 16
+    }
+}
+
+impl Heuristic<ChessBoard> for ChessPieceValueQuiescenceHeuristic {
+    type V = i32;
+
+    fn value(&self, board: &ChessBoard, length: u32) -> Self::V {
+        ChessPieceValueHeuristic.value(board, length)
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        ChessPieceValueHeuristic::merge(old, new)
+    }
+
+    fn move_score(&self, board: &ChessBoard, mv: ChessMove) -> i32 {
+        ChessPieceValueHeuristic.move_score(board, mv)
+    }
+
+    /// Captures (including en passant) and promotions are noisy, they always reduce the material on the board
+    /// or the number of pawns, so the quiescence search is guaranteed to end.
+    fn is_noisy(&self, board: &ChessBoard, mv: ChessMove) -> bool {
+        let inner = board.inner();
+
+        let capture = inner.piece_on(mv.get_dest()).is_some();
+        let en_passant = inner.piece_on(mv.get_source()) == Some(Piece::Pawn)
+            && mv.get_source().get_file() != mv.get_dest().get_file();
+
+        capture || en_passant || mv.get_promotion().is_some()
+    }
+
+    fn has_noisy_moves(&self) -> bool {
+        true
+    }
+}
+
+fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
 }
@@ -2,5 +2,9 @@
 pub mod ataxx;
 #[cfg(feature = "game_chess")]
 pub mod chess;
+#[cfg(feature = "game_oware")]
+pub mod oware;
+#[cfg(feature = "game_pig")]
+pub mod pig;
 #[cfg(feature = "game_sttt")]
 pub mod sttt;
@@ -0,0 +1,27 @@
+use std::cmp::{max, Ordering};
+
+use crate::ai::minimax::Heuristic;
+use crate::ai::solver::SolverHeuristic;
+use crate::board::Board;
+use crate::games::oware::OwareBoard;
+
+/// Heuristic that compares the number of captured seeds of both players.
+#[derive(Debug)]
+pub struct OwareScoreHeuristic;
+
+impl<const P: usize> Heuristic<OwareBoard<P>> for OwareScoreHeuristic {
+    type V = i32;
+
+    fn value(&self, board: &OwareBoard<P>, length: u32) -> Self::V {
+        if board.is_done() {
+            return SolverHeuristic.value(board, length).to_i32();
+        }
+
+        let next = board.next_player();
+        board.score(next) as i32 - board.score(next.other()) as i32
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        (max(old, new), new.cmp(&old))
+    }
+}
@@ -0,0 +1,34 @@
+use std::cmp::Ordering;
+
+use crate::ai::minimax::Heuristic;
+use crate::ai::solver::SolverHeuristic;
+use crate::board::Board;
+use crate::games::pig::PigBoard;
+
+/// Heuristic that compares the scores of both players, counting the current turn total as already held.
+#[derive(Debug)]
+pub struct PigScoreHeuristic;
+
+impl Heuristic<PigBoard> for PigScoreHeuristic {
+    type V = f32;
+
+    fn value(&self, board: &PigBoard, length: u32) -> Self::V {
+        if board.is_done() {
+            return SolverHeuristic.value(board, length).to_i32() as f32;
+        }
+
+        let next = board.next_player();
+        let next_score = board.score(next) + board.turn_total();
+        let other_score = board.score(next.other());
+
+        next_score as f32 - other_score as f32
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        (old.max(new), new.partial_cmp(&old).unwrap())
+    }
+
+    fn expectation(children: &[(Self::V, f32)]) -> Self::V {
+        children.iter().map(|&(value, p)| value * p).sum()
+    }
+}
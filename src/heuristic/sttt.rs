@@ -47,17 +47,10 @@ impl Heuristic<STTTBoard> for STTTTileHeuristic {
         tile_value + macr_value
     }
 
-    fn value_update(
-        &self,
-        board: &STTTBoard,
-        board_value: i32,
-        board_length: u32,
-        mv: Coord,
-        child: &STTTBoard,
-    ) -> i32 {
+    fn value_update_in_place(&self, board_value: i32, board_length: u32, mv: Coord, child: &STTTBoard) -> i32 {
         // win
-        if board.outcome().is_some() {
-            return self.value(board, board_length + 1);
+        if child.outcome().is_some() {
+            return self.value(child, board_length + 1);
         }
 
         let mut neg_child_value = board_value;
@@ -1,10 +1,14 @@
 use std::cmp::Ordering;
 use std::io::{BufRead, Write};
 use std::str::FromStr;
+use std::time::Duration;
 
 use itertools::Itertools;
 use nohash_hasher::IntSet;
+use rand::Rng;
 
+use crate::ai::budget::SearchBudget;
+use crate::ai::mcts::MCTSSearcher;
 use crate::board::{Board, BoardDone, PlayError, Player};
 use crate::games::go::{go_player_from_symbol, Chains, GoBoard, Komi, Move, Rules, State, Tile, Zobrist, GO_MAX_SIZE};
 use crate::interface::gtp::command::{Command, CommandKind, FinalStatusKind, Response, ResponseInner};
@@ -19,6 +23,19 @@ pub trait GtpBot {
     fn select_action(&mut self, board: &GoBoard, time: &TimeInfo, log: &mut impl Write) -> Result<Action, BoardDone>;
 }
 
+/// Searches each move for [TimeInfo::simple_time_to_use], assuming half of the empty tiles will still be filled.
+/// The searcher ponders between moves if it was configured to, see [MCTSSearcher::with_pondering].
+impl<R: Rng> GtpBot for MCTSSearcher<GoBoard, R> {
+    fn select_action(&mut self, board: &GoBoard, time: &TimeInfo, log: &mut impl Write) -> Result<Action, BoardDone> {
+        let expected_stones_left = board.empty_tiles().len() as f32 / 2.0;
+        let time_to_use = time.simple_time_to_use(expected_stones_left);
+        let _ = writeln!(log, "planning to use {}s", time_to_use);
+
+        let budget = SearchBudget::time(Duration::from_secs_f32(time_to_use.max(0.0)));
+        self.select_move_within(board, &budget).map(Action::Move)
+    }
+}
+
 #[derive(Debug)]
 pub struct GtpEngineState {
     size: u8,
@@ -1,8 +1,12 @@
 use std::collections::VecDeque;
 use std::io::{BufRead, BufReader, BufWriter, Read};
 use std::io::{ErrorKind, Write};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use rand::Rng;
+
+use crate::ai::budget::SearchBudget;
+use crate::ai::mcts::MCTSSearcher;
 use crate::board::{Board, PlayError, Player};
 use crate::games::ataxx::{AtaxxBoard, Move};
 use crate::interface::uai::command::{Command, GoTimeSettings, Position};
@@ -28,6 +32,18 @@ pub fn run(
     result
 }
 
+/// Wrap `searcher` into a bot for [run] that searches for the planned amount of time.
+/// The searcher ponders between `go` commands if it was configured to, see [MCTSSearcher::with_pondering].
+pub fn mcts_bot<R: Rng>(
+    searcher: &mut MCTSSearcher<AtaxxBoard, R>,
+) -> impl FnMut(&AtaxxBoard, f32) -> (Move, String) + '_ {
+    move |board, time_to_use| {
+        let budget = SearchBudget::time(Duration::from_secs_f32(time_to_use.max(0.0)));
+        let mv = searcher.select_move_within(board, &budget).unwrap();
+        (mv, String::new())
+    }
+}
+
 pub fn run_inner(
     mut bot: impl FnMut(&AtaxxBoard, f32) -> (Move, String),
     name: &str,
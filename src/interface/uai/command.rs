@@ -36,7 +36,7 @@ pub enum Position<'a> {
 }
 
 impl<'a> Command<'a> {
-    pub fn parse(input: &'a str) -> Result<Command, nom::Err<nom::error::Error<&str>>> {
+    pub fn parse(input: &'a str) -> Result<Command<'a>, nom::Err<nom::error::Error<&'a str>>> {
         parse::command()(input).map(|(left, command)| {
             assert!(left.is_empty());
             command
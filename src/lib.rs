@@ -5,46 +5,63 @@
 #![allow(clippy::assertions_on_constants)]
 #![allow(clippy::new_without_default)]
 
-//! A [Board](crate::board::Board) abstraction for deterministic two player games.
+//! A [Board](crate::board::Board) abstraction for two player games,
+//! optionally with [chance nodes](crate::board::Board::is_chance_node).
 //! This allows for code to be generic over the actual game, so it only needs to written once.
 //!
 //! # Features
 //!
 //! Currently, the implemented games are:
 //! * [Chess](https://en.wikipedia.org/wiki/Chess) as [ChessBoard](crate::games::chess::ChessBoard),
-//!     implemented as a simple wrapper around the [chess](https://crates.io/crates/chess) crate.
+//!   implemented as a simple wrapper around the [chess](https://crates.io/crates/chess) crate.
 //! * [Go/Baduk](https://en.wikipedia.org/wiki/Go_(game))
-//!     as [GoBoard](crate::games::go::board::GoBoard).
+//!   as [GoBoard](crate::games::go::board::GoBoard).
 //! * [Super/Ultimate tic-tac-toe](https://en.wikipedia.org/wiki/Ultimate_tic-tac-toe)
-//!     as [STTTBoard](crate::games::sttt::STTTBoard).
+//!   as [STTTBoard](crate::games::sttt::STTTBoard).
 //! * [Ataxx](https://en.wikipedia.org/wiki/Ataxx)
-//!     as [AtaxxBoard](crate::games::ataxx::board::AtaxxBoard).
-//! * [Oware](https://en.wikipedia.org/wiki/Oware) as [OwareBoard](crate::games::oware::OwareBoard).
-//! * [Connect4](https://en.wikipedia.org/wiki/Connect_Four) as [Connect4](crate::games::connect4::Connect4).
+//!   as [AtaxxBoard](crate::games::ataxx::board::AtaxxBoard).
+//! * [Oware](https://en.wikipedia.org/wiki/Oware) as [OwareBoard](crate::games::oware::OwareBoard),
+//!   small variants can be solved exhaustively with [solve_oware](crate::games::oware::solver::solve_oware).
+//! * [Connect4](https://en.wikipedia.org/wiki/Connect_Four) as [Connect4](crate::games::connect4::Connect4),
+//!   with a dedicated [Connect4Solver](crate::games::connect4::solver::Connect4Solver).
 //! * [Tic Tac Toe](https://en.wikipedia.org/wiki/Tic-tac-toe) as [TTTBoard](crate::games::ttt::TTTBoard).
+//! * [Pig](https://en.wikipedia.org/wiki/Pig_(dice_game)) as [PigBoard](crate::games::pig::PigBoard),
+//!   a simple dice game with chance nodes.
 //!
 //! Most game implementations are heavily optimized, using bitboards or other techniques where appropriate.
 //!
 //! There are also some utility boards:
 //! * [MaxMovesBoard](crate::games::max_length::MaxMovesBoard)
-//!     wraps another board and sets the outcome to a draw after move limit has been reached.
+//!   wraps another board and sets the outcome to a draw after move limit has been reached.
 //! * [DummyGame](crate::games::dummy::DummyGame)
-//!     is a board that is constructed from an explicit game tree, useful for debugging.
+//!   is a board that is constructed from an explicit game tree, useful for debugging.
 //!
 //! Utilities in this crate that work for any [Board](crate::board::Board):
 //! * Game-playing algorithms, specifically:
 //!     * [RandomBot](crate::ai::simple::RandomBot),
-//!         which simply picks a random move.
+//!       which simply picks a random move.
 //!     * [RolloutBot](crate::ai::simple::RolloutBot),
-//!         which simulates a fixed number of random games for each possible move and picks the one with the best win probability.
+//!       which simulates a fixed number of random games for each possible move and picks the one with the best win probability.
+//!       The games can also be played out by a custom [RolloutPolicy](crate::ai::rollout::RolloutPolicy).
 //!     * [MinimaxBot](crate::ai::minimax::MiniMaxBot),
-//!         which picks the best move as evaluated by a customizable heuristic at a fixed depth. (implemented as alpha-beta negamax).
+//!       which picks the best move as evaluated by a customizable heuristic at a fixed depth. (implemented as alpha-beta negamax).
+//!     * [IterativeMiniMaxBot](crate::ai::minimax::IterativeMiniMaxBot),
+//!       which runs the same search with increasing depths until its time or node budget runs out.
+//!     * [LazySmpBot](crate::ai::minimax::LazySmpBot),
+//!       which runs that search on multiple threads that share a transposition table.
 //!     * [MCTSBot](crate::ai::mcts::MCTSBot),
-//!         which picks the best move as found by [Monte Carlo Tree Search](https://en.wikipedia.org/wiki/Monte_Carlo_tree_search).
+//!       which picks the best move as found by [Monte Carlo Tree Search](https://en.wikipedia.org/wiki/Monte_Carlo_tree_search).
+//!     * [DagMCTSBot](crate::ai::mcts_dag::DagMCTSBot),
+//!       which runs the same search on a graph where transpositions and symmetric positions share a node.
 //! * Random board generation functions, see [board_gen](crate::util::board_gen).
 //! * A bot vs bot game runner to compare playing strength, see [bot_game](crate::util::bot_game).
 //! * Simple game statistics (perft, random game length) which can be used to test board implementations.
 //!
+//! Games with more than two players are supported through the separate [NBoard](crate::multi::board::NBoard)
+//! abstraction in the [multi] module, which comes with its own MCTS bot and bot game runner.
+//! [MultiTTTBoard](crate::games::multi_ttt::MultiTTTBoard) is an example implementation,
+//! tic-tac-toe generalized to any number of players.
+//!
 //! This crate is also used as the foundation for [kZero](https://github.com/KarelPeeters/kZero),
 //! a general AlphaZero implementation.
 //!
@@ -119,3 +136,5 @@ pub mod heuristic;
 pub mod util;
 
 pub mod interface;
+
+pub mod multi;
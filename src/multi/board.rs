@@ -0,0 +1,158 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use rand::Rng;
+
+use crate::board::{BoardDone, PlayError};
+
+/// The index of a player in a game with any number of players, starting from zero.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PlayerIndex(u8);
+
+/// The outcome of a finished game with any number of players,
+/// represented as the reward each player gets.
+///
+/// Rewards are typically in the range `0..=1` and sum to one, for example:
+/// * a win for a single player gives that player reward `1` and all other players `0`
+/// * a draw between `k` players gives each of them reward `1/k`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NOutcome {
+    rewards: Vec<f32>,
+}
+
+/// The equivalent of [Board](crate::board::Board) for games with any number of players.
+///
+/// Unlike [Board](crate::board::Board) this trait does not support symmetries or chance nodes,
+/// and moves are returned as a [Vec] instead of an internal iterator.
+pub trait NBoard: 'static + Debug + Display + Clone + Eq + Send + Sync {
+    /// The type used to represent moves on this board.
+    type Move: Debug + Display + Eq + Copy + Send + Sync;
+
+    /// The number of players in this game, at least one.
+    fn player_count(&self) -> u8;
+
+    /// Return the next player to make a move.
+    /// If the board is done this is the player that would have been next.
+    fn next_player(&self) -> PlayerIndex;
+
+    /// Return whether the given move is available.
+    fn is_available_move(&self, mv: Self::Move) -> Result<bool, BoardDone>;
+
+    /// Return the available moves, is always nonempty. The ordering is consistent when the board is not modified.
+    fn available_moves(&self) -> Result<Vec<Self::Move>, BoardDone>;
+
+    /// Pick a random move from the `available_moves` with a uniform distribution.
+    /// Can be overridden for better performance.
+    fn random_available_move(&self, rng: &mut impl Rng) -> Result<Self::Move, BoardDone> {
+        let moves = self.available_moves()?;
+        Ok(moves[rng.gen_range(0..moves.len())])
+    }
+
+    /// Play the move `mv`, modifying this board.
+    fn play(&mut self, mv: Self::Move) -> Result<(), PlayError>;
+
+    /// Clone this board, play `mv` on it and return the new board.
+    fn clone_and_play(&self, mv: Self::Move) -> Result<Self, PlayError> {
+        let mut next = self.clone();
+        next.play(mv)?;
+        Ok(next)
+    }
+
+    /// The outcome of this board, is `None` when this games is not done yet.
+    fn outcome(&self) -> Option<NOutcome>;
+
+    /// Whether this games is done.
+    fn is_done(&self) -> bool {
+        self.outcome().is_some()
+    }
+
+    /// Returns
+    /// * `Err(BoardDone)` if `self.is_done()`
+    /// * `Ok(())` otherwise.
+    fn check_done(&self) -> Result<(), BoardDone> {
+        match self.is_done() {
+            true => Err(BoardDone),
+            false => Ok(()),
+        }
+    }
+
+    /// Returns
+    /// * `Err(BoardDone)` if `self.is_done()`
+    /// * `Err(UnavailableMove)` if `!self.is_available_move(mv)`
+    /// * `Ok(())` otherwise.
+    fn check_can_play(&self, mv: Self::Move) -> Result<(), PlayError> {
+        match self.is_available_move(mv)? {
+            true => Ok(()),
+            false => Err(PlayError::UnavailableMove),
+        }
+    }
+}
+
+impl PlayerIndex {
+    pub const fn new(index: u8) -> Self {
+        PlayerIndex(index)
+    }
+
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    /// The player that comes after this one, wrapping around after the last player.
+    pub fn next(self, player_count: u8) -> PlayerIndex {
+        debug_assert!(self.0 < player_count);
+        PlayerIndex((self.0 + 1) % player_count)
+    }
+
+    /// Iterate over all players for the given player count.
+    pub fn all(player_count: u8) -> impl Iterator<Item = PlayerIndex> {
+        (0..player_count).map(PlayerIndex)
+    }
+}
+
+impl Display for PlayerIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "P{}", self.0)
+    }
+}
+
+impl NOutcome {
+    pub fn from_rewards(rewards: Vec<f32>) -> Self {
+        assert!(!rewards.is_empty(), "there must be at least one player");
+        NOutcome { rewards }
+    }
+
+    /// The outcome where `winner` gets reward `1` and all other players `0`.
+    pub fn won_by(winner: PlayerIndex, player_count: u8) -> Self {
+        assert!(winner.index() < player_count);
+        let rewards = PlayerIndex::all(player_count)
+            .map(|p| if p == winner { 1.0 } else { 0.0 })
+            .collect();
+        NOutcome { rewards }
+    }
+
+    /// The outcome where all players draw, each of them gets reward `1 / player_count`.
+    pub fn draw(player_count: u8) -> Self {
+        assert!(player_count > 0);
+        NOutcome {
+            rewards: vec![1.0 / player_count as f32; player_count as usize],
+        }
+    }
+
+    pub fn player_count(&self) -> u8 {
+        self.rewards.len() as u8
+    }
+
+    pub fn reward(&self, player: PlayerIndex) -> f32 {
+        self.rewards[player.index() as usize]
+    }
+
+    pub fn rewards(&self) -> &[f32] {
+        &self.rewards
+    }
+
+    /// The player that got all of the reward, if any.
+    pub fn winner(&self) -> Option<PlayerIndex> {
+        let total = self.rewards.iter().sum::<f32>();
+        let winner = self.rewards.iter().position(|&r| r > 0.0 && r == total)?;
+        Some(PlayerIndex(winner as u8))
+    }
+}
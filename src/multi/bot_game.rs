@@ -0,0 +1,178 @@
+//! Utilities to run bots against each other in games with any number of players.
+use std::fmt::{Debug, Formatter};
+use std::time::Instant;
+
+use itertools::Itertools;
+
+use crate::multi::board::{NBoard, NOutcome, PlayerIndex};
+use crate::multi::NBot;
+use crate::util::bot_game::{debug_to_string, parallel_map_unordered};
+
+/// A function that constructs a new bot, used by [run] to create a fresh bot for every game.
+pub type NBotFactory<'a, B> = Box<dyn Fn() -> Box<dyn NBot<B>> + Sync + 'a>;
+
+/// Run the given bots against each other on boards given by `start`.
+///
+/// The number of bots must match the player count of the boards.
+/// Bots are rotated between the players, so every start position is played `bots.len()` times
+/// and each bot gets to play as every player once.
+///
+/// `callback` is called after each game finishes.
+#[must_use]
+pub fn run<B: NBoard>(
+    start: impl Fn() -> B + Sync,
+    bots: &[NBotFactory<B>],
+    games_per_seat: u32,
+    cores: Option<usize>,
+    callback: impl Fn(&NReplay<B>) + Sync,
+) -> NBotGameResult<B> {
+    let bot_count = bots.len();
+    assert!(bot_count > 0, "there must be at least one bot");
+
+    // this instantiates all bots at least once so we catch errors before starting a bunch of threads
+    let debug = bots.iter().map(|bot| debug_to_string(&bot())).collect_vec();
+
+    let starts = (0..games_per_seat).map(|_| start()).collect_vec();
+    for start in &starts {
+        assert_eq!(
+            start.player_count() as usize,
+            bot_count,
+            "bot count must match player count"
+        );
+    }
+
+    let game_count = games_per_seat * bot_count as u32;
+
+    let replays: Vec<NReplay<B>> = parallel_map_unordered(cores, game_count, |game_i| {
+        let start = &starts[game_i as usize / bot_count];
+        let rotation = game_i as usize % bot_count;
+
+        // the bot index for each player
+        let seats = (0..bot_count).map(|p| (p + rotation) % bot_count).collect_vec();
+
+        let replay = play_single_game(start, seats, bots);
+        callback(&replay);
+        replay
+    });
+
+    let mut total_rewards = vec![0.0; bot_count];
+    let mut wins = vec![0; bot_count];
+    let mut total_time = vec![0.0; bot_count];
+    let mut move_count = vec![0; bot_count];
+
+    for replay in &replays {
+        for (p, &bot) in replay.seats.iter().enumerate() {
+            let player = PlayerIndex::new(p as u8);
+            total_rewards[bot] += replay.outcome.reward(player);
+            if replay.outcome.winner() == Some(player) {
+                wins[bot] += 1;
+            }
+            total_time[bot] += replay.total_time[bot];
+            move_count[bot] += replay.move_count[bot];
+        }
+    }
+
+    NBotGameResult {
+        game_count,
+        average_game_length: replays.iter().map(|r| r.moves.len() as f32).sum::<f32>() / game_count as f32,
+        average_rewards: total_rewards.iter().map(|&r| r / game_count as f32).collect(),
+        wins,
+        time: total_time
+            .iter()
+            .zip(&move_count)
+            .map(|(&t, &c)| t / c as f32)
+            .collect(),
+        debug,
+        replays,
+    }
+}
+
+fn play_single_game<B: NBoard>(start: &B, seats: Vec<usize>, bots: &[NBotFactory<B>]) -> NReplay<B> {
+    let bot_count = bots.len();
+    let mut bots = bots.iter().map(|bot| bot()).collect_vec();
+
+    let mut board = start.clone();
+    let mut total_time = vec![0.0; bot_count];
+    let mut move_count = vec![0; bot_count];
+    let mut moves = vec![];
+
+    loop {
+        match board.outcome() {
+            None => {
+                let bot = seats[board.next_player().index() as usize];
+
+                let start_time = Instant::now();
+                let mv = bots[bot].select_move(&board).unwrap();
+                total_time[bot] += start_time.elapsed().as_secs_f32();
+                move_count[bot] += 1;
+
+                moves.push(mv);
+                board.play(mv).unwrap();
+            }
+            Some(outcome) => {
+                return NReplay {
+                    start: start.clone(),
+                    seats,
+                    moves,
+                    outcome,
+                    total_time,
+                    move_count,
+                    debug: bots.iter().map(debug_to_string).collect(),
+                };
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NReplay<B: NBoard> {
+    pub start: B,
+    /// The index of the bot that played as each player.
+    pub seats: Vec<usize>,
+
+    pub moves: Vec<B::Move>,
+    pub outcome: NOutcome,
+
+    /// Indexed by bot.
+    pub total_time: Vec<f32>,
+    /// Indexed by bot.
+    pub move_count: Vec<u32>,
+    /// Indexed by bot.
+    pub debug: Vec<String>,
+}
+
+/// Structure returned by the function [`run`], all vectors are indexed by bot.
+pub struct NBotGameResult<B: NBoard> {
+    pub game_count: u32,
+    pub replays: Vec<NReplay<B>>,
+
+    pub average_game_length: f32,
+    pub average_rewards: Vec<f32>,
+    pub wins: Vec<u32>,
+
+    //time per move in seconds
+    pub time: Vec<f32>,
+
+    pub debug: Vec<String>,
+}
+
+impl<B: NBoard> Debug for NBotGameResult<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "NBotGameResult {{")?;
+        writeln!(
+            f,
+            "  {} games, average length {}",
+            self.game_count, self.average_game_length
+        )?;
+        for i in 0..self.debug.len() {
+            writeln!(
+                f,
+                "  bot {}: reward {:.3}, wins {}, time {:.4}, {}",
+                i, self.average_rewards[i], self.wins[i], self.time[i], self.debug[i]
+            )?;
+        }
+        writeln!(f, "}}")?;
+
+        Ok(())
+    }
+}
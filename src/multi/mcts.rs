@@ -0,0 +1,264 @@
+use std::fmt::{Debug, Formatter};
+use std::num::NonZeroUsize;
+use std::ops::{Index, IndexMut};
+
+use decorum::N32;
+use rand::Rng;
+
+use crate::ai::mcts::IdxRange;
+use crate::board::BoardDone;
+use crate::multi::board::{NBoard, NOutcome, PlayerIndex};
+use crate::multi::NBot;
+
+/// How simulation results are propagated back up the tree.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Backup {
+    /// Every player maximizes their own reward, see
+    /// [max^n](https://www.aaai.org/Papers/AAAI/1986/AAAI86-025.pdf).
+    MaxN,
+    /// All other players are assumed to form a coalition that minimizes the reward of the root player,
+    /// which effectively turns the game into a two player game.
+    Paranoid,
+}
+
+/// Represents a node in the MCTS search tree.
+///
+/// `rewards` contains the total reward for each player, summed over all visits.
+#[derive(Debug)]
+pub struct NNode<M> {
+    pub last_move: Option<M>,
+    pub children: Option<IdxRange>,
+    pub visits: u64,
+    pub rewards: Vec<f32>,
+}
+
+impl<M> NNode<M> {
+    fn new(last_move: Option<M>, player_count: u8) -> Self {
+        NNode {
+            last_move,
+            children: None,
+            visits: 0,
+            rewards: vec![0.0; player_count as usize],
+        }
+    }
+
+    fn increment(&mut self, rewards: &[f32]) {
+        self.visits += 1;
+        for (total, &r) in self.rewards.iter_mut().zip(rewards) {
+            *total += r;
+        }
+    }
+
+    /// The average reward for `player` in this node.
+    pub fn value(&self, player: PlayerIndex) -> f32 {
+        self.rewards[player.index() as usize] / self.visits as f32
+    }
+
+    fn uct(&self, player: PlayerIndex, parent_visits: u64, exploration_weight: f32) -> f32 {
+        let visits = self.visits as f32;
+        let explore = ((parent_visits as f32).ln() / visits).sqrt();
+        self.value(player) + exploration_weight * explore
+    }
+}
+
+/// The search tree built by [n_mcts_build_tree].
+#[derive(Debug)]
+pub struct NTree<B: NBoard> {
+    pub root_board: B,
+    pub nodes: Vec<NNode<B::Move>>,
+}
+
+impl<B: NBoard> NTree<B> {
+    pub fn best_child(&self) -> usize {
+        let children = self[0].children.expect("Root node must have children");
+        children.iter().max_by_key(|&c| self[c].visits).unwrap()
+    }
+
+    pub fn best_move(&self) -> B::Move {
+        let best_child = self.best_child();
+        self[best_child].last_move.unwrap()
+    }
+
+    /// The average rewards for each player in `root_board`, as used by the backup strategy.
+    pub fn values(&self) -> Vec<f32> {
+        PlayerIndex::all(self.root_board.player_count())
+            .map(|p| self[0].value(p))
+            .collect()
+    }
+}
+
+impl<B: NBoard> Index<usize> for NTree<B> {
+    type Output = NNode<B::Move>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.nodes[index]
+    }
+}
+
+impl<B: NBoard> IndexMut<usize> for NTree<B> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.nodes[index]
+    }
+}
+
+/// Convert an outcome to the rewards that are propagated through the tree.
+fn backup_rewards(outcome: &NOutcome, root_player: PlayerIndex, backup: Backup) -> Vec<f32> {
+    match backup {
+        Backup::MaxN => outcome.rewards().to_vec(),
+        Backup::Paranoid => {
+            let root_reward = outcome.reward(root_player);
+            PlayerIndex::all(outcome.player_count())
+                .map(|p| {
+                    if p == root_player {
+                        root_reward
+                    } else {
+                        1.0 - root_reward
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn random_playout<B: NBoard>(mut board: B, rng: &mut impl Rng) -> NOutcome {
+    loop {
+        if let Some(outcome) = board.outcome() {
+            return outcome;
+        }
+
+        let mv = board.random_available_move(rng).unwrap();
+        board.play(mv).unwrap();
+    }
+}
+
+/// Run a single MCTS step, returning the rewards that were propagated back.
+/// This function has already increments `curr_node` before it returns.
+fn n_mcts_step<B: NBoard>(
+    tree: &mut NTree<B>,
+    curr_node: usize,
+    curr_board: &B,
+    root_player: PlayerIndex,
+    exploration_weight: f32,
+    backup: Backup,
+    rng: &mut impl Rng,
+) -> Vec<f32> {
+    if let Some(outcome) = curr_board.outcome() {
+        let rewards = backup_rewards(&outcome, root_player, backup);
+        tree[curr_node].increment(&rewards);
+        return rewards;
+    }
+
+    // initialize children
+    let children = match tree[curr_node].children {
+        Some(children) => children,
+        None => {
+            let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
+            let player_count = curr_board.player_count();
+            for mv in curr_board.available_moves().unwrap() {
+                tree.nodes.push(NNode::new(Some(mv), player_count));
+            }
+
+            let length = tree.nodes.len() - start.get();
+            let children = IdxRange { start, length };
+            tree[curr_node].children = Some(children);
+            children
+        }
+    };
+
+    // pick an unvisited child if there are any, otherwise the max-uct child from the POV of the next player
+    let next_player = curr_board.next_player();
+    let unvisited: Vec<usize> = children.iter().filter(|&c| tree[c].visits == 0).collect();
+
+    let picked = if unvisited.is_empty() {
+        let parent_visits = tree[curr_node].visits;
+        children
+            .iter()
+            .max_by_key(|&c| N32::from(tree[c].uct(next_player, parent_visits, exploration_weight)))
+            .unwrap()
+    } else {
+        unvisited[rng.gen_range(0..unvisited.len())]
+    };
+
+    let picked_mv = tree[picked].last_move.unwrap();
+    let next_board = curr_board.clone_and_play(picked_mv).unwrap();
+
+    let rewards = if tree[picked].visits == 0 {
+        // run a random playout for new nodes
+        let outcome = random_playout(next_board, rng);
+        let rewards = backup_rewards(&outcome, root_player, backup);
+        tree[picked].increment(&rewards);
+        rewards
+    } else {
+        // continue recursing
+        n_mcts_step(tree, picked, &next_board, root_player, exploration_weight, backup, rng)
+    };
+
+    tree[curr_node].increment(&rewards);
+    rewards
+}
+
+pub fn n_mcts_build_tree<B: NBoard>(
+    root_board: &B,
+    iterations: u64,
+    exploration_weight: f32,
+    backup: Backup,
+    rng: &mut impl Rng,
+) -> NTree<B> {
+    assert!(iterations > 0);
+    assert!(!root_board.is_done());
+
+    let mut tree = NTree {
+        root_board: root_board.clone(),
+        nodes: vec![NNode::new(None, root_board.player_count())],
+    };
+    let root_player = root_board.next_player();
+
+    for _ in 0..iterations {
+        n_mcts_step(&mut tree, 0, root_board, root_player, exploration_weight, backup, rng);
+    }
+
+    tree
+}
+
+pub struct NMCTSBot<R: Rng> {
+    iterations: u64,
+    exploration_weight: f32,
+    backup: Backup,
+    rng: R,
+}
+
+impl<R: Rng> Debug for NMCTSBot<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "NMCTSBot {{ iterations: {}, exploration_weight: {}, backup: {:?} }}",
+            self.iterations, self.exploration_weight, self.backup
+        )
+    }
+}
+
+impl<R: Rng> NMCTSBot<R> {
+    pub fn new(iterations: u64, exploration_weight: f32, backup: Backup, rng: R) -> Self {
+        assert!(iterations > 0);
+        NMCTSBot {
+            iterations,
+            exploration_weight,
+            backup,
+            rng,
+        }
+    }
+}
+
+impl<B: NBoard, R: Rng> NBot<B> for NMCTSBot<R> {
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        board.check_done()?;
+        let tree = n_mcts_build_tree(
+            board,
+            self.iterations,
+            self.exploration_weight,
+            self.backup,
+            &mut self.rng,
+        );
+        Ok(tree.best_move())
+    }
+}
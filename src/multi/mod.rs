@@ -0,0 +1,27 @@
+//! Support for games with more than two players.
+//!
+//! The main [Board](crate::board::Board) trait is specialized for two players, which allows for a lot of convenient
+//! utilities like [Outcome](crate::board::Outcome) and [WDL](crate::wdl::WDL).
+//! This module contains a separate abstraction [NBoard](board::NBoard) for games with any number of players,
+//! together with some matching tooling:
+//! * [NRandomBot](simple::NRandomBot), which simply picks a random move.
+//! * [NMCTSBot](mcts::NMCTSBot), Monte Carlo Tree Search with max^n or paranoid backups.
+//! * A bot game runner, see [bot_game].
+use std::fmt::Debug;
+
+use crate::board::BoardDone;
+use crate::multi::board::NBoard;
+
+pub mod board;
+pub mod bot_game;
+pub mod mcts;
+pub mod simple;
+
+/// The equivalent of [Bot](crate::ai::Bot) for [NBoard].
+pub trait NBot<B: NBoard>: Debug {
+    /// Pick a move to play.
+    ///
+    /// `self` is mutable to allow for random state, this method is not supposed to
+    /// modify `self` in any other significant way.
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone>;
+}
@@ -0,0 +1,30 @@
+use std::fmt::{Debug, Formatter};
+
+use rand::Rng;
+
+use crate::board::BoardDone;
+use crate::multi::board::NBoard;
+use crate::multi::NBot;
+
+/// Bot that chooses moves randomly uniformly among possible moves.
+pub struct NRandomBot<R: Rng> {
+    rng: R,
+}
+
+impl<R: Rng> Debug for NRandomBot<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NRandomBot")
+    }
+}
+
+impl<R: Rng> NRandomBot<R> {
+    pub fn new(rng: R) -> Self {
+        NRandomBot { rng }
+    }
+}
+
+impl<B: NBoard, R: Rng> NBot<B> for NRandomBot<R> {
+    fn select_move(&mut self, board: &B) -> Result<B::Move, BoardDone> {
+        board.random_available_move(&mut self.rng)
+    }
+}
@@ -20,6 +20,8 @@ use crate::wdl::WDL;
 /// which case a match consists of two games per start position where players switch sides.
 ///
 /// Progress indications can be displayed at intervals of `print_progress_every`.
+///
+/// Chance nodes are resolved randomly without asking either bot, see [Board::is_chance_node].
 #[must_use]
 pub fn run<B: Board, L: Bot<B> + Debug, R: Bot<B> + Debug>(
     start: impl Fn() -> B + Sync,
@@ -72,7 +74,7 @@ pub fn run<B: Board, L: Bot<B> + Debug, R: Bot<B> + Debug>(
     }
 }
 
-fn parallel_map_unordered<R: Send>(cores: Option<usize>, len: u32, f: impl Fn(u32) -> R + Sync) -> Vec<R> {
+pub(crate) fn parallel_map_unordered<R: Send>(cores: Option<usize>, len: u32, f: impl Fn(u32) -> R + Sync) -> Vec<R> {
     let cores = cores.unwrap_or_else(num_cpus::get);
 
     let next = AtomicU32::new(0);
@@ -136,8 +138,15 @@ fn play_single_game<B: Board, L: Bot<B> + Debug, R: Bot<B> + Debug>(
     let mut move_count_r: u32 = 0;
     let mut moves = vec![];
 
+    let mut rng = rand::thread_rng();
+
     loop {
         match board.outcome() {
+            None if board.is_chance_node() => {
+                let mv = board.random_available_move(&mut rng).unwrap();
+                moves.push(mv);
+                board.play(mv).unwrap();
+            }
             None => {
                 let start_time = Instant::now();
                 let mv = if board.next_player() == player_l {
@@ -231,7 +240,7 @@ impl<B: Board> Debug for BotGameResult<B> {
     }
 }
 
-fn debug_to_string(d: &impl Debug) -> String {
+pub(crate) fn debug_to_string(d: &impl Debug) -> String {
     let mut s = String::new();
     write!(&mut s, "{:?}", d).unwrap();
     s
@@ -6,12 +6,16 @@ use internal_iterator::InternalIterator;
 use rand::Rng;
 
 use crate::ai::Bot;
-use crate::board::{Board, Player};
+use crate::board::{Board, Player, ZobristBoard};
 use crate::pov::NonPov;
+use crate::util::tt::{Bound, TTEntry, TranspositionTable};
+use crate::util::zobrist::ZobristKey;
 use crate::wdl::WDL;
 
 /// The number of legal positions reachable after `depth` moves, including duplicates.
 /// See <https://www.chessprogramming.org/Perft>.
+///
+/// Chance nodes don't count towards `depth`, all of their outcomes are expanded and counted separately.
 pub fn perft<B: Board + Hash>(board: &B, depth: u32) -> u64 {
     let mut map = HashMap::default();
     perft_recurse(&mut map, board.clone(), depth)
@@ -24,7 +28,8 @@ fn perft_recurse<B: Board + Hash>(map: &mut HashMap<(B, u32), u64>, board: B, de
     if board.is_done() {
         return 0;
     }
-    if depth == 1 {
+    let is_chance = board.is_chance_node();
+    if depth == 1 && !is_chance {
         return board.available_moves().unwrap().count() as u64;
     }
 
@@ -36,15 +41,98 @@ fn perft_recurse<B: Board + Hash>(map: &mut HashMap<(B, u32), u64>, board: B, de
         return p;
     }
 
+    let child_depth = if is_chance { depth } else { depth - 1 };
     let mut p = 0;
     board.children().unwrap().for_each(|(_, child)| {
-        p += perft_recurse(map, child, depth - 1);
+        p += perft_recurse(map, child, child_depth);
     });
 
     map.insert(key, p);
     p
 }
 
+/// Same as [perft] but caches visited boards by their [ZobristBoard::zobrist] key instead of the full board.
+/// This is a lot cheaper, but symmetries are not taken into account and the result can be wrong if keys collide.
+pub fn perft_zobrist<B: ZobristBoard>(board: &B, depth: u32) -> u64 {
+    let mut map = HashMap::default();
+    perft_zobrist_recurse(&mut map, &mut board.clone(), depth)
+}
+
+fn perft_zobrist_recurse<B: ZobristBoard>(map: &mut HashMap<(B::Key, u32), u64>, board: &mut B, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if board.is_done() {
+        return 0;
+    }
+    let is_chance = board.is_chance_node();
+    if depth == 1 && !is_chance {
+        return board.available_moves().unwrap().count() as u64;
+    }
+
+    let key = (board.zobrist(), depth);
+    if let Some(&p) = map.get(&key) {
+        return p;
+    }
+
+    let child_depth = if is_chance { depth } else { depth - 1 };
+    let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+    let mut p = 0;
+    for mv in moves {
+        p += board
+            .play_scoped(mv, |child| perft_zobrist_recurse(map, child, child_depth))
+            .unwrap();
+    }
+
+    map.insert(key, p);
+    p
+}
+
+/// Same as [perft_zobrist] but caches perft values in a fixed-size [TranspositionTable] instead of a map,
+/// so the memory usage stays bounded for large depths.
+/// The table can be reused between calls for the same game.
+pub fn perft_tt<B: ZobristBoard>(board: &B, depth: u32, tt: &TranspositionTable<B::Move, u64>) -> u64 {
+    perft_tt_recurse(tt, &mut board.clone(), depth)
+}
+
+fn perft_tt_recurse<B: ZobristBoard>(tt: &TranspositionTable<B::Move, u64>, board: &mut B, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if board.is_done() {
+        return 0;
+    }
+    let is_chance = board.is_chance_node();
+    if depth == 1 && !is_chance {
+        return board.available_moves().unwrap().count() as u64;
+    }
+
+    let key = board.zobrist().to_u64();
+    if let Some(entry) = tt.get(key) {
+        if entry.depth == depth {
+            return entry.value;
+        }
+    }
+
+    let child_depth = if is_chance { depth } else { depth - 1 };
+    let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+    let mut p = 0;
+    for mv in moves {
+        p += board
+            .play_scoped(mv, |child| perft_tt_recurse(tt, child, child_depth))
+            .unwrap();
+    }
+
+    let entry = TTEntry {
+        depth,
+        value: p,
+        bound: Bound::Exact,
+        best_move: None,
+    };
+    tt.insert(key, entry);
+    p
+}
+
 /// Same as [perft] but without any caching of perft values for visited boards.
 pub fn perft_naive<B: Board>(board: &B, depth: u32) -> u64 {
     if depth == 0 {
@@ -53,13 +141,15 @@ pub fn perft_naive<B: Board>(board: &B, depth: u32) -> u64 {
     if board.is_done() {
         return 0;
     }
-    if depth == 1 {
+    let is_chance = board.is_chance_node();
+    if depth == 1 && !is_chance {
         return board.available_moves().unwrap().count() as u64;
     }
 
+    let child_depth = if is_chance { depth } else { depth - 1 };
     let mut p = 0;
     board.available_moves().unwrap().for_each(|mv: B::Move| {
-        p += perft_naive(&board.clone_and_play(mv).unwrap(), depth - 1);
+        p += perft_naive(&board.clone_and_play(mv).unwrap(), child_depth);
     });
     p
 }
@@ -73,7 +163,10 @@ pub struct GameStats {
 }
 
 /// Return `GameStats` estimated from `n` games starting from `start` played by `bot`.
+/// Chance nodes are resolved randomly instead of asking the bot, see [Board::is_chance_node].
 pub fn average_game_stats<B: Board>(mut start: impl FnMut() -> B, mut bot: impl Bot<B>, n: u64) -> GameStats {
+    let mut rng = rand::thread_rng();
+
     let mut total_moves = 0;
     let mut total_positions = 0;
     let mut total_wdl_a = WDL::default();
@@ -85,7 +178,12 @@ pub fn average_game_stats<B: Board>(mut start: impl FnMut() -> B, mut bot: impl
             total_moves += board.available_moves().unwrap().count();
             total_positions += 1;
 
-            board.play(bot.select_move(&board).unwrap()).unwrap();
+            let mv = if board.is_chance_node() {
+                board.random_available_move(&mut rng).unwrap()
+            } else {
+                bot.select_move(&board).unwrap()
+            };
+            board.play(mv).unwrap();
 
             if let Some(outcome) = board.outcome() {
                 break outcome;
@@ -109,32 +207,30 @@ pub fn average_game_stats<B: Board>(mut start: impl FnMut() -> B, mut bot: impl
 pub fn all_possible_boards<B: Board + Hash>(start: &B, depth: u32, include_done: bool) -> Vec<B> {
     let mut set = HashSet::new();
     let mut result = vec![];
-    all_possible_boards_impl(start, depth, include_done, &mut result, &mut set);
-    result
-}
 
-fn all_possible_boards_impl<B: Board + Hash>(
-    start: &B,
-    depth: u32,
-    include_done: bool,
-    result: &mut Vec<B>,
-    set: &mut HashSet<B>,
-) {
-    if !include_done && start.is_done() {
-        return;
-    }
-    if !set.insert(start.clone()) {
-        return;
-    }
-    result.push(start.clone());
-    if start.is_done() || depth == 0 {
-        return;
+    // depth-first with an explicit stack, games can be too long to recurse
+    let mut stack = vec![(start.clone(), depth)];
+    let mut children = vec![];
+
+    while let Some((board, depth)) = stack.pop() {
+        if !include_done && board.is_done() {
+            continue;
+        }
+        if !set.insert(board.clone()) {
+            continue;
+        }
+        result.push(board.clone());
+        if board.is_done() || depth == 0 {
+            continue;
+        }
+
+        // push in reverse so the first child is visited first
+        children.clear();
+        board.children().unwrap().for_each(|(_, child)| children.push(child));
+        stack.extend(children.drain(..).rev().map(|child| (child, depth - 1)));
     }
 
-    start
-        .children()
-        .unwrap()
-        .for_each(|(_, child)| all_possible_boards_impl(&child, depth - 1, include_done, result, set))
+    result
 }
 
 /// Collect all available moves form `n` games played until the end with random moves.
@@ -0,0 +1,163 @@
+//! A fixed-size [transposition table](https://www.chessprogramming.org/Transposition_Table),
+//! caching search results keyed by a board hash, typically [ZobristBoard::zobrist](crate::board::ZobristBoard::zobrist).
+//!
+//! The table is split into shards that are each protected by their own lock,
+//! so it can be shared between threads that search at the same time.
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+/// How the value of an entry relates to the true value of the position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Bound {
+    /// The value is exact.
+    Exact,
+    /// The true value is at least the stored value, the search failed high.
+    Lower,
+    /// The true value is at most the stored value, the search failed low.
+    Upper,
+}
+
+/// A single search result stored in a [TranspositionTable].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TTEntry<M, V> {
+    /// The remaining depth the position was searched to.
+    pub depth: u32,
+    pub value: V,
+    pub bound: Bound,
+    /// The best move found, if any. Even a move from a shallower search is useful for move ordering.
+    pub best_move: Option<M>,
+}
+
+/// Decides whether a new entry overwrites the existing entry in the same slot.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Replacement {
+    /// Always overwrite the existing entry.
+    Always,
+    /// Only overwrite entries that were searched less deep than the new entry,
+    /// or that were stored before the last call to [TranspositionTable::new_generation].
+    DepthPreferred,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Slot<M, V> {
+    key: u64,
+    generation: u32,
+    entry: TTEntry<M, V>,
+}
+
+type Shard<M, V> = Mutex<Vec<Option<Slot<M, V>>>>;
+
+/// A fixed-size transposition table mapping 64-bit keys to [TTEntry]s.
+///
+/// Each key maps to a single slot, collisions are resolved using the [Replacement] policy.
+/// The full key is stored in the slot, so lookups only return entries for the exact same key.
+pub struct TranspositionTable<M, V> {
+    shards: Vec<Shard<M, V>>,
+    shard_size: usize,
+    replacement: Replacement,
+    generation: AtomicU32,
+}
+
+impl<M: Copy, V: Copy> TranspositionTable<M, V> {
+    /// The maximum number of shards, each shard has its own lock.
+    const MAX_SHARDS: usize = 64;
+
+    /// Create a new table with space for (at least) `capacity` entries.
+    pub fn new(capacity: usize, replacement: Replacement) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+
+        let shard_count = capacity.min(Self::MAX_SHARDS);
+        let shard_size = capacity.div_ceil(shard_count);
+        let shards = (0..shard_count).map(|_| Mutex::new(vec![None; shard_size])).collect();
+
+        TranspositionTable {
+            shards,
+            shard_size,
+            replacement,
+            generation: AtomicU32::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shards.len() * self.shard_size
+    }
+
+    pub fn replacement(&self) -> Replacement {
+        self.replacement
+    }
+
+    /// The number of slots that are currently filled.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().iter().filter(|s| s.is_some()).count())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().iter_mut().for_each(|slot| *slot = None);
+        }
+    }
+
+    /// Mark all current entries as old, so they can be replaced by [Replacement::DepthPreferred].
+    /// They can still be returned by [TranspositionTable::get] until then.
+    /// Typically called before starting a search on a new root position.
+    pub fn new_generation(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the entry for `key`, if there is one.
+    pub fn get(&self, key: u64) -> Option<TTEntry<M, V>> {
+        let (shard, index) = self.location(key);
+        let shard = self.shards[shard].lock().unwrap();
+
+        match shard[index] {
+            Some(slot) if slot.key == key => Some(slot.entry),
+            _ => None,
+        }
+    }
+
+    /// Store `entry` for `key`, depending on the replacement policy.
+    pub fn insert(&self, key: u64, entry: TTEntry<M, V>) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let (shard, index) = self.location(key);
+        let mut shard = self.shards[shard].lock().unwrap();
+        let slot = &mut shard[index];
+
+        let replace = match (self.replacement, &*slot) {
+            (Replacement::Always, _) | (_, None) => true,
+            (Replacement::DepthPreferred, Some(old)) => {
+                old.key == key || old.generation != generation || entry.depth >= old.entry.depth
+            }
+        };
+
+        if replace {
+            *slot = Some(Slot { key, generation, entry });
+        }
+    }
+
+    fn location(&self, key: u64) -> (usize, usize) {
+        // use different bits for the shard and the index, the keys are assumed to be well mixed
+        let shard = (key % self.shards.len() as u64) as usize;
+        let index = ((key >> 32) % self.shard_size as u64) as usize;
+        (shard, index)
+    }
+}
+
+impl<M, V> Debug for TranspositionTable<M, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("shards", &self.shards.len())
+            .field("shard_size", &self.shard_size)
+            .field("replacement", &self.replacement)
+            .field("generation", &self.generation.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
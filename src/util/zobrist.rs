@@ -0,0 +1,57 @@
+//! Utilities for [Zobrist hashing](https://www.chessprogramming.org/Zobrist_Hashing), see [ZobristBoard](crate::board::ZobristBoard).
+//!
+//! The random values are generated at compile time from a fixed seed with a [SplitMix64](https://prng.di.unimi.it/splitmix64.c)
+//! generator, so keys are consistent between runs and platforms.
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitXor, BitXorAssign};
+
+/// The key type returned by [ZobristBoard::zobrist](crate::board::ZobristBoard::zobrist).
+pub trait ZobristKey:
+    'static + Debug + Copy + Eq + Hash + Default + Send + Sync + BitXor<Output = Self> + BitXorAssign
+{
+    /// Fold this key into 64 bits, for example to index into a table.
+    fn to_u64(self) -> u64;
+}
+
+impl ZobristKey for u64 {
+    fn to_u64(self) -> u64 {
+        self
+    }
+}
+
+impl ZobristKey for u128 {
+    fn to_u64(self) -> u64 {
+        (self as u64) ^ ((self >> 64) as u64)
+    }
+}
+
+/// The [SplitMix64](https://prng.di.unimi.it/splitmix64.c) finalizer.
+/// This is a bijection, so it can also be used to turn an existing perfect hash into a well distributed key.
+pub const fn mix64(x: u64) -> u64 {
+    let mut z = x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// The random value at position `index` in the stream identified by `seed`.
+/// Useful for games where the number of required values is not known up front.
+pub const fn zobrist_value(seed: u64, index: u64) -> u64 {
+    mix64(
+        seed.wrapping_mul(0x9e3779b97f4a7c15)
+            .wrapping_add(index.wrapping_add(1).wrapping_mul(0x2545f4914f6cdd1d)),
+    )
+}
+
+/// A table of `N` random values for the stream `seed`, intended to be stored in a `const`.
+/// Entry `i` is equal to `zobrist_value(seed, i)`.
+pub const fn zobrist_table<const N: usize>(seed: u64) -> [u64; N] {
+    let mut table = [0; N];
+    let mut i = 0;
+    while i < N {
+        table[i] = zobrist_value(seed, i as u64);
+        i += 1;
+    }
+    table
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "game_pig")]
+
+use std::cmp::Ordering;
+
+use board_game::ai::mcts::mcts_build_tree;
+use board_game::ai::minimax::{minimax, minimax_value, Heuristic};
+use board_game::ai::simple::RandomBot;
+use board_game::ai::solver::{solve, SolverValue};
+use board_game::board::Board;
+use board_game::games::pig::{Move, PigBoard};
+use board_game::heuristic::pig::PigScoreHeuristic;
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::game_stats::average_game_stats;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+
+/// A board where player A can win immediately by holding.
+fn winning_board() -> PigBoard {
+    board_with_moves(PigBoard::new(10), &[Move::Roll, Move::Die(6), Move::Roll, Move::Die(5)])
+}
+
+#[test]
+fn minimax_hold_win() {
+    let board = winning_board();
+    let result = minimax(&board, &PigScoreHeuristic, 3, &mut consistent_rng());
+    assert_eq!(result.best_move, Some(Move::Hold));
+}
+
+#[test]
+fn solver_hold_win() {
+    let board = winning_board();
+    let result = solve(&board, 3, &mut consistent_rng());
+    assert_eq!(result.value, SolverValue::WinIn(1));
+    assert_eq!(result.best_move, Some(Move::Hold));
+}
+
+#[test]
+fn expectiminimax_chance_root() {
+    let board = board_with_moves(PigBoard::new(10), &[Move::Roll]);
+    assert!(board.is_chance_node());
+
+    // rolling a one passes the turn with both scores still zero, anything else adds the rolled value
+    let expected = (2 + 3 + 4 + 5 + 6) as f32 / 6.0;
+    let value = minimax_value(&board, &PigScoreHeuristic, 0);
+    assert!((value - expected).abs() < 1e-4, "expected {}, got {}", expected, value);
+
+    let result = minimax(&board, &PigScoreHeuristic, 2, &mut consistent_rng());
+    assert_eq!(result.best_move, None);
+}
+
+/// Heuristic that doesn't implement [Heuristic::expectation] itself.
+#[derive(Debug)]
+struct TurnTotalHeuristic;
+
+impl Heuristic<PigBoard> for TurnTotalHeuristic {
+    type V = i32;
+
+    fn value(&self, board: &PigBoard, _: u32) -> Self::V {
+        board.turn_total() as i32
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        (old.max(new), new.cmp(&old))
+    }
+}
+
+#[test]
+fn default_expectation_most_likely() {
+    let children = [(1, 0.25), (5, 0.5), (3, 0.25)];
+    assert_eq!(5, TurnTotalHeuristic::expectation(&children));
+
+    // all rolls are equally likely, so the first one is used
+    let board = board_with_moves(PigBoard::new(10), &[Move::Roll]);
+    assert_eq!(0, minimax_value(&board, &TurnTotalHeuristic, 1));
+}
+
+#[test]
+fn mcts_hold_win() {
+    let board = winning_board();
+    let tree = mcts_build_tree(&board, 1000, 2.0, &mut consistent_rng());
+    assert_eq!(tree.best_move(), Move::Hold);
+}
+
+#[test]
+fn mcts_chance_root() {
+    let board = board_with_moves(PigBoard::new(10), &[Move::Roll]);
+    let tree = mcts_build_tree(&board, 1000, 2.0, &mut consistent_rng());
+
+    // the outcome depends on the dice, so the root can't be solved
+    assert_eq!(tree[0].solution(), None);
+
+    let wdl = tree.wdl();
+    assert!(wdl.win > 0.0 && wdl.loss > 0.0, "unexpected wdl {:?}", wdl);
+}
+
+#[test]
+fn mcts_proven_chance() {
+    // all chance outcomes lead to positions where A can win by holding
+    let board = board_with_moves(
+        PigBoard::new(10),
+        &[Move::Roll, Move::Die(6), Move::Roll, Move::Die(6), Move::Roll],
+    );
+    assert!(board.is_chance_node());
+    let tree = mcts_build_tree(&board, 1000, 2.0, &mut consistent_rng());
+
+    assert_eq!(tree[0].solution(), None);
+    let solved_children = tree[0]
+        .children
+        .unwrap()
+        .iter()
+        .filter(|&c| tree[c].solution() == Some(OutcomeWDL::Win))
+        .count();
+    assert_eq!(solved_children, 5, "all non-one outcomes should be proven wins for A");
+}
+
+#[test]
+fn random_games() {
+    let stats = average_game_stats(|| PigBoard::new(20), RandomBot::new(consistent_rng()), 100);
+    assert_eq!(stats.total_wdl_a.sum(), 100);
+    assert_eq!(stats.total_wdl_a.draw, 0);
+    assert!(stats.game_length > 0.0);
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "game_connect4")]
+
+use board_game::ai::simple::RandomBot;
+use board_game::ai::solver::{solve_value, SolverValue};
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardMoves};
+use board_game::games::connect4::solver::{score_to_value, Connect4Solver, Connect4SolverBot, OpeningBook};
+use board_game::games::connect4::Connect4;
+use board_game::pov::NonPov;
+use board_game::util::board_gen::{board_with_moves, random_board_with_moves};
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+use internal_iterator::InternalIterator;
+
+fn random_endgames(moves: u32, count: usize) -> Vec<Connect4> {
+    let mut rng = consistent_rng();
+    let mut boards = vec![];
+    while boards.len() < count {
+        let board = random_board_with_moves(&Connect4::default(), moves, &mut rng);
+        if !board.is_done() {
+            boards.push(board);
+        }
+    }
+    boards
+}
+
+#[test]
+fn endgame_matches_minimax() {
+    let mut solver = Connect4Solver::new(1 << 16);
+
+    for board in random_endgames(34, 50) {
+        let expected = solve_value(&board, 20);
+
+        assert_eq!(expected, solver.solve_value(&board).unwrap(), "{}", board);
+        assert_eq!(
+            expected.to_outcome_wdl().unwrap(),
+            solver.solve_outcome(&board).unwrap(),
+            "{}",
+            board
+        );
+    }
+}
+
+#[test]
+fn all_moves_consistent() {
+    let mut solver = Connect4Solver::new(1 << 16);
+
+    for board in random_endgames(24, 20) {
+        let score = solver.solve(&board).unwrap();
+        let moves = solver.solve_all_moves(&board).unwrap();
+        assert_eq!(score, moves.iter().map(|&(_, s)| s).max().unwrap(), "{}", board);
+
+        for &(mv, move_score) in &moves {
+            let child = board.clone_and_play(mv).unwrap();
+            let expected = match child.outcome() {
+                Some(outcome) => outcome.pov(board.next_player()).sign::<i32>(),
+                None => -solver.solve(&child).unwrap().signum(),
+            };
+            assert_eq!(expected, move_score.signum(), "{} {}", board, mv);
+        }
+
+        let best = solver.best_move(&board).unwrap();
+        assert!(moves.contains(&(best, score)));
+    }
+}
+
+#[test]
+fn score_conversion() {
+    // a single move away from a vertical win for either player
+    let board = board_with_moves(Connect4::default(), &[0, 1, 0, 1, 0, 1]);
+    let mut solver = Connect4Solver::new(1 << 10);
+    let score = solver.solve(&board).unwrap();
+    assert_eq!((Connect4::TILES as i32 + 1 - 6) / 2, score);
+    assert_eq!(SolverValue::WinIn(1), score_to_value(&board, score));
+
+    let board = board_with_moves(Connect4::default(), &[0, 1, 0, 1, 0, 2]);
+    let score = solver.solve(&board).unwrap();
+    assert!(score > 0);
+    assert_eq!(SolverValue::WinIn(1), score_to_value(&board, score));
+
+    // the next player can't prevent both threats
+    let board = board_with_moves(Connect4::default(), &[1, 1, 2, 2, 3]);
+    let score = solver.solve(&board).unwrap();
+    assert_eq!(SolverValue::LossIn(2), score_to_value(&board, score));
+}
+
+#[test]
+fn book_matches_search() {
+    let root = random_endgames(26, 1).remove(0);
+    let max_moves = root.game_length() + 3;
+
+    let mut solver = Connect4Solver::new(1 << 16);
+    let book = OpeningBook::generate(&mut solver, &root, max_moves);
+    assert!(!book.is_empty());
+    assert_eq!(Some(solver.solve(&root).unwrap()), book.get(&root));
+
+    let mut buffer = vec![];
+    book.write(&mut buffer).unwrap();
+    let read = OpeningBook::read(&buffer[..]).unwrap();
+    assert_eq!(book.len(), read.len());
+    assert_eq!(book.max_moves(), read.max_moves());
+    assert!(OpeningBook::read(&buffer[1..]).is_err());
+
+    let mut book_solver = Connect4Solver::new(1 << 16).with_book(read);
+    let mut plain_solver = Connect4Solver::new(1 << 16);
+
+    let mut rng = consistent_rng();
+    for _ in 0..20 {
+        let board = random_board_with_moves(&root, 2, &mut rng);
+        if board.is_done() {
+            continue;
+        }
+
+        let expected = plain_solver.solve(&board).unwrap();
+        assert_eq!(expected, book_solver.solve(&board).unwrap(), "{}", board);
+        assert_eq!(Some(expected), book.get(&board), "{}", board);
+    }
+    assert!(book_solver.nodes() < plain_solver.nodes());
+}
+
+#[test]
+fn bot_keeps_win() {
+    let mut solver = Connect4Solver::new(1 << 16);
+    let start = random_endgames(20, 20)
+        .into_iter()
+        .find(|board| solver.solve_outcome(board).unwrap() == OutcomeWDL::Win)
+        .unwrap();
+
+    let mut bot = Connect4SolverBot::new(solver);
+    let mut random = RandomBot::new(consistent_rng());
+    let player = start.next_player();
+
+    for _ in 0..5 {
+        let mut board = start.clone();
+        while !board.is_done() {
+            let mv = if board.next_player() == player {
+                bot.select_move(&board).unwrap()
+            } else {
+                random.select_move(&board).unwrap()
+            };
+            board.play(mv).unwrap();
+        }
+        assert_eq!(OutcomeWDL::Win, board.outcome().unwrap().pov(player));
+    }
+}
+
+#[test]
+fn book_near_start() {
+    // positions just past the start book are too slow to solve here, but a book for a later one is quick to generate
+    let root = board_with_moves(Connect4::default(), &[3, 3, 3, 3, 2, 4, 4, 2, 2, 4]);
+    let mut plain_solver = Connect4Solver::new(1 << 20);
+    let expected = plain_solver.solve(&root).unwrap();
+
+    let mut solver = Connect4Solver::new(1 << 20);
+    let book = OpeningBook::generate(&mut solver, &root, root.game_length() + 1);
+    assert_eq!(Some(expected), book.get(&root));
+
+    // the root and all of its children are in the book, so barely any search is needed
+    let mut book_solver = Connect4Solver::new(1 << 10).with_book(book);
+    assert_eq!(expected, book_solver.solve(&root).unwrap());
+    let best = book_solver.best_move(&root).unwrap();
+    let child = root.clone_and_play(best).unwrap();
+    assert_eq!(-expected, book_solver.solve(&child).unwrap());
+    assert!(book_solver.nodes() < 100, "{} nodes", book_solver.nodes());
+    assert!(plain_solver.nodes() > 1000);
+}
+
+#[test]
+fn start_book() {
+    let book = OpeningBook::start();
+    assert_eq!(2, book.max_moves());
+
+    // the scores of the first two layers follow from the layer below them
+    fn check(book: &OpeningBook, board: &Connect4) -> i32 {
+        let score = book.get(board).unwrap();
+        if board.game_length() < book.max_moves() {
+            let best = board
+                .available_moves()
+                .unwrap()
+                .map(|mv| -check(book, &board.clone_and_play(mv).unwrap()))
+                .max()
+                .unwrap();
+            assert_eq!(best, score, "{}", board);
+        }
+        score
+    }
+    assert_eq!(1, check(&book, &Connect4::default()));
+
+    let child = board_with_moves(Connect4::default(), &[1]);
+    assert_eq!(book.get(&child), book.get(&board_with_moves(Connect4::default(), &[5])));
+    assert_eq!(None, book.get(&board_with_moves(Connect4::default(), &[3, 3, 3])));
+}
+
+/// Only quick with optimizations, even with the book.
+#[test]
+#[cfg(not(debug_assertions))]
+fn solve_start() {
+    // the first player wins with their last stone, but only by starting in the center
+    let board = Connect4::default();
+    let mut solver = Connect4Solver::new(1 << 20).with_book(OpeningBook::start());
+
+    assert_eq!(OutcomeWDL::Win, solver.solve_outcome(&board).unwrap());
+    assert_eq!(SolverValue::WinIn(41), solver.solve_value(&board).unwrap());
+    assert_eq!(3, solver.best_move(&board).unwrap());
+
+    let moves = solver.solve_all_moves(&board).unwrap();
+    assert_eq!(1, moves.iter().filter(|&&(_, score)| score > 0).count());
+}
+
+/// Too slow without optimizations, run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn center_opening() {
+    // after both players start in the center the first player still wins, with their last stone
+    let board = board_with_moves(Connect4::default(), &[3, 3]);
+    let mut solver = Connect4Solver::new(1 << 24);
+
+    assert_eq!(OutcomeWDL::Win, solver.solve_outcome(&board).unwrap());
+    assert_eq!(1, solver.solve(&board).unwrap());
+    assert_eq!(SolverValue::WinIn(39), solver.solve_value(&board).unwrap());
+    assert_eq!(Some(1), OpeningBook::start().get(&board));
+}
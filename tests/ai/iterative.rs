@@ -0,0 +1,185 @@
+#![cfg(all(feature = "game_ataxx", feature = "game_chess", feature = "game_ttt"))]
+
+use std::time::{Duration, Instant};
+
+use board_game::ai::budget::{SearchBudget, StopFlag};
+use board_game::ai::minimax::{
+    minimax_iterative, minimax_iterative_tt, minimax_value, IterativeMiniMaxBot, MoveOrdering,
+};
+use board_game::ai::solver::SolverHeuristic;
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::util::tiny::consistent_rng;
+use board_game::util::tt::{Replacement, TranspositionTable};
+use board_game::wdl::OutcomeWDL;
+
+#[test]
+fn iterative_matches_fixed_depth() {
+    let heuristic = AtaxxTileHeuristic::default();
+    let tt = TranspositionTable::new(4096, Replacement::DepthPreferred);
+    let mut rng = consistent_rng();
+
+    let mut board = AtaxxBoard::diagonal(5);
+    for _ in 0..10 {
+        if board.is_done() {
+            break;
+        }
+
+        for max_depth in 1..4 {
+            let expected = minimax_value(&board, &heuristic, max_depth);
+            let budget = SearchBudget::unlimited();
+
+            for ordering in [MoveOrdering::NONE, MoveOrdering::ALL] {
+                let result = minimax_iterative(&board, &heuristic, max_depth, &budget, ordering, &mut rng);
+                assert_eq!(max_depth, result.depth);
+                assert_eq!(expected, result.value);
+                assert!(board.is_available_move(result.best_move.unwrap()).unwrap());
+                assert_eq!(result.best_move, result.pv.first().copied());
+
+                tt.new_generation();
+                let result = minimax_iterative_tt(&board, &heuristic, max_depth, &budget, ordering, &tt, &mut rng);
+                assert_eq!(max_depth, result.depth);
+                assert_eq!(expected, result.value);
+            }
+        }
+
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+}
+
+#[test]
+fn iterative_stops_when_solved() {
+    let result = minimax_iterative(
+        &TTTBoard::default(),
+        &SolverHeuristic,
+        100,
+        &SearchBudget::unlimited(),
+        MoveOrdering::ALL,
+        &mut consistent_rng(),
+    );
+
+    // the full game tree has depth 9
+    assert_eq!(9, result.depth);
+    assert_eq!(Some(OutcomeWDL::Draw), result.value.to_outcome_wdl());
+}
+
+#[test]
+fn ordering_reduces_nodes() {
+    let budget = SearchBudget::unlimited();
+
+    let board = ChessBoard::default();
+    let heuristic = ChessPieceValueHeuristic;
+    let none = minimax_iterative(
+        &board,
+        &heuristic,
+        4,
+        &budget,
+        MoveOrdering::NONE,
+        &mut consistent_rng(),
+    );
+    let all = minimax_iterative(&board, &heuristic, 4, &budget, MoveOrdering::ALL, &mut consistent_rng());
+    assert_eq!(none.value, all.value);
+    assert!(all.nodes < none.nodes, "{} >= {}", all.nodes, none.nodes);
+
+    let board = AtaxxBoard::default();
+    let heuristic = AtaxxTileHeuristic::default();
+    let none = minimax_iterative(
+        &board,
+        &heuristic,
+        3,
+        &budget,
+        MoveOrdering::NONE,
+        &mut consistent_rng(),
+    );
+    let all = minimax_iterative(&board, &heuristic, 3, &budget, MoveOrdering::ALL, &mut consistent_rng());
+    assert_eq!(none.value, all.value);
+    assert!(all.nodes < none.nodes, "{} >= {}", all.nodes, none.nodes);
+}
+
+#[test]
+fn iterative_node_budget() {
+    let board = ChessBoard::default();
+    let budget = SearchBudget::nodes(2000);
+    let result = minimax_iterative(
+        &board,
+        &ChessPieceValueHeuristic,
+        100,
+        &budget,
+        MoveOrdering::ALL,
+        &mut consistent_rng(),
+    );
+
+    assert!(result.depth >= 1 && result.depth < 100);
+    assert!(board.is_available_move(result.best_move.unwrap()).unwrap());
+    assert!(result.nodes <= 2000, "visited {} nodes", result.nodes);
+}
+
+#[test]
+fn iterative_stop_flag() {
+    let board = ChessBoard::default();
+
+    // already stopped, only the first iteration runs
+    let stop = StopFlag::new();
+    stop.stop();
+    let budget = SearchBudget::unlimited().with_stop(stop.clone());
+    let result = minimax_iterative(
+        &board,
+        &ChessPieceValueHeuristic,
+        100,
+        &budget,
+        MoveOrdering::ALL,
+        &mut consistent_rng(),
+    );
+    assert_eq!(1, result.depth);
+    assert!(result.best_move.is_some());
+
+    // stopped from another thread
+    stop.reset();
+    let start = Instant::now();
+    let stopper = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            stop.stop();
+        })
+    };
+    let result = minimax_iterative(
+        &board,
+        &ChessPieceValueHeuristic,
+        100,
+        &budget,
+        MoveOrdering::ALL,
+        &mut consistent_rng(),
+    );
+    stopper.join().unwrap();
+
+    assert!(result.depth < 100);
+    assert!(result.best_move.is_some());
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn iterative_bot() {
+    let mut bot = IterativeMiniMaxBot::new(
+        100,
+        SearchBudget::time(Duration::from_millis(20)),
+        MoveOrdering::ALL,
+        AtaxxTileHeuristic::default(),
+        consistent_rng(),
+    );
+
+    let mut rng = consistent_rng();
+    let mut board = AtaxxBoard::diagonal(5);
+    while !board.is_done() {
+        let mv = bot.select_move(&board).unwrap();
+        board.play(mv).unwrap();
+        if !board.is_done() {
+            board.play_random_available_move(&mut rng).unwrap();
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(all(feature = "game_ataxx", feature = "game_chess"))]
+
+use std::time::Duration;
+
+use board_game::ai::budget::SearchBudget;
+use board_game::ai::minimax::{minimax_lazy_smp, minimax_value, LazySmpBot, MoveOrdering};
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::util::tiny::consistent_rng;
+use board_game::util::tt::{Replacement, TranspositionTable};
+
+#[test]
+fn lazy_smp_matches_fixed_depth() {
+    let heuristic = AtaxxTileHeuristic::default();
+    let tt = TranspositionTable::new(1 << 16, Replacement::DepthPreferred);
+    let budget = SearchBudget::unlimited();
+    let mut rng = consistent_rng();
+
+    let mut board = AtaxxBoard::diagonal(5);
+    for _ in 0..6 {
+        if board.is_done() {
+            break;
+        }
+
+        for threads in [1, 4] {
+            let depth = 3;
+            tt.new_generation();
+            let result = minimax_lazy_smp(
+                &board,
+                &heuristic,
+                depth,
+                &budget,
+                MoveOrdering::ALL,
+                &tt,
+                threads,
+                &mut rng,
+            );
+
+            assert_eq!(depth, result.depth);
+            assert_eq!(minimax_value(&board, &heuristic, depth), result.value);
+            assert!(board.is_available_move(result.best_move.unwrap()).unwrap());
+        }
+
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+}
+
+#[test]
+fn lazy_smp_time_budget() {
+    let board = ChessBoard::default();
+    let tt = TranspositionTable::new(1 << 16, Replacement::DepthPreferred);
+    let budget = SearchBudget::time(Duration::from_millis(50));
+
+    let result = minimax_lazy_smp(
+        &board,
+        &ChessPieceValueHeuristic,
+        100,
+        &budget,
+        MoveOrdering::ALL,
+        &tt,
+        4,
+        &mut consistent_rng(),
+    );
+
+    assert!(result.depth >= 1 && result.depth < 100);
+    assert!(board.is_available_move(result.best_move.unwrap()).unwrap());
+}
+
+#[test]
+fn lazy_smp_bot() {
+    let mut bot = LazySmpBot::new(
+        4,
+        100,
+        SearchBudget::time(Duration::from_millis(10)),
+        MoveOrdering::ALL,
+        1 << 14,
+        AtaxxTileHeuristic::default(),
+        consistent_rng(),
+    );
+
+    let mut rng = consistent_rng();
+    let mut board = AtaxxBoard::diagonal(5);
+    while !board.is_done() {
+        let mv = bot.select_move(&board).unwrap();
+        board.play(mv).unwrap();
+        if !board.is_done() {
+            board.play_random_available_move(&mut rng).unwrap();
+        }
+    }
+}
@@ -0,0 +1,149 @@
+#![cfg(all(feature = "game_ttt", feature = "game_pig"))]
+
+use internal_iterator::InternalIterator;
+
+use board_game::ai::mcts::{mcts_build_tree, mcts_expand_tree, MCTSBot, Tree};
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardMoves};
+use board_game::games::pig::{Move, PigBoard};
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::Pov;
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::WDL;
+
+#[test]
+fn reroot_ttt() {
+    let board = TTTBoard::default();
+    let mut tree = mcts_build_tree(&board, 1000, 2.0, &mut consistent_rng());
+
+    let mv = tree.best_move();
+    let child = tree.best_child();
+    let reply_child = tree[child]
+        .children
+        .unwrap()
+        .iter()
+        .max_by_key(|&c| tree[c].visits)
+        .unwrap();
+    let reply = tree[reply_child].last_move.unwrap();
+
+    let expected_visits = tree[reply_child].visits;
+    let expected_wdl = tree[reply_child].wdl();
+
+    let next_board = board_with_moves(board, &[mv, reply]);
+    assert_eq!(tree.find_moves_to(&next_board, 2), Some(vec![mv, reply]));
+    assert_eq!(tree.find_moves_to(&next_board, 1), None);
+
+    assert!(tree.reroot(&[mv, reply]));
+    assert_eq!(next_board, tree.root_board);
+    assert_eq!(expected_visits, tree[0].visits);
+    assert_eq!(None, tree[0].last_move);
+    assert_wdl_eq(expected_wdl.flip(), tree.wdl());
+    check_tree(&tree);
+
+    // the search can continue from the new root
+    mcts_expand_tree(&mut tree, 100, 2.0, &mut consistent_rng());
+    assert_eq!(expected_visits + 100, tree[0].visits);
+    assert!(next_board.is_available_move(tree.best_move()).unwrap());
+    check_tree(&tree);
+}
+
+#[test]
+fn reroot_unexpanded() {
+    let board = TTTBoard::default();
+    let mut tree = mcts_build_tree(&board, 2, 2.0, &mut consistent_rng());
+    let node_count = tree.nodes.len();
+
+    // the children of the root children have not been expanded yet
+    let mv = board.available_moves().unwrap().collect::<Vec<_>>()[0];
+    let child = board.clone_and_play(mv).unwrap();
+    let reply = child.available_moves().unwrap().collect::<Vec<_>>()[0];
+
+    assert!(!tree.reroot(&[mv, reply]));
+    assert_eq!(node_count, tree.nodes.len());
+    assert_eq!(board, tree.root_board);
+}
+
+#[test]
+fn reroot_chance_pov() {
+    // rolling does not change the next player, so the pov of the new root has to be flipped
+    let board = PigBoard::new(10);
+    let mut tree = mcts_build_tree(&board, 1000, 2.0, &mut consistent_rng());
+
+    let roll_child = tree[0]
+        .children
+        .unwrap()
+        .iter()
+        .find(|&c| tree[c].last_move == Some(Move::Roll))
+        .unwrap();
+    let child_wdl = tree[roll_child].wdl();
+
+    assert!(tree.reroot(&[Move::Roll]));
+    assert!(tree.root_board.is_chance_node());
+    assert_eq!(board.next_player(), tree.root_board.next_player());
+    assert_wdl_eq(child_wdl, tree.wdl());
+    check_tree(&tree);
+}
+
+#[test]
+fn bot_reuses_tree() {
+    let mut bot = MCTSBot::new(500, 2.0, consistent_rng()).with_tree_reuse(true);
+    let mut rng = consistent_rng();
+
+    let mut board = TTTBoard::default();
+    let mut moves = 0;
+    while !board.is_done() {
+        let prev_visits = bot.tree::<TTTBoard>().map_or(0, |tree| {
+            let moves = tree.find_moves_to(&board, 2).unwrap();
+            tree[tree.find_node(&moves).unwrap()].visits
+        });
+        if moves > 0 {
+            assert!(prev_visits > 0);
+        }
+
+        let mv = bot.select_move(&board).unwrap();
+        let tree = bot.tree::<TTTBoard>().unwrap();
+        assert_eq!(&board, &tree.root_board);
+        if tree[0].solution().is_none() {
+            assert_eq!(prev_visits + 500, tree[0].visits);
+        }
+
+        board.play(mv).unwrap();
+        if !board.is_done() {
+            board.play_random_available_move(&mut rng).unwrap();
+        }
+        moves += 1;
+    }
+}
+
+#[test]
+fn bot_without_reuse() {
+    // reuse is disabled by default
+    let mut bot = MCTSBot::new(100, 2.0, consistent_rng());
+    let board = TTTBoard::default();
+    bot.select_move(&board).unwrap();
+    assert!(bot.tree::<TTTBoard>().is_none());
+}
+
+/// Check that the children of all nodes are in bounds and that every node except the root has exactly one parent.
+fn check_tree<B: Board>(tree: &Tree<B>) {
+    let mut parent_count = vec![0; tree.nodes.len()];
+    for (i, node) in tree.nodes.iter().enumerate() {
+        if let Some(children) = node.children {
+            for child in children {
+                assert!(child > i && child < tree.nodes.len());
+                parent_count[child] += 1;
+            }
+        }
+    }
+
+    assert_eq!(0, parent_count[0]);
+    assert!(parent_count[1..].iter().all(|&c| c == 1));
+}
+
+fn assert_wdl_eq(expected: WDL<f32>, actual: WDL<f32>) {
+    let close = (expected.win - actual.win).abs() < 1e-6
+        && (expected.draw - actual.draw).abs() < 1e-6
+        && (expected.loss - actual.loss).abs() < 1e-6;
+    assert!(close, "expected {:?}, got {:?}", expected, actual);
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "game_ataxx")]
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use internal_iterator::InternalIterator;
+
+use board_game::ai::budget::{SearchBudget, StopFlag};
+use board_game::ai::mcts::{mcts_build_tree, mcts_build_tree_budget, mcts_expand_tree_budget, MCTSSearcher};
+use board_game::board::{Board, BoardMoves};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::interface::uai::client::{mcts_bot, run};
+use board_game::util::tiny::consistent_rng;
+
+#[test]
+fn budget_nodes() {
+    let board = AtaxxBoard::default();
+    let mut tree = mcts_build_tree(&board, 1, 2.0, &mut consistent_rng());
+
+    let iterations = mcts_expand_tree_budget(&mut tree, &SearchBudget::nodes(100), 2.0, &mut consistent_rng());
+    assert_eq!(100, iterations);
+    assert_eq!(101, tree[0].visits);
+}
+
+#[test]
+fn budget_stop_flag() {
+    let stop = StopFlag::new();
+    stop.stop();
+
+    // even a stopped search runs a single iteration so there is a move to play
+    let budget = SearchBudget::unlimited().with_stop(stop);
+    let tree = mcts_build_tree_budget(&AtaxxBoard::default(), &budget, 2.0, &mut consistent_rng());
+    assert_eq!(1, tree[0].visits);
+    assert!(tree[0].children.is_some());
+}
+
+#[test]
+fn budget_time() {
+    let start = Instant::now();
+    let budget = SearchBudget::time(Duration::from_millis(50));
+    let tree = mcts_build_tree_budget(&AtaxxBoard::default(), &budget, 2.0, &mut consistent_rng());
+
+    assert!(tree[0].visits > 0);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn searcher_ponders() {
+    let board = AtaxxBoard::default();
+    let mut searcher = MCTSSearcher::new(2.0, consistent_rng()).with_pondering(SearchBudget::nodes(10_000));
+
+    let mv = searcher.select_move_within(&board, &SearchBudget::nodes(100)).unwrap();
+    assert!(searcher.is_pondering());
+    assert!(searcher.tree().is_none());
+
+    sleep(Duration::from_millis(100));
+    searcher.stop_pondering();
+
+    let after_mv = board.clone_and_play(mv).unwrap();
+    let tree = searcher.tree().unwrap();
+    assert_eq!(after_mv, tree.root_board);
+    assert!(tree[0].visits > 1);
+
+    // the opponent plays the reply we spent the most time on
+    let reply = tree.best_move();
+    let reply_visits = tree[tree.best_child()].visits;
+    let after_reply = after_mv.clone_and_play(reply).unwrap();
+
+    let tree = searcher.search(&after_reply, &SearchBudget::nodes(10));
+    assert_eq!(after_reply, tree.root_board);
+    assert_eq!(reply_visits + 10, tree[0].visits);
+}
+
+#[test]
+fn searcher_does_not_ponder_done_board() {
+    // a single move away from filling the board
+    let board = AtaxxBoard::from_fen("xxxxxxx/xxxxxxx/xxxxxxx/xxxxxxx/xxxxxxx/xxxxxxx/xxxxxo1 x 0 1").unwrap();
+    let mut searcher = MCTSSearcher::new(2.0, consistent_rng()).with_pondering(SearchBudget::nodes(100));
+
+    let mv = searcher.select_move_within(&board, &SearchBudget::nodes(10)).unwrap();
+    assert!(board.clone_and_play(mv).unwrap().is_done());
+    assert!(!searcher.is_pondering());
+}
+
+#[test]
+fn uai_mcts_bot() {
+    let mut searcher = MCTSSearcher::new(2.0, consistent_rng()).with_pondering(SearchBudget::nodes(1000));
+
+    let input = "uai\nposition startpos\ngo movetime 20\ngo movetime 20\nquit\n";
+    let mut output = vec![];
+    run(
+        mcts_bot(&mut searcher),
+        "mcts",
+        "test",
+        input.as_bytes(),
+        &mut output,
+        std::io::sink(),
+    )
+    .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let best_moves: Vec<&str> = output.lines().filter(|l| l.starts_with("bestmove ")).collect();
+    assert_eq!(2, best_moves.len(), "output:\n{}", output);
+
+    let first_move = best_moves[0].strip_prefix("bestmove ").unwrap();
+    let board = AtaxxBoard::default();
+    let available: Vec<String> = board.available_moves().unwrap().map(|mv| mv.to_uai()).collect();
+    assert!(available.iter().any(|mv| mv == first_move));
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "game_ttt")]
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use board_game::ai::mcts::{
+    mcts_build_tree_with, DirichletNoise, FinalMove, Fpu, MCTSBot, MCTSConfig, Rave, RolloutEvaluator, Selection,
+};
+use board_game::ai::solver::{solve, SolverValue};
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::NonPov;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+
+fn configs() -> Vec<MCTSConfig> {
+    vec![
+        MCTSConfig::uct(2.0),
+        MCTSConfig {
+            selection: Selection::Ucb1Tuned,
+            ..MCTSConfig::uct(1.0)
+        },
+        MCTSConfig {
+            fpu: Fpu::Parent(0.1),
+            progressive_bias: 1.0,
+            ..MCTSConfig::uct(2.0)
+        },
+        MCTSConfig {
+            final_move: FinalMove::Lcb(1.0),
+            ..MCTSConfig::puct(2.0)
+        },
+        MCTSConfig {
+            final_move: FinalMove::Value,
+            root_noise: Some(DirichletNoise {
+                alpha: 0.3,
+                fraction: 0.25,
+            }),
+            ..MCTSConfig::puct(2.0)
+        },
+        MCTSConfig {
+            rave: Some(Rave { equivalence: 100.0 }),
+            ..MCTSConfig::uct(1.0)
+        },
+    ]
+}
+
+#[test]
+fn configs_find_wins() {
+    for config in configs() {
+        let mut rng = consistent_rng();
+        let mut bot = MCTSBot::new(500, 2.0, consistent_rng()).with_config(config);
+        let mut checked = 0;
+
+        while checked < 10 {
+            let board = random_board_with_moves(&TTTBoard::default(), rng.gen_range(4..7), &mut rng);
+            if board.is_done() || solve(&board, 1, &mut rng).value != SolverValue::WinIn(1) {
+                continue;
+            }
+
+            let after = board.clone_and_play(bot.select_move(&board).unwrap()).unwrap();
+            assert_eq!(
+                Some(OutcomeWDL::Win),
+                after.outcome().pov(board.next_player()),
+                "{:?} missed win on board\n{}",
+                config,
+                board
+            );
+            checked += 1;
+        }
+    }
+}
+
+#[test]
+fn fpu_controls_exploration() {
+    let board = TTTBoard::default();
+
+    let infinite = mcts_build_tree_with(
+        &board,
+        20,
+        &MCTSConfig::uct(2.0),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+    let children = infinite[0].children.unwrap();
+    assert!(children.iter().all(|c| !infinite[c].is_unvisited()));
+
+    let pessimistic = MCTSConfig {
+        fpu: Fpu::Fixed(0.0),
+        ..MCTSConfig::uct(2.0)
+    };
+    let tree = mcts_build_tree_with(&board, 20, &pessimistic, &mut RolloutEvaluator, &mut consistent_rng());
+    let children = tree[0].children.unwrap();
+    assert!(children.iter().any(|c| tree[c].is_unvisited()));
+}
+
+#[test]
+fn final_move_by_value() {
+    let tree = mcts_build_tree_with(
+        &TTTBoard::default(),
+        500,
+        &MCTSConfig::uct(2.0),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+    let children = tree[0].children.unwrap();
+
+    let best = tree.best_child_by(FinalMove::Value);
+    let best_value = tree[best].wdl().value();
+    assert!(children.iter().all(|c| tree[c].wdl().value() <= best_value));
+
+    assert_eq!(tree.best_child(), tree.best_child_by(FinalMove::Visits));
+}
+
+#[test]
+fn temperature_sampling() {
+    let tree = mcts_build_tree_with(
+        &TTTBoard::default(),
+        500,
+        &MCTSConfig::uct(2.0),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+    let mut rng = consistent_rng();
+
+    let greedy = MCTSConfig::uct(2.0);
+    assert!((0..20).all(|_| tree.select_move(&greedy, &mut rng) == tree.best_move()));
+
+    let sampled = MCTSConfig {
+        temperature: 1.0,
+        ..greedy
+    };
+    let moves: HashSet<_> = (0..100).map(|_| tree.select_move(&sampled, &mut rng)).collect();
+    assert!(moves.len() > 1);
+}
+
+#[test]
+fn root_noise_changes_priors() {
+    let config = MCTSConfig {
+        root_noise: Some(DirichletNoise {
+            alpha: 0.3,
+            fraction: 0.25,
+        }),
+        ..MCTSConfig::puct(2.0)
+    };
+    let tree = mcts_build_tree_with(
+        &TTTBoard::default(),
+        10,
+        &config,
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+
+    let children = tree[0].children.unwrap();
+    let priors: Vec<f32> = children.iter().map(|c| tree[c].prior).collect();
+    let sum: f32 = priors.iter().sum();
+    assert!((sum - 1.0).abs() < 1e-4);
+    assert!(priors.iter().any(|&p| (p - 1.0 / 9.0).abs() > 1e-3));
+}
@@ -0,0 +1,131 @@
+#![cfg(all(feature = "game_connect4", feature = "game_ttt"))]
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use rand::Rng;
+
+use board_game::ai::mcts::{mcts_build_tree, RolloutEvaluator};
+use board_game::ai::mcts_dag::{mcts_dag_build_tree, mcts_dag_expand_tree, DagMCTSBot, DagNode, DagTree};
+use board_game::ai::solver::{solve, SolverValue};
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::connect4::Connect4;
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::NonPov;
+use board_game::util::board_gen::{board_with_moves, random_board_with_moves};
+use board_game::util::coord::Coord3;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::{OutcomeWDL, WDLAbs};
+
+/// The number of distinct reachable tic-tac-toe positions.
+const TTT_POSITIONS: usize = 5478;
+
+fn check_visits<B: Board + Hash>(tree: &DagTree<B>) {
+    for node in &tree.nodes {
+        if let Some(edges) = &node.edges {
+            let edge_visits: u64 = edges.iter().map(|e| e.visits).sum();
+            assert_eq!(node.visits, 1 + edge_visits);
+        }
+        if node.visits > 0 {
+            assert!((node.value.sum() - 1.0).abs() < 1e-4, "{:?}", node.value);
+        }
+    }
+}
+
+#[test]
+fn ttt_positions_are_merged() {
+    let board = TTTBoard::default();
+    let iterations = 20_000;
+
+    let dag = mcts_dag_build_tree(&board, iterations, 2.0, &mut consistent_rng());
+    let tree = mcts_build_tree(&board, iterations, 2.0, &mut consistent_rng());
+
+    assert!(dag.len() <= TTT_POSITIONS);
+    assert!(dag.len() < tree.nodes.len() / 2);
+    assert_eq!(iterations, dag[0].visits);
+    check_visits(&dag);
+}
+
+#[test]
+fn transpositions_share_node() {
+    let start = TTTBoard::default();
+    let dag = mcts_dag_build_tree(&start, 2000, 2.0, &mut consistent_rng());
+
+    let moves = [0, 4, 8].map(Coord3::from_index);
+    let a = board_with_moves(start.clone(), &moves);
+    let b = board_with_moves(start, &[moves[2], moves[1], moves[0]]);
+
+    assert!(dag.find_node(&a).is_some());
+    assert_eq!(dag.find_node(&a), dag.find_node(&b));
+}
+
+#[test]
+fn symmetric_positions_share_node() {
+    let start = Connect4::default();
+    let dag = mcts_dag_build_tree(&start, 500, 2.0, &mut consistent_rng());
+
+    let left = start.clone_and_play(0).unwrap();
+    let right = start.clone_and_play(6).unwrap();
+    assert!(dag.find_node(&left).is_some());
+    assert_eq!(dag.find_node(&left), dag.find_node(&right));
+
+    // the root only has 4 distinct children
+    let children: HashSet<_> = dag[0].edges.as_ref().unwrap().iter().filter_map(|e| e.child).collect();
+    assert_eq!(4, children.len());
+}
+
+#[test]
+fn dag_finds_wins() {
+    let mut rng = consistent_rng();
+    let mut bot = DagMCTSBot::new(500, 2.0, consistent_rng());
+    let mut checked = 0;
+
+    while checked < 10 {
+        let board = random_board_with_moves(&TTTBoard::default(), rng.gen_range(4..7), &mut rng);
+        if board.is_done() || solve(&board, 1, &mut rng).value != SolverValue::WinIn(1) {
+            continue;
+        }
+
+        let mv = bot.select_move(&board).unwrap();
+        let after = board.clone_and_play(mv).unwrap();
+        assert_eq!(
+            Some(OutcomeWDL::Win),
+            after.outcome().pov(board.next_player()),
+            "{}",
+            board
+        );
+        checked += 1;
+    }
+}
+
+#[test]
+fn connect4_smoke() {
+    let board = Connect4::default();
+    let dag = mcts_dag_build_tree(&board, 2000, 2.0, &mut consistent_rng());
+
+    assert_eq!(2000, dag[0].visits);
+    check_visits(&dag);
+    assert!(board.is_available_move(dag.best_move()).unwrap());
+}
+
+#[test]
+fn unevaluated_child_is_unvisited() {
+    // an edge can be visited without its child being evaluated when the child closes a cycle,
+    //   simulate that by resetting a visited child of the root
+    let mut rng = consistent_rng();
+    let mut dag = mcts_dag_build_tree(&TTTBoard::default(), 100, 2.0, &mut rng);
+
+    let edge = dag[0].edges.as_ref().unwrap().iter().find(|e| e.visits > 0).unwrap();
+    let child = edge.child.unwrap();
+    dag.nodes[child] = DagNode {
+        visits: 0,
+        outcome: None,
+        eval: WDLAbs::nan(),
+        value: WDLAbs::nan(),
+        edges: None,
+    };
+
+    mcts_dag_expand_tree(&mut dag, 100, 2.0, &mut RolloutEvaluator, &mut rng);
+    assert!(!dag[child].value.win_a.is_nan());
+}
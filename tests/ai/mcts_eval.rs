@@ -0,0 +1,125 @@
+#![cfg(feature = "game_ttt")]
+
+use internal_iterator::InternalIterator;
+use rand::Rng;
+
+use board_game::ai::mcts::{
+    mcts_build_tree, mcts_build_tree_with, Evaluation, Evaluator, MCTSConfig, RolloutEvaluator,
+};
+use board_game::ai::solver::{solve, SolverValue};
+use board_game::board::{Board, BoardMoves};
+use board_game::games::ttt::TTTBoard;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::coord::Coord3;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::{OutcomeWDL, WDL};
+
+/// Evaluates every board as a draw without any rollouts, optionally preferring a single move.
+#[derive(Debug, Default)]
+struct DrawEvaluator {
+    preferred_move: Option<u8>,
+    calls: u64,
+}
+
+impl Evaluator<TTTBoard> for DrawEvaluator {
+    fn evaluate(&mut self, board: &TTTBoard, _: &mut impl Rng) -> Evaluation<Coord3> {
+        assert!(!board.is_done());
+        self.calls += 1;
+
+        let moves: Vec<_> = board.available_moves().unwrap().collect();
+        let policy = self.preferred_move.map(|preferred| {
+            let preferred = moves.iter().position(|mv| mv.index() == preferred);
+            (0..moves.len())
+                .map(|i| match preferred {
+                    Some(p) if p == i => 0.9,
+                    Some(_) => 0.1 / (moves.len() - 1) as f32,
+                    None => 1.0 / moves.len() as f32,
+                })
+                .collect()
+        });
+
+        Evaluation::new(OutcomeWDL::Draw.to_wdl(), policy)
+    }
+}
+
+#[test]
+fn puct_without_rollouts() {
+    let mut evaluator = DrawEvaluator::default();
+    let tree = mcts_build_tree_with(
+        &TTTBoard::default(),
+        200,
+        &MCTSConfig::puct(1.5),
+        &mut evaluator,
+        &mut consistent_rng(),
+    );
+
+    assert_eq!(200, tree[0].visits);
+    assert!(evaluator.calls > 0 && evaluator.calls <= 200);
+    assert_eq!(WDL::new(0.0, 1.0, 0.0), tree.wdl());
+
+    let children = tree[0].children.unwrap();
+    let prior_sum: f32 = children.iter().map(|c| tree[c].prior).sum();
+    assert!((prior_sum - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn puct_follows_policy() {
+    let center = 4;
+    let mut evaluator = DrawEvaluator {
+        preferred_move: Some(center),
+        calls: 0,
+    };
+    let tree = mcts_build_tree_with(
+        &TTTBoard::default(),
+        100,
+        &MCTSConfig::puct(1.5),
+        &mut evaluator,
+        &mut consistent_rng(),
+    );
+
+    assert_eq!(center, tree.best_move().index());
+    assert_eq!(0.9, tree[tree.best_child()].prior);
+}
+
+#[test]
+fn puct_still_proves_wins() {
+    let mut rng = consistent_rng();
+    let mut checked = 0;
+
+    while checked < 10 {
+        let board = random_board_with_moves(&TTTBoard::default(), rng.gen_range(4..7), &mut rng);
+        if board.is_done() || solve(&board, 1, &mut rng).value != SolverValue::WinIn(1) {
+            continue;
+        }
+
+        let tree = mcts_build_tree_with(
+            &board,
+            100,
+            &MCTSConfig::puct(1.5),
+            &mut DrawEvaluator::default(),
+            &mut rng,
+        );
+        assert_eq!(Some(OutcomeWDL::Loss), tree[0].solution());
+        assert_eq!(Some(OutcomeWDL::Win), tree[tree.best_child()].solution());
+        checked += 1;
+    }
+}
+
+#[test]
+fn uct_rollout_evaluator_matches_default() {
+    let board = TTTBoard::default();
+    let expected = mcts_build_tree(&board, 500, 2.0, &mut consistent_rng());
+    let actual = mcts_build_tree_with(
+        &board,
+        500,
+        &MCTSConfig::uct(2.0),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+
+    assert_eq!(expected.nodes.len(), actual.nodes.len());
+    for (e, a) in expected.nodes.iter().zip(&actual.nodes) {
+        assert_eq!(e.visits, a.visits);
+        assert_eq!(format!("{:?}", e.kind), format!("{:?}", a.kind));
+    }
+}
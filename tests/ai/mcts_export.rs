@@ -0,0 +1,89 @@
+#![cfg(feature = "game_ttt")]
+
+use board_game::ai::mcts::{mcts_build_tree, ExportFilter, Tree};
+use board_game::games::ttt::TTTBoard;
+use board_game::util::tiny::consistent_rng;
+
+fn tree() -> Tree<TTTBoard> {
+    mcts_build_tree(&TTTBoard::default(), 1000, 2.0, &mut consistent_rng())
+}
+
+/// The number of nodes selected by `filter`, computed directly from the tree.
+fn expected_count(tree: &Tree<TTTBoard>, node: usize, depth: u32, filter: ExportFilter) -> usize {
+    let children = match tree[node].children {
+        Some(children) if depth < filter.max_depth => children,
+        _ => return 1,
+    };
+
+    1 + children
+        .iter()
+        .filter(|&c| tree[c].visits >= filter.min_visits)
+        .map(|c| expected_count(tree, c, depth + 1, filter))
+        .sum::<usize>()
+}
+
+#[test]
+fn dot_respects_filter() {
+    let tree = tree();
+
+    for filter in [
+        ExportFilter::default(),
+        ExportFilter::new(0, 1),
+        ExportFilter::new(1, 1),
+        ExportFilter::new(3, 20),
+    ] {
+        let dot = tree.to_dot(filter);
+        assert!(dot.starts_with("digraph mcts {\n"));
+        assert!(dot.ends_with("}\n"));
+
+        let count = expected_count(&tree, 0, 0, filter);
+        assert_eq!(count, dot.matches("[label=").count(), "filter {:?}", filter);
+        assert_eq!(count - 1, dot.matches(" -> ").count(), "filter {:?}", filter);
+    }
+}
+
+#[test]
+fn dot_root_and_children() {
+    let tree = tree();
+    let dot = tree.to_dot(ExportFilter::new(1, 1));
+
+    assert!(dot.contains(&format!("n0 [label=\"root\\nvisits: {}\\n", tree[0].visits)));
+    for c in tree[0].children.unwrap() {
+        let mv = tree[c].last_move.unwrap();
+        assert!(dot.contains(&format!("n{} [label=\"{}\\nvisits: {}\\n", c, mv, tree[c].visits)));
+        assert!(dot.contains(&format!("n0 -> n{};", c)));
+    }
+}
+
+#[test]
+fn json_respects_filter() {
+    let tree = tree();
+
+    for filter in [
+        ExportFilter::default(),
+        ExportFilter::new(2, 10),
+        ExportFilter::new(0, 1),
+    ] {
+        let json = tree.to_json(filter);
+        assert_eq!(expected_count(&tree, 0, 0, filter), json.matches("\"move\":").count());
+
+        // brackets are balanced, and the board string is escaped
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+        assert_eq!(json.matches('[').count(), json.matches(']').count());
+        assert!(!json.contains('\n'));
+    }
+}
+
+#[test]
+fn json_root_fields() {
+    let tree = tree();
+    let json = tree.to_json(ExportFilter::new(0, 1));
+
+    let wdl = tree[0].wdl();
+    let expected = format!(
+        "\"root\":{{\"move\":null,\"visits\":{},\"wdl\":{{\"win\":{},\"draw\":{},\"loss\":{}}},\"solution\":null,\"children\":[]}}}}",
+        tree[0].visits, wdl.win, wdl.draw, wdl.loss
+    );
+    assert!(json.starts_with("{\"root_board\":\""));
+    assert!(json.ends_with(&expected), "got {}", json);
+}
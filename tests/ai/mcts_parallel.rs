@@ -0,0 +1,68 @@
+#![cfg(all(feature = "game_ataxx", feature = "game_ttt"))]
+
+use board_game::ai::mcts::{mcts_build_tree_parallel, MCTSBot};
+use board_game::ai::solver::{solve, SolverValue};
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::NonPov;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+use rand::Rng;
+
+#[test]
+fn parallel_visits_add_up() {
+    let board = AtaxxBoard::default();
+    let tree = mcts_build_tree_parallel(&board, 1001, 2.0, 4, &mut consistent_rng());
+
+    assert_eq!(1001, tree[0].visits);
+
+    let children = tree[0].children.unwrap();
+    let child_visits: i64 = children.iter().map(|c| tree[c].visits).sum();
+    assert_eq!(1001, child_visits);
+}
+
+#[test]
+fn parallel_more_threads_than_iterations() {
+    let tree = mcts_build_tree_parallel(&AtaxxBoard::default(), 2, 2.0, 8, &mut consistent_rng());
+    assert_eq!(2, tree[0].visits);
+}
+
+#[test]
+fn parallel_finds_ttt_wins() {
+    let mut rng = consistent_rng();
+    let mut checked = 0;
+
+    while checked < 10 {
+        let board = random_board_with_moves(&TTTBoard::default(), rng.gen_range(4..7), &mut rng);
+        if board.is_done() || solve(&board, 1, &mut rng).value != SolverValue::WinIn(1) {
+            continue;
+        }
+
+        let tree = mcts_build_tree_parallel(&board, 1000, 2.0, 4, &mut rng);
+        let after = board.clone_and_play(tree.best_move()).unwrap();
+        assert_eq!(
+            Some(OutcomeWDL::Win),
+            after.outcome().pov(board.next_player()),
+            "missed win on board\n{}",
+            board
+        );
+        checked += 1;
+    }
+}
+
+#[test]
+fn parallel_bot_reuses_tree() {
+    let mut bot = MCTSBot::new(200, 2.0, consistent_rng())
+        .with_threads(3)
+        .with_tree_reuse(true);
+    let mut board = AtaxxBoard::default();
+
+    for _ in 0..6 {
+        let mv = bot.select_move(&board).unwrap();
+        board.play(mv).unwrap();
+    }
+    assert!(bot.tree::<AtaxxBoard>().is_some());
+}
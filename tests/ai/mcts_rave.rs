@@ -0,0 +1,130 @@
+#![cfg(all(feature = "game_ttt", feature = "game_go"))]
+
+use board_game::ai::mcts::{
+    mcts_build_tree_with, mcts_expand_tree_with, MCTSConfig, Rave, RolloutEvaluator, SNodeKind,
+};
+use board_game::board::Board;
+use board_game::games::go::{GoBoard, Komi, Rules};
+use board_game::games::ttt::TTTBoard;
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::coord::Coord3;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::WDL;
+
+fn rave_config() -> MCTSConfig {
+    MCTSConfig {
+        rave: Some(Rave { equivalence: 50.0 }),
+        ..MCTSConfig::uct(1.0)
+    }
+}
+
+#[test]
+fn amaf_only_collected_with_rave() {
+    let board = TTTBoard::default();
+
+    let plain = mcts_build_tree_with(
+        &board,
+        200,
+        &MCTSConfig::uct(1.0),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+    let children = plain[0].children.unwrap();
+    assert!(children.iter().all(|c| plain[c].amaf.sum() == 0.0));
+
+    let rave = mcts_build_tree_with(
+        &board,
+        200,
+        &rave_config(),
+        &mut RolloutEvaluator,
+        &mut consistent_rng(),
+    );
+    let children = rave[0].children.unwrap();
+    for c in children.iter() {
+        // every simulation that visits a child also counts for its AMAF statistics
+        assert!(
+            rave[c].amaf.sum() >= rave[c].visits as f64,
+            "child {:?} has amaf {:?} but {} visits",
+            rave[c].last_move,
+            rave[c].amaf,
+            rave[c].visits
+        );
+    }
+
+    // at most one AMAF update per child per simulation
+    let total_amaf: f64 = children.iter().map(|c| rave[c].amaf.sum()).sum();
+    assert!(total_amaf <= (children.length as f64) * rave[0].visits as f64);
+}
+
+#[test]
+fn rave_changes_selection() {
+    // give one child the best mean and another one a worse mean but a great AMAF value,
+    //   without exploration plain UCT picks the first one and RAVE the second one
+    let board = TTTBoard::default();
+    let mut rng = consistent_rng();
+    let exploit = MCTSConfig::uct(0.0);
+    let rave = MCTSConfig {
+        rave: Some(Rave { equivalence: 50.0 }),
+        ..exploit
+    };
+
+    let build = |rng: &mut _| {
+        let mut tree = mcts_build_tree_with(&board, 100, &exploit, &mut RolloutEvaluator, rng);
+        let children = tree[0].children.unwrap();
+        for c in children.iter() {
+            let node = &mut tree[c];
+            assert!(node.solution().is_none());
+            node.visits = 10;
+            node.kind = SNodeKind::Estimate(WDL::new(5.0, 0.0, 5.0));
+            node.amaf = WDL::default();
+        }
+
+        let best_mean = children.get(0);
+        let best_amaf = children.get(1);
+        tree[best_mean].kind = SNodeKind::Estimate(WDL::new(6.0, 0.0, 4.0));
+        tree[best_amaf].amaf = WDL::new(100.0, 0.0, 0.0);
+        (tree, best_mean, best_amaf)
+    };
+
+    let (mut tree, best_mean, _) = build(&mut rng);
+    mcts_expand_tree_with(&mut tree, 1, &exploit, &mut RolloutEvaluator, &mut rng);
+    assert_eq!(11, tree[best_mean].visits);
+
+    let (mut tree, _, best_amaf) = build(&mut rng);
+    mcts_expand_tree_with(&mut tree, 1, &rave, &mut RolloutEvaluator, &mut rng);
+    assert_eq!(11, tree[best_amaf].visits);
+}
+
+#[test]
+fn rave_finds_win() {
+    // x to play and win by completing the first row
+    let board = board_with_moves(
+        TTTBoard::default(),
+        &[
+            Coord3::from_index(0),
+            Coord3::from_index(3),
+            Coord3::from_index(1),
+            Coord3::from_index(4),
+        ],
+    );
+
+    let mut rng = consistent_rng();
+    let tree = mcts_build_tree_with(&board, 500, &rave_config(), &mut RolloutEvaluator, &mut rng);
+    let mv = tree.best_move();
+
+    let after = board.clone_and_play(mv).unwrap();
+    assert!(after.is_done(), "expected winning move, got {:?}", mv);
+}
+
+#[test]
+fn rave_small_go() {
+    let board = GoBoard::new(5, Komi::zero(), Rules::tromp_taylor());
+
+    let mut rng = consistent_rng();
+    let tree = mcts_build_tree_with(&board, 300, &rave_config(), &mut RolloutEvaluator, &mut rng);
+
+    assert_eq!(300, tree[0].visits);
+    let children = tree[0].children.unwrap();
+    assert!(children.iter().all(|c| tree[c].amaf.sum() > 0.0));
+    assert!(board.is_available_move(tree.best_move()).unwrap());
+}
@@ -1,2 +1,21 @@
+pub mod chance;
+pub mod connect4_solver;
 pub mod is_double_forced_draw;
+pub mod iterative;
+pub mod lazy_smp;
+pub mod mcts;
+pub mod mcts_budget;
+pub mod mcts_config;
+pub mod mcts_dag;
+pub mod mcts_eval;
+pub mod mcts_export;
+pub mod mcts_parallel;
+pub mod mcts_rave;
+pub mod oware_solver;
+pub mod pns;
+pub mod pv;
+pub mod quiescence;
+pub mod rollout;
 pub mod solver;
+pub mod tablebase;
+pub mod tt;
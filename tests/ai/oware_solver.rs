@@ -0,0 +1,63 @@
+#![cfg(feature = "game_oware")]
+
+use board_game::ai::solver::solve_value;
+use board_game::board::Board;
+use board_game::games::oware::solver::solve_oware;
+use board_game::games::oware::OwareBoard;
+use board_game::pov::Pov;
+use board_game::util::game_stats::all_possible_boards;
+use board_game::wdl::OutcomeWDL;
+
+#[test]
+fn tiny_variant() {
+    let start = OwareBoard::<2>::new(2);
+    let solution = solve_oware(&start);
+
+    assert_eq!(
+        all_possible_boards(&start, u32::MAX, true).len(),
+        solution.positions.len()
+    );
+    assert_eq!(start, solution.positions[0].board);
+    assert_eq!(solve_value(&start, 20), solution.value().to_solver_value());
+    assert_eq!(OutcomeWDL::Win, solution.value().value);
+}
+
+#[test]
+fn best_moves_keep_value() {
+    let start = OwareBoard::<3>::new(2);
+    let solution = solve_oware(&start);
+    let tablebase = solution.tablebase();
+
+    for position in &solution.positions {
+        let board = &position.board;
+        if board.is_done() {
+            assert!(position.best_moves.is_empty());
+            continue;
+        }
+        assert!(!position.best_moves.is_empty());
+
+        for &mv in &position.best_moves {
+            let child = tablebase.get(&board.clone_and_play(mv).unwrap()).unwrap();
+            assert_eq!(position.entry.value, child.value.flip(), "{}", board);
+            if position.entry.value != OutcomeWDL::Draw {
+                assert_eq!(position.entry.distance, child.distance + 1, "{}", board);
+            }
+        }
+    }
+}
+
+#[test]
+fn csv_output() {
+    let start = OwareBoard::<2>::new(2);
+    let solution = solve_oware(&start);
+
+    let mut bytes = vec![];
+    solution.write_csv(&mut bytes).unwrap();
+    let csv = String::from_utf8(bytes).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(solution.positions.len() + 1, lines.len());
+    assert_eq!("a0,a1,b0,b1,score_a,score_b,next,value,distance,best_moves", lines[0]);
+    assert_eq!("2,2,2,2,0,0,A,win,3,0", lines[1]);
+    assert!(lines.iter().all(|line| line.split(',').count() == 10));
+}
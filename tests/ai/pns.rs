@@ -0,0 +1,176 @@
+#![cfg(all(feature = "game_connect4", feature = "game_oware", feature = "game_ttt"))]
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use internal_iterator::InternalIterator;
+
+use board_game::ai::budget::SearchBudget;
+use board_game::ai::pns::{pns_solve, Proof};
+use board_game::ai::solver::solve_value;
+use board_game::ai::tablebase::Tablebase;
+use board_game::board::{Board, Player};
+use board_game::games::connect4::Connect4;
+use board_game::games::oware::OwareBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::NonPov;
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::game_stats::all_possible_boards;
+use board_game::wdl::OutcomeWDL;
+
+/// Check that `proof` is a valid strategy for `prover` to get at least `target` starting from `board`.
+fn check_proof<B: Board>(board: &B, proof: &Proof<B::Move>, prover: Player, target: OutcomeWDL) {
+    let mut visited = HashMap::new();
+    check_proof_node(board, proof, 0, prover, target, &mut visited);
+    assert_eq!(proof.nodes.len(), visited.len(), "proof contains unreachable nodes");
+}
+
+/// Check a single node of a proof, `visited` contains the boards of the nodes that were already checked,
+/// a shared node must be reached with the same board every time.
+fn check_proof_node<B: Board>(
+    board: &B,
+    proof: &Proof<B::Move>,
+    node: usize,
+    prover: Player,
+    target: OutcomeWDL,
+    visited: &mut HashMap<usize, B>,
+) {
+    if let Some(prev) = visited.get(&node) {
+        assert_eq!(prev, board, "proof node {} is shared between different boards", node);
+        return;
+    }
+    visited.insert(node, board.clone());
+    let children = &proof.nodes[node].children;
+
+    if let Some(outcome) = board.outcome() {
+        assert!(children.is_empty());
+        let wdl = outcome.pov(prover);
+        assert!(
+            wdl == OutcomeWDL::Win || wdl == target,
+            "{:?} is worse than {:?}",
+            wdl,
+            target
+        );
+        return;
+    }
+
+    let moves: Vec<B::Move> = board.available_moves().unwrap().collect();
+    if board.next_player() == prover {
+        assert_eq!(1, children.len());
+    } else {
+        let proof_moves: Vec<B::Move> = children.iter().map(|&(mv, _)| mv).collect();
+        assert_eq!(moves, proof_moves);
+    }
+
+    for &(mv, child) in children {
+        assert!(moves.contains(&mv));
+        check_proof_node(
+            &board.clone_and_play(mv).unwrap(),
+            proof,
+            child,
+            prover,
+            target,
+            visited,
+        );
+    }
+}
+
+fn check_result<B: Board + std::hash::Hash>(board: &B, expected: OutcomeWDL) {
+    let result = pns_solve(board, &SearchBudget::unlimited());
+    assert_eq!(Some(expected), result.value, "wrong value for\n{}", board);
+
+    let proof = result.proof.unwrap();
+    let (prover, target) = match expected {
+        OutcomeWDL::Win => (board.next_player(), OutcomeWDL::Win),
+        OutcomeWDL::Draw => (board.next_player(), OutcomeWDL::Draw),
+        OutcomeWDL::Loss => (board.next_player().other(), OutcomeWDL::Win),
+    };
+    check_proof(board, &proof, prover, target);
+}
+
+#[test]
+fn ttt_root_draw() {
+    check_result(&TTTBoard::default(), OutcomeWDL::Draw);
+}
+
+#[test]
+fn transpositions_are_shared() {
+    let board = TTTBoard::default();
+    let proof = pns_solve(&board, &SearchBudget::unlimited()).proof.unwrap();
+    check_proof(&board, &proof, board.next_player(), OutcomeWDL::Draw);
+
+    // in a tree every node except the root has a single parent, so more edges means some nodes are shared
+    let edges: usize = proof.nodes.iter().map(|node| node.children.len()).sum();
+    assert!(edges > proof.nodes.len() - 1);
+    assert_eq!(9, proof.depth());
+}
+
+#[test]
+fn ttt_matches_solver() {
+    let boards = all_possible_boards(&TTTBoard::default(), 20, false);
+
+    for board in boards.iter().step_by(13) {
+        if board.is_done() {
+            continue;
+        }
+
+        let expected = solve_value(board, 20).to_outcome_wdl().unwrap();
+        check_result(board, expected);
+    }
+}
+
+#[test]
+fn connect4_open_three() {
+    // after 3 in the middle of the bottom row the opponent can't block both sides
+    let board = board_with_moves(Connect4::default(), &[3, 3, 2, 2]);
+    check_result(&board, OutcomeWDL::Win);
+
+    let result = pns_solve(&board, &SearchBudget::unlimited());
+    assert_eq!(4, result.proof.unwrap().depth() as usize + 1);
+}
+
+#[test]
+fn budget_stops_search() {
+    let board = Connect4::default();
+    let result = pns_solve(&board, &SearchBudget::nodes(100));
+
+    assert_eq!(None, result.value);
+    assert!(result.proof.is_none());
+    assert!(result.nodes >= 100 && result.nodes < 100 + Connect4::WIDTH as u64);
+}
+
+#[test]
+fn oware_repeated_positions() {
+    // positions can repeat in oware, so some of them are stuck on a cycle
+    let start = OwareBoard::<2>::new(2);
+    let tablebase = Tablebase::generate(&start);
+    let time_limit = Duration::from_secs(10);
+
+    let mut solved = 0;
+    for board in all_possible_boards(&start, u32::MAX, false) {
+        if board.is_done() {
+            continue;
+        }
+
+        let start_time = Instant::now();
+        let result = pns_solve(&board, &SearchBudget::time(time_limit));
+        assert!(
+            start_time.elapsed() < time_limit,
+            "search did not stop by itself for\n{}",
+            board
+        );
+
+        let expected = tablebase.get(&board).unwrap().value;
+        match result.value {
+            Some(value) => {
+                assert_eq!(expected, value, "wrong value for\n{}", board);
+                check_result(&board, value);
+                solved += 1;
+            }
+            // draws by endless repetition can't be proven, but lines that get stuck on a cycle
+            //   must not stop the search from finding wins and losses elsewhere
+            None => assert_eq!(OutcomeWDL::Draw, expected, "unknown value for\n{}", board),
+        }
+    }
+    assert!(solved > 0);
+}
@@ -0,0 +1,126 @@
+#![cfg(all(feature = "game_ataxx", feature = "game_ttt"))]
+
+use board_game::ai::minimax::{minimax_all_moves, minimax_all_moves_pv, minimax_pv, Heuristic};
+use board_game::ai::solver::{solve_all_moves_pv, solve_pv, solve_value, SolverHeuristic, SolverValue};
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::pov::NonPov;
+use board_game::util::game_stats::all_possible_boards;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+
+#[test]
+fn solve_pv_ttt() {
+    let mut rng = consistent_rng();
+
+    for board in all_possible_boards(&TTTBoard::default(), 20, false) {
+        if board.is_done() {
+            continue;
+        }
+
+        let result = solve_pv(&board, 20, &mut rng);
+        let end = play_pv(&board, &result.pv);
+        let outcome = end.outcome().unwrap().pov(board.next_player());
+
+        assert_eq!(result.value.to_outcome_wdl(), Some(outcome), "{}", board);
+        match result.value {
+            SolverValue::WinIn(n) | SolverValue::LossIn(n) => assert_eq!(n as usize, result.pv.len()),
+            SolverValue::Draw => {}
+            SolverValue::Unknown => panic!("TTT should be solved at depth 20"),
+        }
+    }
+}
+
+#[test]
+fn solve_all_moves_pv_ttt() {
+    for board in all_possible_boards(&TTTBoard::default(), 20, false) {
+        if board.is_done() {
+            continue;
+        }
+
+        let result = solve_all_moves_pv(&board, 20);
+        let pvs = result.best_move.unwrap();
+        let moves: Vec<_> = pvs.iter().map(|pv| pv[0]).collect();
+        assert_eq!(
+            minimax_all_moves(&board, &SolverHeuristic, 20).best_move.unwrap(),
+            moves
+        );
+
+        for pv in pvs {
+            let child = board.clone_and_play(pv[0]).unwrap();
+            assert_eq!(optimal_child_value(result.value), solve_value(&child, 20));
+            let end = play_pv(&board, &pv);
+            assert_eq!(
+                result.value.to_outcome_wdl(),
+                Some(end.outcome().unwrap().pov(board.next_player()))
+            );
+        }
+    }
+}
+
+#[test]
+fn minimax_pv_ataxx() {
+    let heuristic = AtaxxTileHeuristic::default();
+    let mut rng = consistent_rng();
+
+    let mut board = AtaxxBoard::diagonal(5);
+    for _ in 0..10 {
+        if board.is_done() {
+            break;
+        }
+
+        for depth in 1..4 {
+            let result = minimax_pv(&board, &heuristic, depth, &mut rng);
+            let end = play_pv(&board, &result.pv);
+
+            // the value of the board at the end of the variation is the value of the root
+            let length = result.pv.len() as u32;
+            let end_value = heuristic.value(&end, length);
+            let end_value = if end.next_player() == board.next_player() {
+                end_value
+            } else {
+                -end_value
+            };
+
+            assert!(end.is_done() || length == depth);
+            assert_eq!(result.value, end_value);
+
+            let all = minimax_all_moves_pv(&board, &heuristic, depth);
+            assert_eq!(result.value, all.value);
+            assert!(all.best_move.unwrap().contains(&result.pv));
+        }
+
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+}
+
+#[test]
+fn pv_done_board() {
+    let mut board = TTTBoard::default();
+    let mut rng = consistent_rng();
+    while !board.is_done() {
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+
+    let result = solve_pv(&board, 5, &mut rng);
+    assert!(result.pv.is_empty());
+    assert_ne!(result.value.to_outcome_wdl(), Some(OutcomeWDL::Win));
+}
+
+fn optimal_child_value(value: SolverValue) -> SolverValue {
+    match value {
+        SolverValue::WinIn(n) => SolverValue::LossIn(n - 1),
+        SolverValue::LossIn(n) => SolverValue::WinIn(n - 1),
+        SolverValue::Draw | SolverValue::Unknown => value,
+    }
+}
+
+fn play_pv<B: Board>(board: &B, pv: &[B::Move]) -> B {
+    let mut board = board.clone();
+    for &mv in pv {
+        board.play(mv).unwrap();
+    }
+    board
+}
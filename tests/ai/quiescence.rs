@@ -0,0 +1,91 @@
+#![cfg(all(feature = "game_ataxx", feature = "game_chess"))]
+
+use std::cmp::Ordering;
+
+use board_game::ai::budget::SearchBudget;
+use board_game::ai::minimax::{minimax, minimax_iterative, minimax_value, minimax_value_tt, Heuristic, MoveOrdering};
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::{ChessBoard, Rules};
+use board_game::heuristic::ataxx::{AtaxxTileHeuristic, AtaxxTileQuiescenceHeuristic};
+use board_game::heuristic::chess::{ChessPieceValueHeuristic, ChessPieceValueQuiescenceHeuristic};
+use board_game::util::tiny::consistent_rng;
+use board_game::util::tt::{Replacement, TranspositionTable};
+
+/// Wrapper that disables the quiescence search of the inner heuristic.
+#[derive(Debug)]
+struct NoQuiescence<H>(H);
+
+impl<B: Board, H: Heuristic<B>> Heuristic<B> for NoQuiescence<H> {
+    type V = H::V;
+
+    fn value(&self, board: &B, length: u32) -> Self::V {
+        self.0.value(board, length)
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        H::merge(old, new)
+    }
+
+    fn expectation(children: &[(Self::V, f32)]) -> Self::V {
+        H::expectation(children)
+    }
+}
+
+#[test]
+fn chess_defended_pawn() {
+    // the queen can take a pawn, but it is defended by another pawn
+    let board = ChessBoard::new_without_history_fen("4k3/8/3p4/4p3/8/8/4Q3/4K3 w - - 0 1", Rules::default());
+    let capture = board.parse_move("e2e5").unwrap();
+
+    let result = minimax(&board, &ChessPieceValueHeuristic, 1, &mut consistent_rng());
+    assert_eq!(8, result.value);
+    assert_eq!(Some(capture), result.best_move);
+
+    let result = minimax(&board, &ChessPieceValueQuiescenceHeuristic, 1, &mut consistent_rng());
+    assert_eq!(7, result.value);
+    assert_ne!(Some(capture), result.best_move);
+}
+
+#[test]
+fn quiescence_consistent() {
+    let heuristic = AtaxxTileQuiescenceHeuristic::new(AtaxxTileHeuristic::default(), 2);
+    let tt = TranspositionTable::new(4096, Replacement::DepthPreferred);
+    let mut rng = consistent_rng();
+
+    let mut board = AtaxxBoard::diagonal(5);
+    for _ in 0..10 {
+        if board.is_done() {
+            break;
+        }
+
+        for depth in 0..3 {
+            let value = minimax_value(&board, &heuristic, depth);
+
+            tt.new_generation();
+            assert_eq!(value, minimax_value_tt(&board, &heuristic, depth, &tt));
+
+            if depth > 0 {
+                let budget = SearchBudget::unlimited();
+                let result = minimax_iterative(&board, &heuristic, depth, &budget, MoveOrdering::ALL, &mut rng);
+                assert_eq!(value, result.value);
+            }
+        }
+
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+}
+
+#[test]
+fn quiescence_without_noisy_moves() {
+    let heuristic = AtaxxTileHeuristic::default();
+    let board = AtaxxBoard::diagonal(5);
+
+    for depth in 0..3 {
+        assert_eq!(
+            minimax_value(&board, &heuristic, depth),
+            minimax_value(&board, &NoQuiescence(AtaxxTileHeuristic::default()), depth)
+        );
+    }
+    assert_eq!(heuristic.value(&board, 0), minimax_value(&board, &heuristic, 0));
+}
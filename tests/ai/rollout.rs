@@ -0,0 +1,163 @@
+#![cfg(all(
+    feature = "game_chess",
+    feature = "game_oware",
+    feature = "game_pig",
+    feature = "game_ttt"
+))]
+
+use std::cmp::Ordering;
+
+use board_game::ai::mcts::{mcts_build_tree_with, MCTSConfig, PolicyRolloutEvaluator, RolloutEvaluator};
+use board_game::ai::minimax::{expectation_i32, Heuristic};
+use board_game::ai::rollout::{rollout, CutoffRollout, EpsilonGreedyRollout, RandomRollout, RolloutPolicy};
+use board_game::ai::simple::RolloutBot;
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::chess::{ChessBoard, Rules};
+use board_game::games::oware::OwareBoard;
+use board_game::games::pig::{Move, PigBoard};
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::heuristic::oware::OwareScoreHeuristic;
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::WDL;
+
+/// White can capture the undefended black queen.
+fn free_queen() -> ChessBoard {
+    ChessBoard::new_without_history_fen("4k3/8/8/3q4/8/8/3Q4/4K3 w - - 0 1", Rules::default())
+}
+
+#[test]
+fn random_rollout_finishes_game() {
+    let board = TTTBoard::default();
+    let mut rng = consistent_rng();
+
+    for _ in 0..10 {
+        let mut played = vec![];
+        let wdl = rollout(&board, &mut RandomRollout, &mut played, &mut rng);
+
+        assert!(wdl.try_to_outcome().is_some());
+        assert!(played.len() >= 5 && played.len() <= 9);
+        for (i, &(player, _)) in played.iter().enumerate() {
+            assert_eq!(
+                if i % 2 == 0 {
+                    board.next_player()
+                } else {
+                    board.next_player().other()
+                },
+                player
+            );
+        }
+    }
+}
+
+#[test]
+fn cutoff_after_plies() {
+    let board = OwareBoard::<6>::default();
+    let mut policy = CutoffRollout::new(RandomRollout, OwareScoreHeuristic, 10, 2.0);
+    let mut rng = consistent_rng();
+
+    for _ in 0..10 {
+        let mut played = vec![];
+        let wdl = rollout(&board, &mut policy, &mut played, &mut rng);
+        assert_eq!(10, played.len());
+        assert!((wdl.sum() - 1.0).abs() < 1e-5);
+    }
+
+    // cutting off immediately evaluates the start position, which is balanced
+    let mut immediate = CutoffRollout::new(RandomRollout, OwareScoreHeuristic, 0, 2.0);
+    assert_eq!(Some(WDL::new(0.5, 0.0, 0.5)), immediate.cutoff(&board, 0));
+}
+
+#[test]
+fn greedy_takes_queen() {
+    let board = free_queen();
+    let expected = board.parse_move("d2d5").unwrap();
+
+    let mut policy = EpsilonGreedyRollout::new(ChessPieceValueHeuristic, 0.0);
+    let mut rng = consistent_rng();
+    for _ in 0..10 {
+        assert_eq!(expected, policy.pick_move(&board, &mut rng));
+    }
+
+    let greedy = CutoffRollout::new(policy, ChessPieceValueHeuristic, 4, 1.0);
+    let mut bot = RolloutBot::new(100, consistent_rng()).with_policy(greedy);
+    assert_eq!(expected, bot.select_move(&board).unwrap());
+}
+
+/// Values points collected during the current turn twice as much as points already held,
+/// so it always prefers rolling again.
+#[derive(Debug)]
+struct GreedyPigHeuristic;
+
+impl Heuristic<PigBoard> for GreedyPigHeuristic {
+    type V = i32;
+
+    fn value(&self, board: &PigBoard, _: u32) -> Self::V {
+        let next = board.next_player();
+        (board.score(next) + 2 * board.turn_total()) as i32 - board.score(next.other()) as i32
+    }
+
+    fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
+        (old.max(new), new.cmp(&old))
+    }
+
+    fn expectation(children: &[(Self::V, f32)]) -> Self::V {
+        expectation_i32(children)
+    }
+}
+
+#[test]
+fn greedy_keeps_pov_without_player_switch() {
+    // rolling keeps the same player to move, holding passes the turn to the other player
+    let board = board_with_moves(PigBoard::new(100), &[Move::Roll, Move::Die(6)]);
+    assert_eq!(6, board.turn_total());
+
+    let mut policy = EpsilonGreedyRollout::new(GreedyPigHeuristic, 0.0);
+    let mut rng = consistent_rng();
+    for _ in 0..10 {
+        assert_eq!(Move::Roll, policy.pick_move(&board, &mut rng));
+    }
+}
+
+#[test]
+fn policy_evaluator_random_matches_default() {
+    let board = TTTBoard::default();
+    let config = MCTSConfig::uct(2.0);
+
+    let expected = mcts_build_tree_with(&board, 300, &config, &mut RolloutEvaluator, &mut consistent_rng());
+    let mut evaluator = PolicyRolloutEvaluator::new(RandomRollout);
+    let actual = mcts_build_tree_with(&board, 300, &config, &mut evaluator, &mut consistent_rng());
+
+    assert_eq!(expected.nodes.len(), actual.nodes.len());
+    for (e, a) in expected.nodes.iter().zip(&actual.nodes) {
+        assert_eq!(e.visits, a.visits);
+        assert_eq!(format!("{:?}", e.kind), format!("{:?}", a.kind));
+    }
+}
+
+#[test]
+fn mcts_with_cutoff_rollouts() {
+    let board = OwareBoard::<6>::default();
+    let policy = CutoffRollout::new(
+        EpsilonGreedyRollout::new(OwareScoreHeuristic, 0.2),
+        OwareScoreHeuristic,
+        20,
+        2.0,
+    );
+    let mut evaluator = PolicyRolloutEvaluator::new(policy);
+
+    let tree = mcts_build_tree_with(
+        &board,
+        300,
+        &MCTSConfig::uct(2.0),
+        &mut evaluator,
+        &mut consistent_rng(),
+    );
+    assert_eq!(300, tree[0].visits);
+    assert!(board.is_available_move(tree.best_move()).unwrap());
+
+    let wdl = tree.wdl();
+    assert!((wdl.sum() - 1.0).abs() < 1e-5);
+}
@@ -1,13 +1,18 @@
 // TODO solver tests without TTT?
-#![cfg(feature = "game_ttt")]
+#![cfg(all(feature = "game_connect4", feature = "game_ttt"))]
 
 use internal_iterator::InternalIterator;
 
-use board_game::ai::solver::{solve_all_moves, solve_value, SolverValue};
+use board_game::ai::solver::{
+    solve_all_moves, solve_bounds, solve_bounds_all_moves, solve_value, SolverBounds, SolverValue,
+};
 use board_game::board::Board;
+use board_game::games::connect4::Connect4;
 use board_game::games::ttt::TTTBoard;
+use board_game::util::board_gen::random_board_with_moves;
 use board_game::util::coord::Coord3;
 use board_game::util::game_stats::all_possible_boards;
+use board_game::util::tiny::consistent_rng;
 use board_game::wdl::OutcomeWDL;
 
 #[test]
@@ -75,3 +80,90 @@ fn solver_ttt_consistent() {
         println!();
     }
 }
+
+#[test]
+fn bounds_ttt_root() {
+    let board = TTTBoard::default();
+
+    let bounds = solve_bounds(&board, 20);
+    assert!(bounds.is_proven_draw(), "{:?}", bounds);
+    assert_eq!(bounds, SolverBounds::exact(SolverValue::Draw));
+
+    // a shallow search can't tell yet
+    let bounds = solve_bounds(&board, 2);
+    assert_eq!(bounds, SolverBounds::unknown(2));
+    assert!(!bounds.is_proven_draw());
+    assert_eq!(bounds.outcome(), None);
+    assert_eq!(bounds.value(), SolverValue::Unknown);
+}
+
+#[test]
+fn bounds_ttt_win_distance() {
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(0, 0)).unwrap();
+    board.play(Coord3::from_xy(1, 0)).unwrap();
+
+    let bounds = solve_bounds(&board, 20);
+    assert_eq!(bounds.outcome(), Some(OutcomeWDL::Win));
+    assert_eq!(bounds.value(), solve_value(&board, 20));
+    assert_eq!(bounds.mate_distance(), Some(5));
+
+    // searching up to the win is enough to prove it, including the distance
+    assert_eq!(solve_bounds(&board, 5), bounds);
+    let shallow = solve_bounds(&board, 4);
+    assert_eq!(shallow.outcome(), None);
+    assert_eq!(shallow.mate_distance(), None);
+
+    let moves = solve_bounds_all_moves(&board, 20).unwrap();
+    assert!(moves.iter().all(|(_, child)| child.is_exact()));
+    let best = moves.iter().filter(|(_, child)| *child == bounds).count();
+    assert!(best > 0);
+}
+
+#[test]
+fn bounds_ttt_consistent() {
+    let boards = all_possible_boards(&TTTBoard::default(), 20, false);
+
+    for board in boards {
+        let value = solve_value(&board, 20);
+        assert_eq!(solve_bounds(&board, 20), SolverBounds::exact(value), "{}", board);
+
+        // shallow bounds must contain the real value
+        for depth in 0..4 {
+            let bounds = solve_bounds(&board, depth);
+            assert!(
+                SolverValue::merge(bounds.lower, value).1.is_ge(),
+                "{} {:?}",
+                board,
+                bounds
+            );
+            assert!(
+                SolverValue::merge(bounds.upper, value).1.is_le(),
+                "{} {:?}",
+                board,
+                bounds
+            );
+        }
+    }
+}
+
+#[test]
+fn bounds_connect4_endgames() {
+    // too large to search without pruning
+    let mut rng = consistent_rng();
+    let mut solved = 0;
+
+    for _ in 0..10 {
+        let board = random_board_with_moves(&Connect4::default(), 28, &mut rng);
+        if board.is_done() {
+            continue;
+        }
+
+        let bounds = solve_bounds(&board, 14);
+        assert_eq!(solve_value(&board, 14), bounds.value(), "{}", board);
+        if bounds.is_exact() {
+            solved += 1;
+        }
+    }
+    assert!(solved > 0);
+}
@@ -0,0 +1,187 @@
+#![cfg(all(
+    feature = "game_ataxx",
+    feature = "game_connect4",
+    feature = "game_oware",
+    feature = "game_ttt"
+))]
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::io::ErrorKind;
+
+use internal_iterator::{InternalIterator, IteratorExt};
+
+use board_game::ai::simple::RandomBot;
+use board_game::ai::solver::{solve_value, SolverValue};
+use board_game::ai::tablebase::{Tablebase, TablebaseBot};
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardSymmetry, ZobristBoard};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::connect4::Connect4;
+use board_game::games::oware::OwareBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::pov::{NonPov, Pov};
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::coord::Coord3;
+use board_game::util::game_stats::all_possible_boards;
+use board_game::util::tiny::consistent_rng;
+use board_game::wdl::OutcomeWDL;
+
+#[test]
+fn ttt_matches_solver() {
+    let start = TTTBoard::default();
+    let tablebase = Tablebase::generate(&start);
+
+    let boards = all_possible_boards(&start, 20, true);
+    assert_eq!(boards.len(), tablebase.len());
+
+    for board in boards {
+        let entry = tablebase.get(&board).unwrap();
+        assert_eq!(solve_value(&board, 20), entry.to_solver_value(), "{}", board);
+    }
+
+    assert_eq!(OutcomeWDL::Draw, tablebase.get(&start).unwrap().value);
+}
+
+#[test]
+fn connect4_endgame_matches_solver() {
+    // the first 34 moves of a drawn game, leaving 8 empty tiles
+    let moves = [
+        1, 0, 3, 0, 5, 4, 4, 4, 0, 6, 2, 0, 3, 0, 2, 6, 4, 1, 0, 3, 6, 5, 3, 1, 1, 6, 3, 5, 6, 3, 1, 4, 5, 4,
+    ];
+    let start = board_with_moves(Connect4::default(), &moves);
+    let tablebase = Tablebase::generate(&start);
+
+    for board in all_possible_boards(&start, 20, true) {
+        let entry = tablebase.get(&board).unwrap();
+        assert_eq!(solve_value(&board, 20), entry.to_solver_value(), "{}", board);
+    }
+}
+
+/// Play games between the tablebase bot and a random bot and check that the bot gets at least the table value.
+fn check_bot_never_worse<B: ZobristBoard + Hash>(start: &B, tablebase: Tablebase<B>) {
+    let expected = tablebase.get(start).unwrap().value;
+    let mut bot = TablebaseBot::new(tablebase);
+    let mut random = RandomBot::new(consistent_rng());
+
+    for _ in 0..20 {
+        let player = start.next_player();
+        let mut board = start.clone();
+
+        while !board.is_done() {
+            let mv = if board.next_player() == player {
+                let mv = bot.select_move(&board).unwrap();
+                assert!(board.is_available_move(mv).unwrap());
+                mv
+            } else {
+                random.select_move(&board).unwrap()
+            };
+            board.play(mv).unwrap();
+        }
+
+        let actual = board.outcome().unwrap().pov(player);
+        assert!(
+            actual.sign::<i32>() >= expected.sign::<i32>(),
+            "expected at least {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn ttt_bot() {
+    let start = TTTBoard::default();
+    check_bot_never_worse(&start, Tablebase::generate(&start));
+}
+
+#[test]
+fn oware_small() {
+    let start = OwareBoard::<2>::new(2);
+    let tablebase = Tablebase::generate(&start);
+    assert!(!tablebase.is_empty());
+
+    // every move of the bot keeps the value and the loser delays as long as possible
+    let entry = tablebase.get(&start).unwrap();
+    let mv = tablebase.best_move(&start).unwrap().unwrap();
+    let child = tablebase.get(&start.clone_and_play(mv).unwrap()).unwrap();
+    assert_eq!(entry.value, child.value.flip());
+    if entry.value != OutcomeWDL::Draw {
+        assert_eq!(entry.distance, child.distance + 1);
+    }
+
+    check_bot_never_worse(&start, tablebase);
+}
+
+#[test]
+fn ataxx_small() {
+    // boards that only differ in the number of moves since the last copy are separate positions
+    let start = AtaxxBoard::diagonal(3);
+    let (tablebase, positions) = Tablebase::generate_with_positions(&start);
+    assert_eq!(start.canonicalize(), positions[0]);
+    assert_eq!(positions.len(), tablebase.len());
+
+    let boards = all_possible_boards(&start, u32::MAX, true);
+    let canonical: HashSet<_> = boards.iter().map(|board| board.canonicalize()).collect();
+    assert_eq!(canonical, positions.into_iter().collect());
+
+    for board in boards.iter().step_by(101) {
+        let expected = solve_value(board, 4);
+        if expected != SolverValue::Unknown {
+            assert_eq!(expected, tablebase.get(board).unwrap().to_solver_value(), "{}", board);
+        }
+    }
+
+    check_bot_never_worse(&start, tablebase);
+}
+
+#[test]
+fn write_read_roundtrip() {
+    let start = TTTBoard::default();
+    let tablebase = Tablebase::generate(&start);
+
+    let mut bytes = vec![];
+    tablebase.write(&mut bytes).unwrap();
+    assert_eq!(5 + 8 + 8 + 13 * tablebase.len(), bytes.len());
+
+    let read = Tablebase::read(&bytes[..], &start).unwrap();
+    assert_eq!(tablebase.len(), read.len());
+    all_possible_boards(&start, 20, true).iter().for_each(|board| {
+        assert_eq!(tablebase.get(board), read.get(board));
+    });
+
+    assert!(Tablebase::read(&bytes[1..], &start).is_err());
+    assert!(Tablebase::read(&bytes[..bytes.len() - 1], &start).is_err());
+}
+
+#[test]
+fn read_checks_start() {
+    let start = TTTBoard::default();
+    let mut bytes = vec![];
+    Tablebase::generate(&start).write(&mut bytes).unwrap();
+
+    // a different start position of the same game
+    let other_start = board_with_moves(start, &[Coord3::from_index(4)]);
+    let err = Tablebase::read(&bytes[..], &other_start).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+
+    // a different game
+    let err = Tablebase::read(&bytes[..], &Connect4::default()).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn children_values_consistent() {
+    let start = TTTBoard::default();
+    let tablebase = Tablebase::generate(&start);
+
+    for board in all_possible_boards(&start, 20, false) {
+        let entry = tablebase.get(&board).unwrap();
+        let children: Vec<_> = board
+            .children()
+            .unwrap()
+            .map(|(_, child)| tablebase.get(&child).unwrap().value.flip())
+            .collect();
+        assert_eq!(entry.value, OutcomeWDL::best(children.into_iter().into_internal()));
+    }
+}
@@ -0,0 +1,120 @@
+use board_game::util::tt::{Bound, Replacement, TTEntry, TranspositionTable};
+
+fn entry(depth: u32, value: i32) -> TTEntry<u8, i32> {
+    TTEntry {
+        depth,
+        value,
+        bound: Bound::Exact,
+        best_move: Some(0),
+    }
+}
+
+#[test]
+fn tt_basic() {
+    let tt = TranspositionTable::new(1024, Replacement::Always);
+    assert!(tt.capacity() >= 1024);
+    assert!(tt.is_empty());
+
+    tt.insert(5, entry(3, 10));
+    assert_eq!(tt.get(5), Some(entry(3, 10)));
+    assert_eq!(tt.get(6), None);
+    assert_eq!(tt.len(), 1);
+
+    tt.clear();
+    assert_eq!(tt.get(5), None);
+    assert!(tt.is_empty());
+}
+
+#[test]
+fn tt_replacement() {
+    // a single slot, so all keys collide
+    let always = TranspositionTable::new(1, Replacement::Always);
+    always.insert(1, entry(5, 1));
+    always.insert(2, entry(1, 2));
+    assert_eq!(always.get(1), None);
+    assert_eq!(always.get(2), Some(entry(1, 2)));
+
+    let depth = TranspositionTable::new(1, Replacement::DepthPreferred);
+    depth.insert(1, entry(5, 1));
+    depth.insert(2, entry(1, 2));
+    assert_eq!(depth.get(1), Some(entry(5, 1)));
+    assert_eq!(depth.get(2), None);
+
+    // the same key is always replaced
+    depth.insert(1, entry(2, 3));
+    assert_eq!(depth.get(1), Some(entry(2, 3)));
+
+    // entries from older generations are replaced
+    depth.new_generation();
+    assert_eq!(depth.get(1), Some(entry(2, 3)));
+    depth.insert(2, entry(1, 2));
+    assert_eq!(depth.get(2), Some(entry(1, 2)));
+}
+
+#[cfg(feature = "game_ttt")]
+#[test]
+fn solver_tt_ttt() {
+    use board_game::ai::solver::{solve_tt, solve_value, solve_value_tt};
+    use board_game::games::ttt::TTTBoard;
+    use board_game::util::game_stats::all_possible_boards;
+    use board_game::util::tiny::consistent_rng;
+
+    let boards = all_possible_boards(&TTTBoard::default(), 20, false);
+
+    // small table to force replacements, shared between all searches
+    for replacement in [Replacement::Always, Replacement::DepthPreferred] {
+        let tt = TranspositionTable::new(256, replacement);
+        let mut rng = consistent_rng();
+
+        for board in &boards {
+            let expected = solve_value(board, 20);
+            assert_eq!(expected, solve_value_tt(board, 20, &tt), "Mismatch for {}", board);
+
+            let result = solve_tt(board, 20, &tt, &mut rng);
+            assert_eq!(expected, result.value);
+            assert!(result.best_move.is_some());
+        }
+    }
+}
+
+#[cfg(feature = "game_ataxx")]
+#[test]
+fn minimax_tt_ataxx() {
+    use board_game::ai::minimax::{minimax_value, minimax_value_tt};
+    use board_game::board::Board;
+    use board_game::games::ataxx::AtaxxBoard;
+    use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+    use board_game::util::tiny::consistent_rng;
+
+    let heuristic = AtaxxTileHeuristic::default();
+    let tt = TranspositionTable::new(4096, Replacement::DepthPreferred);
+    let mut rng = consistent_rng();
+
+    let mut board = AtaxxBoard::diagonal(5);
+    while !board.is_done() {
+        tt.new_generation();
+        for depth in 0..4 {
+            assert_eq!(
+                minimax_value(&board, &heuristic, depth),
+                minimax_value_tt(&board, &heuristic, depth, &tt),
+                "Mismatch at depth {} for {}",
+                depth,
+                board
+            );
+        }
+        board.play_random_available_move(&mut rng).unwrap();
+    }
+}
+
+#[cfg(feature = "game_chess")]
+#[test]
+fn perft_tt_chess() {
+    use board_game::games::chess::ChessBoard;
+    use board_game::util::game_stats::{perft_naive, perft_tt};
+
+    let board = ChessBoard::default();
+    let tt = TranspositionTable::new(1024, Replacement::DepthPreferred);
+    for depth in 0..4 {
+        assert_eq!(perft_naive(&board, depth), perft_tt(&board, depth, &tt));
+    }
+}
@@ -3,10 +3,12 @@ use std::str::FromStr;
 use arimaa_engine_step::Action;
 use internal_iterator::InternalIterator;
 
-use board_game::board::{Board, BoardMoves, Outcome, Player};
+use board_game::board::{Board, BoardMoves, Outcome, Player, ZobristBoard};
 use board_game::games::arimaa::ArimaaBoard;
 
-use crate::board::board_test_main;
+use board_game::util::tiny::consistent_rng;
+
+use crate::board::{board_test_main, board_test_zobrist};
 
 #[test]
 fn empty() {
@@ -66,3 +68,27 @@ const GOLD_GOAL: &str = "
      +-----------------+
        a b c d e f g h
 ";
+
+#[test]
+fn zobrist() {
+    board_test_zobrist(&ArimaaBoard::from_str(BASIC_SETUP).unwrap(), 1);
+}
+
+#[test]
+fn zobrist_history_incremental() {
+    // rebuilding the board from its state recomputes the history part of the key from scratch
+    let mut rng = consistent_rng();
+
+    for _ in 0..4 {
+        let mut board = ArimaaBoard::from_str(BASIC_SETUP).unwrap();
+        for _ in 0..400 {
+            if board.is_done() {
+                break;
+            }
+            board.play(board.random_available_move(&mut rng).unwrap()).unwrap();
+
+            let rebuilt = ArimaaBoard::from_state(board.state().clone());
+            assert_eq!(rebuilt.zobrist(), board.zobrist());
+        }
+    }
+}
@@ -3,12 +3,12 @@ use itertools::Itertools;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 
-use board_game::board::{Board, BoardMoves, BoardSymmetry, Outcome, Player};
+use board_game::board::{Board, BoardMoves, BoardSymmetry, Outcome, Player, ZobristBoard};
 use board_game::games::ataxx::{AtaxxBoard, Move};
 use board_game::symmetry::D4Symmetry;
 use board_game::util::board_gen::random_board_with_moves;
 
-use crate::board::{board_perft_main, board_test_main};
+use crate::board::{board_perft_main, board_test_main, board_test_undo, board_test_zobrist};
 
 #[test]
 fn ataxx_empty() {
@@ -249,3 +249,25 @@ fn ataxx_big_perft() {
         )],
     );
 }
+
+#[test]
+fn ataxx_undo() {
+    for size in 2..AtaxxBoard::MAX_SIZE {
+        board_test_undo(&AtaxxBoard::diagonal(size));
+    }
+    board_test_undo(&AtaxxBoard::from_fen("2x3o/1x3oo/7/7/7/7/o3x2 o 0 1").unwrap());
+}
+
+#[test]
+fn ataxx_zobrist() {
+    board_test_zobrist(&AtaxxBoard::diagonal(5), 3);
+    board_test_zobrist(&AtaxxBoard::from_fen("2x3o/1x3oo/7/7/7/7/o3x2 o 0 1").unwrap(), 3);
+}
+
+#[test]
+fn ataxx_zobrist_copy_counter() {
+    // the copy counter can end the game in a draw, so it must be part of the key
+    let board = AtaxxBoard::from_fen("2x3o/1x3oo/7/7/7/7/o3x2 o 0 1").unwrap();
+    let counted = AtaxxBoard::from_fen("2x3o/1x3oo/7/7/7/7/o3x2 o 99 1").unwrap();
+    assert_ne!(board.zobrist(), counted.zobrist());
+}
@@ -1,11 +1,12 @@
 use std::str::FromStr;
 
-use chess::ChessMove;
+use chess::{ChessMove, Square};
+use internal_iterator::InternalIterator;
 
-use board_game::board::Board;
+use board_game::board::{Board, PlayError};
 use board_game::games::chess::{ChessBoard, Rules};
 
-use crate::board::{board_perft_main, board_test_main};
+use crate::board::{board_perft_main, board_test_main, board_test_zobrist, test_pseudo_legal_moves};
 
 //TODO add tests for 50 move and 3-move rule
 
@@ -65,3 +66,43 @@ fn chess_perft() {
         ],
     );
 }
+
+#[test]
+fn chess_pseudo_legal_leaves_king_in_check() {
+    // the bishop on e2 is pinned against the king by the rook on e8
+    let board = ChessBoard::new_without_history_fen("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1", Rules::default());
+    let pinned = ChessMove::new(Square::E2, Square::D3, None);
+
+    assert!(board.pseudo_legal_moves().unwrap().any(|mv| mv == pinned));
+    assert!(!board.is_available_move(pinned).unwrap());
+
+    let mut played = board.clone();
+    assert_eq!(Err(PlayError::UnavailableMove), played.try_play_pseudo(pinned));
+    assert_eq!(board, played);
+}
+
+#[test]
+fn chess_pseudo_legal() {
+    // positions with pins, en passant, castling through check and promotions
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ];
+
+    for fen in fens {
+        let board = ChessBoard::new_without_history_fen(fen, Rules::default());
+        test_pseudo_legal_moves(&board);
+        board.children().unwrap().for_each(|(_, child)| {
+            if !child.is_done() {
+                test_pseudo_legal_moves(&child)
+            }
+        });
+    }
+}
+
+#[test]
+fn chess_zobrist() {
+    board_test_zobrist(&ChessBoard::default(), 3);
+}
@@ -1,9 +1,10 @@
 use board_game::board::Outcome::WonBy;
-use board_game::board::{Board, Outcome, Player};
+use board_game::board::{Board, BoardSymmetry, Outcome, Player};
 use board_game::games::connect4::Connect4;
+use board_game::symmetry::D1Symmetry;
 use board_game::util::board_gen::board_with_moves;
 
-use crate::board::board_test_main;
+use crate::board::{board_test_main, board_test_undo, board_test_zobrist};
 
 #[test]
 fn empty() {
@@ -44,3 +45,26 @@ fn check_outcome(moves: &[u8], outcome: Option<Outcome>) {
 
     board_test_main(&board);
 }
+
+#[test]
+fn mirror() {
+    let sym = D1Symmetry::new(true);
+    let board = board_with_moves(Connect4::default(), &[0, 1, 1]);
+    let mirrored = board_with_moves(Connect4::default(), &[6, 5, 5]);
+
+    assert_eq!(mirrored, board.map(sym));
+    assert_eq!(mirrored.perfect_hash(), board.map(sym).perfect_hash());
+    assert_eq!(6, board.map_move(sym, 0));
+    board_test_main(&board);
+}
+
+#[test]
+fn undo() {
+    board_test_undo(&Connect4::default());
+    board_test_undo(&board_with_moves(Connect4::default(), &[3, 3, 3, 3, 3, 2]));
+}
+
+#[test]
+fn zobrist() {
+    board_test_zobrist(&Connect4::default(), 4);
+}
@@ -2,15 +2,18 @@ use std::str::FromStr;
 use std::time::Instant;
 
 use internal_iterator::InternalIterator;
+use rand::Rng;
 
-use board_game::board::{Board, BoardMoves, Outcome, PlayError, Player};
+use board_game::ai::minimax::minimax_all_moves;
+use board_game::ai::solver::SolverHeuristic;
+use board_game::board::{Board, BoardMoves, Outcome, PlayError, Player, UndoBoard};
 use board_game::games::go::{Direction, FlatTile, GoBoard, Komi, Move, Rules, Score, Tile, GO_MAX_SIZE};
 use board_game::util::board_gen::board_with_moves;
 use board_game::util::game_stats::perft;
 use board_game::util::tiny::consistent_rng;
 
 use crate::board::go_chains::{chains_test_main, chains_test_simulate};
-use crate::board::print_board_with_moves;
+use crate::board::{board_test_undo, board_test_zobrist, print_board_with_moves};
 use crate::util::test_sampler_uniform;
 
 #[test]
@@ -267,6 +270,14 @@ fn suicide_1() {
     assert_eq!(Ok(false), board.is_available_move(mv));
     assert_eq!(board.clone_and_play(mv), Err(PlayError::UnavailableMove));
 
+    // the move is still pseudo-legal, the suicide check only happens when playing it
+    assert!(board.pseudo_legal_moves().unwrap().any(|cand| cand == mv));
+    assert_eq!(board.clone().try_play_pseudo(mv), Err(PlayError::UnavailableMove));
+
+    // minimax should skip it
+    let result = minimax_all_moves(&board, &SolverHeuristic, 1);
+    assert!(!result.best_move.unwrap().contains(&mv));
+
     go_board_test_main(&board);
 }
 
@@ -523,3 +534,52 @@ fn go_board_test_main(board: &GoBoard) {
         });
     }
 }
+
+#[test]
+fn undo() {
+    for rules in [Rules::tromp_taylor(), Rules::cgos()] {
+        board_test_undo(&GoBoard::new(5, Komi::zero(), rules));
+        board_test_undo(&GoBoard::from_fen("...../...../...../..w../.b... b 0", rules).unwrap());
+    }
+}
+
+#[test]
+fn undo_random_walk() {
+    // mix playing and undoing so later moves run on undone chains, small boards to get plenty of captures
+    let mut rng = consistent_rng();
+
+    for rules in [Rules::tromp_taylor(), Rules::cgos()] {
+        for size in [3, 5] {
+            let mut board = GoBoard::new(size, Komi::zero(), rules);
+            let mut stack = vec![];
+
+            for _ in 0..2000 {
+                let mv = match board.random_available_place_move(&mut rng) {
+                    Ok(Some(mv)) if stack.is_empty() || rng.gen_bool(0.6) => mv,
+                    _ => match stack.pop() {
+                        Some((expected, undo)) => {
+                            board.undo(undo);
+                            board.assert_valid();
+                            assert_eq!(expected, board);
+                            continue;
+                        }
+                        None => Move::Pass,
+                    },
+                };
+
+                let before = board.clone();
+                let undo = board.play_undoable(mv).unwrap();
+                board.assert_valid();
+                stack.push((before, undo));
+            }
+        }
+    }
+}
+
+#[test]
+fn zobrist() {
+    for rules in [Rules::tromp_taylor(), Rules::cgos()] {
+        board_test_zobrist(&GoBoard::new(3, Komi::zero(), rules), 4);
+        board_test_zobrist(&GoBoard::new(9, Komi::zero(), rules), 2);
+    }
+}
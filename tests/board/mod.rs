@@ -1,5 +1,5 @@
 use std::collections::hash_map::RandomState;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::iter::FromIterator;
@@ -7,7 +7,7 @@ use std::time::Instant;
 
 use internal_iterator::InternalIterator;
 
-use board_game::board::{Board, BoardDone, PlayError};
+use board_game::board::{Board, BoardDone, PlayError, UndoBoard, ZobristBoard};
 use board_game::symmetry::Symmetry;
 use board_game::util::game_stats;
 use board_game::util::tiny::consistent_rng;
@@ -31,6 +31,8 @@ mod max_moves;
 
 #[cfg(feature = "game_oware")]
 mod oware;
+#[cfg(feature = "game_pig")]
+mod pig;
 #[cfg(feature = "game_sttt")]
 mod sttt;
 #[cfg(feature = "game_ttt")]
@@ -61,6 +63,7 @@ where
         test_done_board_errors(board);
     } else {
         test_available_match(board);
+        test_pseudo_legal_moves(board);
 
         if random_uniform {
             test_random_available_uniform(board);
@@ -185,6 +188,50 @@ where
     }
 }
 
+pub fn test_pseudo_legal_moves<B: Board>(board: &B)
+where
+    B::Move: Hash,
+{
+    println!("pseudo-legal moves:");
+
+    let all: HashSet<B::Move> = B::all_possible_moves().collect();
+    let available: HashSet<B::Move> = board.available_moves().unwrap().collect();
+    let pseudo: Vec<B::Move> = board.pseudo_legal_moves().unwrap().collect();
+
+    assert_eq!(
+        pseudo.len(),
+        HashSet::<_, RandomState>::from_iter(&pseudo).len(),
+        "Found duplicate move"
+    );
+    for mv in &available {
+        assert!(pseudo.contains(mv), "available move {:?} is not pseudo-legal", mv);
+    }
+
+    for &mv in &pseudo {
+        assert!(
+            all.contains(&mv),
+            "pseudo-legal move {:?} is not in all_possible_moves",
+            mv
+        );
+
+        let mut played = board.clone();
+        let result = played.try_play_pseudo(mv);
+
+        if available.contains(&mv) {
+            assert_eq!(Ok(()), result, "available move {:?} was rejected", mv);
+            assert_eq!(board.clone_and_play(mv).unwrap(), played);
+        } else {
+            assert_eq!(
+                Err(PlayError::UnavailableMove),
+                result,
+                "unavailable move {:?} was accepted",
+                mv
+            );
+            assert_eq!(board, &played, "rejected move {:?} changed the board", mv);
+        }
+    }
+}
+
 /// Test whether the random move distribution is uniform using
 /// [Pearson's chi-squared test](https://en.wikipedia.org/wiki/Pearson%27s_chi-squared_test).
 fn test_random_available_uniform<B: Board>(board: &B)
@@ -261,6 +308,88 @@ where
     }
 }
 
+/// Test that [UndoBoard] restores the board for all available moves,
+/// and for every prefix of a random game starting from `board`.
+pub fn board_test_undo<B: UndoBoard>(board: &B) {
+    println!("undo:");
+
+    if !board.is_done() {
+        board.available_moves().unwrap().for_each(|mv| {
+            let expected = board.clone_and_play(mv).unwrap();
+
+            let mut actual = board.clone();
+            let undo = actual.play_undoable(mv).unwrap();
+            assert_eq!(expected, actual, "play_undoable mismatch for move {:?}", mv);
+            actual.undo(undo);
+            assert_eq!(board, &actual, "undo mismatch for move {:?}", mv);
+
+            let child = actual.play_scoped(mv, |child| child.clone()).unwrap();
+            assert_eq!(expected, child, "play_scoped mismatch for move {:?}", mv);
+            assert_eq!(board, &actual, "play_scoped did not restore board for move {:?}", mv);
+        });
+    }
+
+    let mut rng = consistent_rng();
+    let mut curr = board.clone();
+    let mut history = vec![];
+
+    while !curr.is_done() {
+        let mv = curr.random_available_move(&mut rng).unwrap();
+        let undo = curr.play_undoable(mv).unwrap();
+        history.push((curr.clone(), undo));
+    }
+
+    while let Some((expected, undo)) = history.pop() {
+        assert_eq!(expected, curr);
+        curr.undo(undo);
+    }
+    assert_eq!(board, &curr);
+}
+
+/// Test that [ZobristBoard::zobrist] is consistent: equal boards reached through different move orders
+/// must have equal keys, and playing a move must change the key.
+/// All move sequences up to `depth` are visited, followed by some random games.
+/// Also checks that [game_stats::perft_zobrist] matches [game_stats::perft_naive].
+pub fn board_test_zobrist<B: ZobristBoard + Hash>(board: &B, depth: u32) {
+    println!("zobrist:");
+
+    fn check<B: ZobristBoard + Hash>(keys: &mut HashMap<B, B::Key>, board: &B) {
+        let key = board.zobrist();
+        let prev = *keys.entry(board.clone()).or_insert(key);
+        assert_eq!(prev, key, "Different keys for equal board {:?}", board);
+    }
+
+    fn visit<B: ZobristBoard + Hash>(keys: &mut HashMap<B, B::Key>, board: &B, depth: u32) {
+        check(keys, board);
+        if depth == 0 || board.is_done() {
+            return;
+        }
+        board.children().unwrap().for_each(|(mv, child)| {
+            assert_ne!(board.zobrist(), child.zobrist(), "Key did not change for move {:?}", mv);
+            visit(keys, &child, depth - 1);
+        });
+    }
+
+    let mut keys = HashMap::new();
+    visit(&mut keys, board, depth);
+
+    let mut rng = consistent_rng();
+    for _ in 0..10 {
+        let mut curr = board.clone();
+        while !curr.is_done() {
+            check(&mut keys, &curr);
+            curr.play_random_available_move(&mut rng).unwrap();
+        }
+        check(&mut keys, &curr);
+    }
+
+    assert_eq!(
+        game_stats::perft_naive(board, depth),
+        game_stats::perft_zobrist(board, depth),
+        "perft mismatch"
+    );
+}
+
 fn sort_moves<B: Board>(moves: &[B::Move]) -> Vec<B::Move> {
     B::all_possible_moves().filter(|&mv| moves.contains(&mv)).collect()
 }
@@ -2,7 +2,7 @@ use board_game::board::Board;
 use board_game::games::oware::OwareBoard;
 use board_game::util::board_gen::board_with_moves;
 
-use crate::board::board_test_main;
+use crate::board::{board_test_main, board_test_undo, board_test_zobrist};
 
 #[test]
 fn empty() {
@@ -26,3 +26,15 @@ fn done() {
     board_test_main(&board);
     assert!(board.is_done(), "Board should be done");
 }
+
+#[test]
+fn undo() {
+    board_test_undo(&OwareBoard::<6>::default());
+    board_test_undo(&OwareBoard::<4>::new(3));
+}
+
+#[test]
+fn zobrist() {
+    board_test_zobrist(&OwareBoard::<6>::default(), 4);
+    board_test_zobrist(&OwareBoard::<4>::new(3), 4);
+}
@@ -0,0 +1,75 @@
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::pig::{Move, PigBoard};
+use board_game::util::board_gen::board_with_moves;
+use board_game::util::game_stats::{perft, perft_naive};
+
+use crate::board::board_test_main;
+
+#[test]
+fn empty() {
+    board_test_main(&PigBoard::default());
+}
+
+#[test]
+fn chance() {
+    let board = board_with_moves(PigBoard::default(), &[Move::Roll]);
+    assert!(board.is_chance_node());
+    board_test_main(&board);
+
+    let probability: f32 = (1..=6).map(|value| board.chance_probability(Move::Die(value))).sum();
+    assert!((probability - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn turn() {
+    let board = board_with_moves(
+        PigBoard::default(),
+        &[Move::Roll, Move::Die(4), Move::Roll, Move::Die(3)],
+    );
+    assert_eq!(board.next_player(), Player::A);
+    assert_eq!(board.turn_total(), 7);
+    board_test_main(&board);
+
+    let held = board_with_moves(board.clone(), &[Move::Hold]);
+    assert_eq!(held.next_player(), Player::B);
+    assert_eq!(held.score(Player::A), 7);
+    assert_eq!(held.turn_total(), 0);
+    board_test_main(&held);
+
+    let lost = board_with_moves(board, &[Move::Roll, Move::Die(1)]);
+    assert_eq!(lost.next_player(), Player::B);
+    assert_eq!(lost.score(Player::A), 0);
+    board_test_main(&lost);
+}
+
+#[test]
+fn done() {
+    let board = board_with_moves(
+        PigBoard::new(10),
+        &[Move::Roll, Move::Die(6), Move::Roll, Move::Die(5), Move::Hold],
+    );
+    assert_eq!(board.outcome(), Some(Outcome::WonBy(Player::A)));
+    board_test_main(&board);
+}
+
+#[test]
+fn pig_perft() {
+    // depth 2: roll, then a one passes the turn (1 move) and other values allow both roll and hold (2 moves)
+    let expected = [1, 1, 11, 71, 481];
+    let board = PigBoard::default();
+
+    for (depth, &expected) in expected.iter().enumerate() {
+        assert_eq!(
+            perft(&board, depth as u32),
+            expected,
+            "perft mismatch at depth {}",
+            depth
+        );
+        assert_eq!(
+            perft_naive(&board, depth as u32),
+            expected,
+            "naive perft mismatch at depth {}",
+            depth
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use board_game::board::{Board, Outcome};
 use board_game::games::sttt::{board_from_compact_string, STTTBoard};
 
-use crate::board::board_test_main;
+use crate::board::{board_test_main, board_test_undo, board_test_zobrist};
 
 #[test]
 fn sttt_empty() {
@@ -29,3 +29,20 @@ fn sttt_draw() {
     assert_eq!(board.outcome(), Some(Outcome::Draw));
     board_test_main(&board)
 }
+
+#[test]
+fn sttt_undo() {
+    board_test_undo(&STTTBoard::default());
+    board_test_undo(&board_from_compact_string(
+        "                        o  .........               o    x  xxox        x   O  o  ",
+    ));
+}
+
+#[test]
+fn sttt_zobrist() {
+    board_test_zobrist(&STTTBoard::default(), 3);
+    board_test_zobrist(
+        &board_from_compact_string("                        o  .........               o    x  xxox        x   O  o  "),
+        3,
+    );
+}
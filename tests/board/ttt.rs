@@ -2,7 +2,7 @@ use board_game::board::{Board, Outcome, Player};
 use board_game::games::ttt::TTTBoard;
 use board_game::util::coord::Coord3;
 
-use crate::board::board_test_main;
+use crate::board::{board_test_main, board_test_undo, board_test_zobrist};
 
 #[test]
 fn empty() {
@@ -42,3 +42,17 @@ fn always_play_first() {
     board_test_main(&board);
     assert_eq!(board.outcome(), Some(Outcome::WonBy(Player::A)));
 }
+
+#[test]
+fn undo() {
+    board_test_undo(&TTTBoard::default());
+
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(1, 1)).unwrap();
+    board_test_undo(&board);
+}
+
+#[test]
+fn zobrist() {
+    board_test_zobrist(&TTTBoard::default(), 5);
+}
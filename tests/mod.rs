@@ -1,3 +1,4 @@
 pub mod ai;
 pub mod board;
+pub mod multi;
 pub mod util;
@@ -0,0 +1,58 @@
+use board_game::games::multi_ttt::MultiTTTBoard;
+use board_game::multi::board::{NBoard, PlayerIndex};
+use board_game::multi::bot_game;
+use board_game::multi::bot_game::NBotFactory;
+use board_game::multi::mcts::{n_mcts_build_tree, Backup, NMCTSBot};
+use board_game::multi::simple::NRandomBot;
+use board_game::multi::NBot;
+use board_game::util::coord::Coord8;
+use board_game::util::tiny::consistent_rng;
+
+#[test]
+fn mcts_immediate_win() {
+    // player 0 can complete a line at (2, 0)
+    let mut board = MultiTTTBoard::new(5, 3, 3);
+    for (x, y) in [(0, 0), (0, 2), (0, 4), (1, 0), (2, 2), (2, 4)] {
+        board.play(Coord8::from_xy(x, y)).unwrap();
+    }
+    println!("{}", board);
+    assert_eq!(board.next_player(), PlayerIndex::new(0));
+
+    for backup in [Backup::MaxN, Backup::Paranoid] {
+        let tree = n_mcts_build_tree(&board, 2_000, 1.0, backup, &mut consistent_rng());
+        assert_eq!(tree.best_move(), Coord8::from_xy(2, 0), "backup {:?}", backup);
+
+        let values = tree.values();
+        assert_eq!(values.len(), 3);
+        assert!(values[0] > 0.5, "backup {:?}, values {:?}", backup, values);
+    }
+}
+
+#[test]
+fn bot_game_mcts_vs_random() {
+    let bots: Vec<NBotFactory<MultiTTTBoard>> = vec![
+        Box::new(|| Box::new(NMCTSBot::new(200, 1.0, Backup::MaxN, consistent_rng())) as Box<dyn NBot<_>>),
+        Box::new(|| Box::new(NRandomBot::new(consistent_rng())) as Box<dyn NBot<_>>),
+        Box::new(|| Box::new(NRandomBot::new(consistent_rng())) as Box<dyn NBot<_>>),
+    ];
+
+    let result = bot_game::run(|| MultiTTTBoard::new(5, 3, 3), &bots, 4, Some(2), |_| {});
+    println!("{:?}", result);
+
+    assert_eq!(result.game_count, 12);
+    assert_eq!(result.replays.len(), 12);
+    assert_eq!(result.wins.len(), 3);
+
+    // every bot played every seat the same number of times
+    for bot in 0..3 {
+        for seat in 0..3 {
+            let count = result.replays.iter().filter(|r| r.seats[seat] == bot).count();
+            assert_eq!(count, 4);
+        }
+    }
+
+    let total_reward: f32 = result.average_rewards.iter().sum();
+    assert!((total_reward - 1.0).abs() < 1e-4);
+    assert!(result.average_rewards[0] > result.average_rewards[1]);
+    assert!(result.average_rewards[0] > result.average_rewards[2]);
+}
@@ -0,0 +1,4 @@
+#[cfg(feature = "game_multi_ttt")]
+mod mcts;
+#[cfg(feature = "game_multi_ttt")]
+mod multi_ttt;
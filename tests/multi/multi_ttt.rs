@@ -0,0 +1,67 @@
+use board_game::board::{BoardDone, PlayError};
+use board_game::games::multi_ttt::MultiTTTBoard;
+use board_game::multi::board::{NBoard, NOutcome, PlayerIndex};
+use board_game::util::coord::Coord8;
+
+fn board_with_moves(start: MultiTTTBoard, moves: &[(u8, u8)]) -> MultiTTTBoard {
+    let mut board = start;
+    for &(x, y) in moves {
+        board.play(Coord8::from_xy(x, y)).unwrap();
+    }
+    board
+}
+
+#[test]
+fn empty() {
+    let board = MultiTTTBoard::new(4, 3, 3);
+    println!("{}", board);
+
+    assert_eq!(board.player_count(), 3);
+    assert_eq!(board.next_player(), PlayerIndex::new(0));
+    assert_eq!(board.available_moves().unwrap().len(), 16);
+    assert_eq!(board.outcome(), None);
+}
+
+#[test]
+fn turns() {
+    let board = board_with_moves(MultiTTTBoard::new(4, 3, 3), &[(0, 0), (1, 0), (2, 0)]);
+    println!("{}", board);
+
+    assert_eq!(board.next_player(), PlayerIndex::new(0));
+    assert_eq!(board.tile(Coord8::from_xy(1, 0)), Some(PlayerIndex::new(1)));
+    assert_eq!(board.available_moves().unwrap().len(), 13);
+    assert_eq!(
+        board.clone().play(Coord8::from_xy(1, 0)),
+        Err(PlayError::UnavailableMove)
+    );
+    assert_eq!(
+        board.clone().play(Coord8::from_xy(5, 0)),
+        Err(PlayError::UnavailableMove)
+    );
+}
+
+#[test]
+fn win_diagonal() {
+    // player 2 completes the anti-diagonal
+    let moves = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 2), (1, 1), (3, 3), (0, 3), (0, 2)];
+    let board = board_with_moves(MultiTTTBoard::new(4, 3, 3), &moves);
+    println!("{}", board);
+
+    let outcome = board.outcome().unwrap();
+    assert_eq!(outcome, NOutcome::won_by(PlayerIndex::new(2), 3));
+    assert_eq!(outcome.winner(), Some(PlayerIndex::new(2)));
+    assert_eq!(board.available_moves(), Err(BoardDone));
+}
+
+#[test]
+fn draw() {
+    // fill a 3x3 board with 4 players without any line of 3
+    let moves = [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (1, 1), (1, 2), (2, 2), (0, 2)];
+    let board = board_with_moves(MultiTTTBoard::new(3, 4, 3), &moves);
+    println!("{}", board);
+
+    let outcome = board.outcome().unwrap();
+    assert_eq!(outcome, NOutcome::draw(4));
+    assert_eq!(outcome.winner(), None);
+    assert_eq!(outcome.rewards().iter().sum::<f32>(), 1.0);
+}